@@ -190,8 +190,7 @@ macro_rules! define_dep_nodes {
                         #[cfg(debug_assertions)]
                         {
                             if !dep_node.kind.can_reconstruct_query_key() &&
-                            (_tcx.sess.opts.debugging_opts.incremental_info ||
-                                _tcx.sess.opts.debugging_opts.query_dep_graph)
+                            _tcx.sess.opts.enable_dep_node_debug_strs()
                             {
                                 _tcx.dep_graph.register_dep_node_debug_str(dep_node, || {
                                     arg.to_debug_str(_tcx)
@@ -65,6 +65,25 @@ impl DepNodeColor {
     }
 }
 
+/// A node of the previous session which was re-executed in this one, or which explains why
+/// others were. See `DepGraph::rebuilt_nodes`.
+pub struct RebuiltNode {
+    pub node: DepNode,
+    pub state: RebuildState,
+    /// The indices of the nodes which kept this one from being marked green.
+    pub causes: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RebuildState {
+    /// The node was re-executed and its result changed, or the input it stands for changed.
+    Red,
+    /// The node was re-executed, but its result turned out to be the same as before.
+    Green,
+    /// The node couldn't be evaluated in this session, e.g. because its item was removed.
+    Missing,
+}
+
 struct DepGraphData {
     /// The new encoding of the dependency graph, optimized for red/green
     /// tracking. The `current` field is the dependency graph of only the
@@ -148,6 +167,11 @@ impl DepGraph {
         DepGraphQuery::new(&nodes[..], &edges[..])
     }
 
+    /// Like `query`, but over the dep-graph loaded from the previous session.
+    pub fn previous_query(&self) -> DepGraphQuery {
+        self.data.as_ref().unwrap().previous.query()
+    }
+
     pub fn assert_ignored(&self) {
         if let Some(..) = self.data {
             ty::tls::with_context_opt(|icx| {
@@ -882,6 +906,65 @@ impl DepGraph {
         self.node_color(dep_node).map(|c| c.is_green()).unwrap_or(false)
     }
 
+    /// Explains, for `-Z incremental-rebuild-report`, why the nodes of the previous session which
+    /// couldn't be marked green had to be re-executed.
+    ///
+    /// A node is re-executed when one of its dependencies isn't green: the `causes` of each
+    /// returned node are its red dependencies or, if there are none, the ones which couldn't be
+    /// evaluated at all. Following the causes leads to the inputs which changed: red nodes without
+    /// causes of their own (like the `Hir` of an edited item) and `Missing` nodes (like the `Hir`
+    /// of a removed item).
+    pub fn rebuilt_nodes(&self) -> Vec<RebuiltNode> {
+        let data = match self.data {
+            Some(ref data) => data,
+            None => return vec![],
+        };
+        let causes_of = |prev_index| -> Vec<SerializedDepNodeIndex> {
+            let deps = data.previous.edge_targets_from(prev_index);
+            let color = |dep| data.colors.get(dep);
+            let red: Vec<_> =
+                deps.iter().cloned().filter(|&dep| color(dep) == Some(DepNodeColor::Red)).collect();
+            if !red.is_empty() {
+                return red;
+            }
+            deps.iter().cloned().filter(|&dep| color(dep).is_none()).collect()
+        };
+
+        let mut nodes = vec![];
+        let mut causes = vec![];
+        let mut node_indices = FxHashMap::default();
+        for prev_index in data.colors.values.indices() {
+            let node = data.previous.index_to_node(prev_index);
+            let (state, node_causes) = match data.colors.get(prev_index) {
+                Some(DepNodeColor::Red) => (RebuildState::Red, causes_of(prev_index)),
+                Some(DepNodeColor::Green(_)) if !node.kind.is_eval_always() => {
+                    // Green nodes whose dependencies were all green weren't re-executed.
+                    let node_causes = causes_of(prev_index);
+                    if node_causes.is_empty() {
+                        continue;
+                    }
+                    (RebuildState::Green, node_causes)
+                }
+                _ => continue,
+            };
+            node_indices.insert(prev_index, nodes.len());
+            nodes.push(RebuiltNode { node, state, causes: vec![] });
+            causes.push(node_causes);
+        }
+
+        for (index, node_causes) in causes.into_iter().enumerate() {
+            for prev_index in node_causes {
+                let cause = *node_indices.entry(prev_index).or_insert_with(|| {
+                    let node = data.previous.index_to_node(prev_index);
+                    nodes.push(RebuiltNode { node, state: RebuildState::Missing, causes: vec![] });
+                    nodes.len() - 1
+                });
+                nodes[index].causes.push(cause);
+            }
+        }
+        nodes
+    }
+
     // This method loads all on-disk cacheable query results into memory, so
     // they can be written out to the new cache file again. Most query results
     // will already be in memory but in the case where we marked something as
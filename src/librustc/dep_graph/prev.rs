@@ -1,4 +1,5 @@
 use super::dep_node::DepNode;
+use super::query::DepGraphQuery;
 use super::serialized::{SerializedDepGraph, SerializedDepNodeIndex};
 use crate::ich::Fingerprint;
 use rustc_data_structures::fx::FxHashMap;
@@ -24,6 +25,20 @@ impl PreviousDepGraph {
         self.data.edge_targets_from(dep_node_index)
     }
 
+    /// Builds a `DepGraphQuery` over this graph, with the same edges as
+    /// `DepGraph::query`: from each node to the nodes it read.
+    pub fn query(&self) -> DepGraphQuery {
+        let nodes: Vec<_> = self.data.nodes.iter().cloned().collect();
+        let mut edges = Vec::new();
+        for (from, &node) in self.data.nodes.iter_enumerated() {
+            for &to in self.data.edge_targets_from(from) {
+                edges.push((node, self.data.nodes[to]));
+            }
+        }
+
+        DepGraphQuery::new(&nodes[..], &edges[..])
+    }
+
     #[inline]
     pub fn index_to_node(&self, dep_node_index: SerializedDepNodeIndex) -> DepNode {
         self.data.nodes[dep_node_index]
@@ -125,6 +125,7 @@ rustc_queries! {
         query mir_const_qualif(key: DefId) -> mir::ConstQualifs {
             desc { |tcx| "const checking `{}`", tcx.def_path_str(key) }
             cache_on_disk_if { key.is_local() }
+            uses_steal
         }
 
         /// Fetch the MIR for a given `DefId` right after it's built - this includes
@@ -153,6 +154,7 @@ rustc_queries! {
         /// for codegen. This is also the only query that can fetch non-local MIR, at present.
         query optimized_mir(key: DefId) -> &'tcx mir::BodyAndCache<'tcx> {
             cache_on_disk_if { key.is_local() }
+            uses_steal
             load_cached(tcx, id) {
                 let mir: Option<crate::mir::BodyAndCache<'tcx>>
                     = tcx.queries.on_disk_cache.try_load_query_result(tcx, id);
@@ -164,8 +166,26 @@ rustc_queries! {
             }
         }
 
+        /// The functions called by `key`, with their generic arguments, as
+        /// found in its MIR before optimizations. Used by the MIR inliner.
+        query mir_inliner_callees(key: DefId) -> &'tcx [(DefId, SubstsRef<'tcx>)] {
+            desc { |tcx| "computing all function calls in `{}`", tcx.def_path_str(key) }
+        }
+
+        /// Whether `key.0` can (transitively) call `key.1`. Used by the MIR
+        /// inliner to avoid query cycles between the optimized MIR of local functions.
+        query mir_callgraph_reachable(key: (ty::Instance<'tcx>, DefId)) -> bool {
+            no_force
+            desc { |tcx|
+                "computing if `{}` (transitively) calls `{}`",
+                key.0,
+                tcx.def_path_str(key.1)
+            }
+        }
+
         query promoted_mir(key: DefId) -> &'tcx IndexVec<mir::Promoted, mir::BodyAndCache<'tcx>> {
             cache_on_disk_if { key.is_local() }
+            uses_steal
             load_cached(tcx, id) {
                 let promoted: Option<
                     rustc_index::vec::IndexVec<
@@ -225,14 +245,20 @@ rustc_queries! {
         /// predicates (where-clauses) directly defined on it. This is
         /// equal to the `explicit_predicates_of` predicates plus the
         /// `inferred_outlives_of` predicates.
-        query predicates_defined_on(_: DefId) -> ty::GenericPredicates<'tcx> {}
+        query predicates_defined_on(key: DefId) -> ty::GenericPredicates<'tcx> {
+            cache_on_disk_if { key.is_local() }
+        }
 
         /// Returns the predicates written explicitly by the user.
-        query explicit_predicates_of(_: DefId) -> ty::GenericPredicates<'tcx> {}
+        query explicit_predicates_of(key: DefId) -> ty::GenericPredicates<'tcx> {
+            cache_on_disk_if { key.is_local() }
+        }
 
         /// Returns the inferred outlives predicates (e.g., for `struct
         /// Foo<'a, T> { x: &'a T }`, this would return `T: 'a`).
-        query inferred_outlives_of(_: DefId) -> &'tcx [(ty::Predicate<'tcx>, Span)] {}
+        query inferred_outlives_of(key: DefId) -> &'tcx [(ty::Predicate<'tcx>, Span)] {
+            cache_on_disk_if { key.is_local() }
+        }
 
         /// Maps from the `DefId` of a trait to the list of
         /// super-predicates. This is a subset of the full list of
@@ -242,6 +268,7 @@ rustc_queries! {
         /// additional acyclicity requirements).
         query super_predicates_of(key: DefId) -> ty::GenericPredicates<'tcx> {
             desc { |tcx| "computing the supertraits of `{}`", tcx.def_path_str(key) }
+            cache_on_disk_if { key.is_local() }
         }
 
         /// To avoid cycles within the predicates of a single item we compute
@@ -344,7 +371,9 @@ rustc_queries! {
             desc { |tcx| "collecting associated items of {}", tcx.def_path_str(key) }
         }
 
-        query impl_trait_ref(_: DefId) -> Option<ty::TraitRef<'tcx>> {}
+        query impl_trait_ref(key: DefId) -> Option<ty::TraitRef<'tcx>> {
+            cache_on_disk_if { key.is_local() }
+        }
         query impl_polarity(_: DefId) -> ty::ImplPolarity {}
 
         query issue33140_self_ty(_: DefId) -> Option<ty::Ty<'tcx>> {}
@@ -364,13 +393,16 @@ rustc_queries! {
         query unsafety_check_result(key: DefId) -> mir::UnsafetyCheckResult {
             desc { |tcx| "unsafety-checking `{}`", tcx.def_path_str(key) }
             cache_on_disk_if { key.is_local() }
+            uses_steal
         }
 
         /// HACK: when evaluated, this reports a "unsafe derive on repr(packed)" error
         query unsafe_derive_on_repr_packed(_: DefId) -> () {}
 
         /// The signature of functions and closures.
-        query fn_sig(_: DefId) -> ty::PolyFnSig<'tcx> {}
+        query fn_sig(key: DefId) -> ty::PolyFnSig<'tcx> {
+            cache_on_disk_if { key.is_local() }
+        }
     }
 
     Other {
@@ -466,11 +498,8 @@ rustc_queries! {
         /// additional requirements that the closure's creator must verify.
         query mir_borrowck(key: DefId) -> &'tcx mir::BorrowCheckResult<'tcx> {
             desc { |tcx| "borrow-checking `{}`", tcx.def_path_str(key) }
-            cache_on_disk_if(tcx, opt_result) {
-                key.is_local()
-                    && (tcx.is_closure(key)
-                        || opt_result.map_or(false, |r| !r.concrete_opaque_types.is_empty()))
-            }
+            cache_on_disk_if { key.is_local() }
+            uses_steal
         }
     }
 
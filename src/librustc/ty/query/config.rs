@@ -29,6 +29,9 @@ pub trait QueryConfig<'tcx> {
 pub(crate) trait QueryAccessors<'tcx>: QueryConfig<'tcx> {
     const ANON: bool;
     const EVAL_ALWAYS: bool;
+    /// Whether the query reads `Steal` results, so that it can't always be
+    /// recomputed after its result was loaded from the on-disk cache.
+    const USES_STEAL: bool;
 
     type Cache: QueryCache<Self::Key, Self::Value>;
 
@@ -162,7 +162,8 @@ impl<'sess> OnDiskCache<'sess> {
         tcx.dep_graph.with_ignore(|| {
             // Allocate `SourceFileIndex`es.
             let (file_to_file_index, file_index_to_stable_id) = {
-                let files = tcx.sess.source_map().files();
+                let source_map = tcx.sess.source_map();
+                let files = source_map.files();
                 let mut file_to_file_index =
                     FxHashMap::with_capacity_and_hasher(files.len(), Default::default());
                 let mut file_index_to_stable_id =
@@ -172,7 +173,7 @@ impl<'sess> OnDiskCache<'sess> {
                     let index = SourceFileIndex(index as u32);
                     let file_ptr: *const SourceFile = &**file as *const _;
                     file_to_file_index.insert(file_ptr, index);
-                    file_index_to_stable_id.insert(index, StableSourceFileId::new(&file));
+                    file_index_to_stable_id.insert(index, source_map.stable_id(&file));
                 }
 
                 (file_to_file_index, file_index_to_stable_id)
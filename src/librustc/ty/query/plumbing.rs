@@ -439,7 +439,7 @@ impl<'tcx> TyCtxt<'tcx> {
             key,
             |value, index| {
                 if unlikely!(self.prof.enabled()) {
-                    self.prof.query_cache_hit(index.into());
+                    self.prof.query_cache_hit(Q::NAME, index.into());
                 }
                 #[cfg(debug_assertions)]
                 {
@@ -494,7 +494,7 @@ impl<'tcx> TyCtxt<'tcx> {
         }
 
         if Q::ANON {
-            let prof_timer = self.prof.query_provider();
+            let prof_timer = self.prof.query_provider(Q::NAME);
 
             let ((result, dep_node_index), diagnostics) = with_diagnostics(|diagnostics| {
                 self.start_query(job.id, diagnostics, |tcx| {
@@ -564,7 +564,7 @@ impl<'tcx> TyCtxt<'tcx> {
         let result = if Q::cache_on_disk(self, key.clone(), None)
             && self.sess.opts.debugging_opts.incremental_queries
         {
-            let prof_timer = self.prof.incr_cache_loading();
+            let prof_timer = self.prof.incr_cache_loading(Q::NAME);
             let result = Q::try_load_from_disk(self, prev_dep_node_index);
             prof_timer.finish_with_query_invocation_id(dep_node_index.into());
 
@@ -582,11 +582,18 @@ impl<'tcx> TyCtxt<'tcx> {
         };
 
         let result = if let Some(result) = result {
+            // If `-Zincremental-verify-cache` is specified, recompute a sample
+            // of the results loaded from the cache and compare them.
+            if let Some(percent) = self.sess.opts.debugging_opts.incremental_verify_cache {
+                if !Q::USES_STEAL && dep_node.hash.to_smaller_hash() % 100 < percent as u64 {
+                    self.incremental_verify_cached_result::<Q>(key, &result, dep_node);
+                }
+            }
             result
         } else {
             // We could not load a result from the on-disk cache, so
             // recompute.
-            let prof_timer = self.prof.query_provider();
+            let prof_timer = self.prof.query_provider(Q::NAME);
 
             // The dep-graph for this computation is already in-place.
             let result = self.dep_graph.with_ignore(|| Q::compute(self, key));
@@ -605,6 +612,44 @@ impl<'tcx> TyCtxt<'tcx> {
         result
     }
 
+    #[inline(never)]
+    #[cold]
+    fn incremental_verify_cached_result<Q: QueryDescription<'tcx>>(
+        self,
+        key: Q::Key,
+        cached: &Q::Value,
+        dep_node: &DepNode,
+    ) {
+        use crate::ich::Fingerprint;
+
+        debug!("BEGIN verify_cached_result({:?})", dep_node);
+
+        // The diagnostics of the query have already been replayed from the
+        // cache, and the handler doesn't emit the same diagnostic twice.
+        let recomputed = self.dep_graph.with_ignore(|| Q::compute(self, key.clone()));
+
+        let mut hcx = self.create_stable_hashing_context();
+        let cached_hash = Q::hash_result(&mut hcx, cached).unwrap_or(Fingerprint::ZERO);
+        let recomputed_hash = Q::hash_result(&mut hcx, &recomputed).unwrap_or(Fingerprint::ZERO);
+        debug!("END verify_cached_result({:?})", dep_node);
+
+        if cached_hash != recomputed_hash {
+            self.sess
+                .struct_err(&format!(
+                    "the cached result of {} differs from a fresh computation",
+                    Q::describe(self, key.clone())
+                ))
+                .note(&format!("dep-node: {:?}", dep_node))
+                .note(&format!("query key: {:?}", key))
+                .note(&format!(
+                    "fingerprint of the cached result: {}, of the recomputed one: {}",
+                    cached_hash, recomputed_hash
+                ))
+                .note("this is a bug in incremental compilation, please report it")
+                .emit();
+        }
+    }
+
     #[inline(never)]
     #[cold]
     fn incremental_verify_ich<Q: QueryDescription<'tcx>>(
@@ -654,7 +699,7 @@ impl<'tcx> TyCtxt<'tcx> {
             dep_node
         );
 
-        let prof_timer = self.prof.query_provider();
+        let prof_timer = self.prof.query_provider(Q::NAME);
 
         let ((result, dep_node_index), diagnostics) = with_diagnostics(|diagnostics| {
             self.start_query(job.id, diagnostics, |tcx| {
@@ -714,7 +759,7 @@ impl<'tcx> TyCtxt<'tcx> {
                 let _ = self.get_query::<Q>(DUMMY_SP, key);
             }
             Some((_, dep_node_index)) => {
-                self.prof.query_cache_hit(dep_node_index.into());
+                self.prof.query_cache_hit(Q::NAME, dep_node_index.into());
             }
         }
     }
@@ -790,6 +835,18 @@ macro_rules! is_eval_always {
     };
 }
 
+macro_rules! is_uses_steal {
+    ([]) => {{
+        false
+    }};
+    ([uses_steal $($rest:tt)*]) => {{
+        true
+    }};
+    ([$other:ident $(($($other_args:tt)*))* $(, $($modifiers:tt)*)*]) => {
+        is_uses_steal!([$($($modifiers)*)*])
+    };
+}
+
 macro_rules! query_storage {
     ([][$K:ty, $V:ty]) => {
         <<$K as Key>::CacheSelector as CacheSelector<$K, $V>>::Cache
@@ -971,6 +1028,7 @@ macro_rules! define_queries_inner {
         impl<$tcx> QueryAccessors<$tcx> for queries::$name<$tcx> {
             const ANON: bool = is_anon!([$($modifiers)*]);
             const EVAL_ALWAYS: bool = is_eval_always!([$($modifiers)*]);
+            const USES_STEAL: bool = is_uses_steal!([$($modifiers)*]);
 
             type Cache = query_storage!([$($modifiers)*][$K, $V]);
 
@@ -34,7 +34,11 @@ impl<'a> LlvmSelfProfiler<'a> {
     fn before_pass_callback(&'a mut self, pass_name: &str, ir_name: &str) {
         let event_id = llvm_args_to_string_id(&self.profiler, pass_name, ir_name);
 
-        self.stack.push(TimingGuard::start(&self.profiler, self.llvm_pass_event_kind, event_id));
+        let timing_guard = TimingGuard::start(&self.profiler, self.llvm_pass_event_kind, event_id)
+            .with_trace(&self.profiler, "LLVM pass", || {
+                format!("{}({})", pass_name, ir_name).into()
+            });
+        self.stack.push(timing_guard);
     }
     fn after_pass_callback(&mut self) {
         self.stack.pop();
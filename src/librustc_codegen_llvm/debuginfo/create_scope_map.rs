@@ -2,10 +2,13 @@ use super::metadata::file_metadata;
 use super::utils::{span_start, DIB};
 use rustc_codegen_ssa::mir::debuginfo::{DebugScope, FunctionDebugContext};
 
+use crate::abi::FnAbi;
 use crate::common::CodegenCx;
 use crate::llvm;
-use crate::llvm::debuginfo::{DIScope, DISubprogram};
+use crate::llvm::debuginfo::{DILocation, DIScope, DISubprogram};
 use rustc::mir::{Body, SourceScope};
+use rustc::ty::layout::FnAbiExt;
+use rustc::ty::{self, Instance};
 
 use libc::c_uint;
 
@@ -14,12 +17,14 @@ use rustc_span::Pos;
 use rustc_index::bit_set::BitSet;
 use rustc_index::vec::Idx;
 
-/// Produces DIScope DIEs for each MIR Scope which has variables defined in it.
+/// Produces DIScope DIEs for each MIR Scope which has variables defined in it,
+/// or which is the root of a function body inlined by the MIR inliner.
 pub fn compute_mir_scopes(
-    cx: &CodegenCx<'ll, '_>,
-    mir: &Body<'_>,
+    cx: &CodegenCx<'ll, 'tcx>,
+    instance: Instance<'tcx>,
+    mir: &Body<'tcx>,
     fn_metadata: &'ll DISubprogram,
-    debug_context: &mut FunctionDebugContext<&'ll DIScope>,
+    debug_context: &mut FunctionDebugContext<&'ll DIScope, &'ll DILocation>,
 ) {
     // Find all the scopes with variables defined in them.
     let mut has_variables = BitSet::new_empty(mir.source_scopes.len());
@@ -32,16 +37,17 @@ pub fn compute_mir_scopes(
     // Instantiate all scopes.
     for idx in 0..mir.source_scopes.len() {
         let scope = SourceScope::new(idx);
-        make_mir_scope(cx, &mir, fn_metadata, &has_variables, debug_context, scope);
+        make_mir_scope(cx, instance, &mir, fn_metadata, &has_variables, debug_context, scope);
     }
 }
 
 fn make_mir_scope(
-    cx: &CodegenCx<'ll, '_>,
-    mir: &Body<'_>,
+    cx: &CodegenCx<'ll, 'tcx>,
+    instance: Instance<'tcx>,
+    mir: &Body<'tcx>,
     fn_metadata: &'ll DISubprogram,
     has_variables: &BitSet<SourceScope>,
-    debug_context: &mut FunctionDebugContext<&'ll DISubprogram>,
+    debug_context: &mut FunctionDebugContext<&'ll DISubprogram, &'ll DILocation>,
     scope: SourceScope,
 ) {
     if debug_context.scopes[scope].is_valid() {
@@ -50,22 +56,23 @@ fn make_mir_scope(
 
     let scope_data = &mir.source_scopes[scope];
     let parent_scope = if let Some(parent) = scope_data.parent_scope {
-        make_mir_scope(cx, mir, fn_metadata, has_variables, debug_context, parent);
+        make_mir_scope(cx, instance, mir, fn_metadata, has_variables, debug_context, parent);
         debug_context.scopes[parent]
     } else {
         // The root is the function itself.
         let loc = span_start(cx, mir.span);
         debug_context.scopes[scope] = DebugScope {
             scope_metadata: Some(fn_metadata),
+            inlined_at: None,
             file_start_pos: loc.file.start_pos,
             file_end_pos: loc.file.end_pos,
         };
         return;
     };
 
-    if !has_variables.contains(scope) {
-        // Do not create a DIScope if there are no variables
-        // defined in this MIR Scope, to avoid debuginfo bloat.
+    if !has_variables.contains(scope) && scope_data.inlined.is_none() {
+        // Do not create a DIScope if there are no variables defined in this
+        // MIR `SourceScope`, and it's not `inlined`, to avoid debuginfo bloat.
         debug_context.scopes[scope] = parent_scope;
         return;
     }
@@ -73,17 +80,40 @@ fn make_mir_scope(
     let loc = span_start(cx, scope_data.span);
     let file_metadata = file_metadata(cx, &loc.file.name, debug_context.defining_crate);
 
-    let scope_metadata = unsafe {
-        Some(llvm::LLVMRustDIBuilderCreateLexicalBlock(
-            DIB(cx),
-            parent_scope.scope_metadata.unwrap(),
-            file_metadata,
-            loc.line as c_uint,
-            loc.col.to_usize() as c_uint,
-        ))
+    let scope_metadata = match scope_data.inlined {
+        Some((callee, _)) => {
+            let callee = cx.tcx.subst_and_normalize_erasing_regions(
+                instance.substs,
+                ty::ParamEnv::reveal_all(),
+                &callee,
+            );
+            let callee_fn_abi = FnAbi::of_instance(cx, callee, &[]);
+            cx.dbg_scope_fn(callee, &callee_fn_abi, scope_data.span, None)
+        }
+        None => unsafe {
+            llvm::LLVMRustDIBuilderCreateLexicalBlock(
+                DIB(cx),
+                parent_scope.scope_metadata.unwrap(),
+                file_metadata,
+                loc.line as c_uint,
+                loc.col.to_usize() as c_uint,
+            )
+        },
     };
+
+    // The body of an inlined function is positioned, as a whole, at the
+    // location of its call site in the parent scope.
+    let inlined_at = scope_data.inlined.map(|(_, callsite_span)| {
+        cx.create_debug_loc(
+            parent_scope.scope_metadata.unwrap(),
+            parent_scope.inlined_at,
+            callsite_span,
+        )
+    });
+
     debug_context.scopes[scope] = DebugScope {
-        scope_metadata,
+        scope_metadata: Some(scope_metadata),
+        inlined_at: inlined_at.or(parent_scope.inlined_at),
         file_start_pos: loc.file.start_pos,
         file_end_pos: loc.file.end_pos,
     };
@@ -1,16 +1,21 @@
 use super::metadata::UNKNOWN_COLUMN_NUMBER;
-use super::utils::{debug_context, span_start};
+use super::utils::span_start;
 
 use crate::common::CodegenCx;
-use crate::llvm::debuginfo::DIScope;
-use crate::llvm::{self, Value};
+use crate::llvm;
+use crate::llvm::debuginfo::{DILocation, DIScope};
 use rustc_codegen_ssa::traits::*;
 
 use libc::c_uint;
 use rustc_span::{Pos, Span};
 
 impl CodegenCx<'ll, '_> {
-    pub fn create_debug_loc(&self, scope: &'ll DIScope, span: Span) -> &'ll Value {
+    pub fn create_debug_loc(
+        &self,
+        scope: &'ll DIScope,
+        inlined_at: Option<&'ll DILocation>,
+        span: Span,
+    ) -> &'ll DILocation {
         let loc = span_start(self, span);
 
         // For MSVC, set the column number to zero.
@@ -24,11 +29,10 @@ impl CodegenCx<'ll, '_> {
 
         unsafe {
             llvm::LLVMRustDIBuilderCreateDebugLocation(
-                debug_context(self).llcontext,
                 loc.line as c_uint,
                 col_used,
                 scope,
-                None,
+                inlined_at,
             )
         }
     }
@@ -50,6 +50,7 @@ use rustc_span::Span;
 
 use std::cmp;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 pub fn bin_op_to_icmp_predicate(op: hir::BinOpKind, signed: bool) -> IntPredicate {
@@ -522,7 +523,7 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
 
         ongoing_codegen.codegen_finished(tcx);
 
-        finalize_tcx(tcx);
+        finalize_tcx(tcx, None);
 
         ongoing_codegen.check_for_errors(tcx.sess);
 
@@ -730,7 +731,7 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
 
     ongoing_codegen.check_for_errors(tcx.sess);
 
-    finalize_tcx(tcx);
+    finalize_tcx(tcx, Some(&codegen_units[..]));
 
     ongoing_codegen.into_inner()
 }
@@ -782,9 +783,11 @@ impl<B: ExtraBackendMethods> Drop for AbortCodegenOnDrop<B> {
     }
 }
 
-fn finalize_tcx(tcx: TyCtxt<'_>) {
+fn finalize_tcx<'tcx>(tcx: TyCtxt<'tcx>, codegen_units: Option<&[Arc<CodegenUnit<'tcx>>]>) {
     tcx.sess.time("assert_dep_graph", || ::rustc_incremental::assert_dep_graph(tcx));
-    tcx.sess.time("serialize_dep_graph", || ::rustc_incremental::save_dep_graph(tcx));
+    tcx.sess.time("serialize_dep_graph", || {
+        ::rustc_incremental::save_dep_graph(tcx, codegen_units)
+    });
 
     // We assume that no queries are run past here. If there are new queries
     // after this point, they'll show up as "<unknown>" in self-profiling data.
@@ -13,8 +13,8 @@ use super::operand::OperandValue;
 use super::place::PlaceRef;
 use super::{FunctionCx, LocalRef};
 
-pub struct FunctionDebugContext<D> {
-    pub scopes: IndexVec<mir::SourceScope, DebugScope<D>>,
+pub struct FunctionDebugContext<S, L> {
+    pub scopes: IndexVec<mir::SourceScope, DebugScope<S, L>>,
     pub defining_crate: CrateNum,
 }
 
@@ -38,15 +38,19 @@ pub struct PerLocalVarDebugInfo<'tcx, D> {
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct DebugScope<D> {
-    pub scope_metadata: Option<D>,
+pub struct DebugScope<S, L> {
+    pub scope_metadata: Option<S>,
+
+    /// Call site location, if this scope was inlined from another function.
+    pub inlined_at: Option<L>,
+
     // Start and end offsets of the file to which this DIScope belongs.
     // These are used to quickly determine whether some span refers to the same file.
     pub file_start_pos: BytePos,
     pub file_end_pos: BytePos,
 }
 
-impl<D> DebugScope<D> {
+impl<S, L> DebugScope<S, L> {
     pub fn is_valid(&self) -> bool {
         self.scope_metadata.is_some()
     }
@@ -56,7 +60,7 @@ impl<'a, 'tcx, Bx: BuilderMethods<'a, 'tcx>> FunctionCx<'a, 'tcx, Bx> {
     pub fn set_debug_loc(&self, bx: &mut Bx, source_info: mir::SourceInfo) {
         let (scope, span) = self.debug_loc(source_info);
         if let Some(scope) = scope {
-            bx.set_source_location(scope, span);
+            bx.set_source_location(scope, self.inlined_at(source_info.scope), span);
         }
     }
 
@@ -84,6 +88,12 @@ impl<'a, 'tcx, Bx: BuilderMethods<'a, 'tcx>> FunctionCx<'a, 'tcx, Bx> {
         }
     }
 
+    /// Returns the call site location of the function which the MIR inliner
+    /// inlined `scope` from, if any.
+    fn inlined_at(&self, scope: mir::SourceScope) -> Option<Bx::DILocation> {
+        self.debug_context.as_ref()?.scopes[scope].inlined_at
+    }
+
     // DILocations inherit source file name from the parent DIScope.  Due to macro expansions
     // it may so happen that the current span belongs to a different file than the DIScope
     // corresponding to span's containing source scope.  If so, we need to create a DIScope
@@ -280,6 +290,7 @@ impl<'a, 'tcx, Bx: BuilderMethods<'a, 'tcx>> FunctionCx<'a, 'tcx, Bx> {
                     bx.dbg_var_addr(
                         dbg_var,
                         scope,
+                        self.inlined_at(var.source_info.scope),
                         base.llval,
                         direct_offset,
                         &indirect_offsets,
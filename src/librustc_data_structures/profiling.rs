@@ -79,24 +79,51 @@
 //! invocation) and allocate the corresponding strings together with a mapping
 //! for `DepNodeIndex as StringId`.
 //!
+//!
+//! ## Summary
+//!
+//! With `-Z self-profile-summary`, the `SelfProfiler` also aggregates the
+//! events it records, so that a table of the time spent in each query and
+//! generic activity can be printed at the end of the compilation without any
+//! post-processing. Since `event_id`s are only mapped to strings at the very
+//! end, the summary is keyed by the static labels passed when recording the
+//! events (the query name for query events) instead. The self time of an event
+//! is its duration minus the durations of the events recorded while it was
+//! running on the same thread, which are tracked with a thread-local stack.
+//!
+//!
+//! ## Chrome Trace Events
+//!
+//! With `-Z self-profile-format=chrome`, the events are instead written as a
+//! single JSON file in the [trace event format][te] understood by Chrome's
+//! tracing UI and Perfetto, with one track per thread. For the same reason as
+//! the summary, the events are kept in memory with their labels (and function
+//! arguments) until the end of the compilation, rather than their `event_id`s.
+//!
+//! [te]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
 //! [mm]: https://github.com/rust-lang/measureme/
 
 use crate::cold_path;
 use crate::fx::FxHashMap;
 
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
+use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::convert::Into;
 use std::error::Error;
 use std::fs;
-use std::path::Path;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::u32;
 
 use measureme::{EventId, EventIdBuilder, SerializableString, StringId};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use rustc_serialize::json::as_json;
+use tempfile::TempDir;
 
 /// MmapSerializatioSink is faster on macOS and Linux
 /// but FileSerializationSink is faster on Windows
@@ -136,6 +163,11 @@ bitflags::bitflags! {
                         Self::INCR_CACHE_LOADS.bits;
 
         const ARGS = Self::QUERY_KEYS.bits | Self::FUNCTION_ARGS.bits;
+
+        const SUMMARY = Self::GENERIC_ACTIVITIES.bits |
+                        Self::QUERY_PROVIDERS.bits |
+                        Self::QUERY_CACHE_HITS.bits |
+                        Self::INCR_CACHE_LOADS.bits;
     }
 }
 
@@ -263,9 +295,11 @@ impl SelfProfilerRef {
     #[inline(always)]
     pub fn generic_activity(&self, event_label: &'static str) -> TimingGuard<'_> {
         self.exec(EventFilter::GENERIC_ACTIVITIES, |profiler| {
-            let event_label = profiler.get_or_alloc_cached_string(event_label);
-            let event_id = EventId::from_label(event_label);
+            let event_label_id = profiler.get_or_alloc_cached_string(event_label);
+            let event_id = EventId::from_label(event_label_id);
             TimingGuard::start(profiler, profiler.generic_activity_event_kind, event_id)
+                .with_summary(profiler, SummaryEvent::GenericActivity, event_label)
+                .with_trace(profiler, "generic activity", || event_label.into())
         })
     }
 
@@ -281,35 +315,50 @@ impl SelfProfilerRef {
         A: Borrow<str> + Into<String>,
     {
         self.exec(EventFilter::GENERIC_ACTIVITIES, |profiler| {
+            let trace_name =
+                profiler.trace.as_ref().map(|_| format!("{}({})", event_label, event_arg.borrow()));
             let builder = EventIdBuilder::new(&profiler.profiler);
-            let event_label = profiler.get_or_alloc_cached_string(event_label);
+            let event_label_id = profiler.get_or_alloc_cached_string(event_label);
             let event_id = if profiler.event_filter_mask.contains(EventFilter::FUNCTION_ARGS) {
                 let event_arg = profiler.get_or_alloc_cached_string(event_arg);
-                builder.from_label_and_arg(event_label, event_arg)
+                builder.from_label_and_arg(event_label_id, event_arg)
             } else {
-                builder.from_label(event_label)
+                builder.from_label(event_label_id)
             };
             TimingGuard::start(profiler, profiler.generic_activity_event_kind, event_id)
+                .with_summary(profiler, SummaryEvent::GenericActivity, event_label)
+                .with_trace(profiler, "generic activity", || trace_name.unwrap().into())
         })
     }
 
     /// Start profiling a query provider. Profiling continues until the
     /// TimingGuard returned from this call is dropped.
     #[inline(always)]
-    pub fn query_provider(&self) -> TimingGuard<'_> {
+    pub fn query_provider(&self, query_name: &'static str) -> TimingGuard<'_> {
         self.exec(EventFilter::QUERY_PROVIDERS, |profiler| {
             TimingGuard::start(profiler, profiler.query_event_kind, EventId::INVALID)
+                .with_summary(profiler, SummaryEvent::QueryProvider, query_name)
+                .with_trace(profiler, "query", || query_name.into())
         })
     }
 
     /// Record a query in-memory cache hit.
     #[inline(always)]
-    pub fn query_cache_hit(&self, query_invocation_id: QueryInvocationId) {
+    pub fn query_cache_hit(
+        &self,
+        query_name: &'static str,
+        query_invocation_id: QueryInvocationId,
+    ) {
         self.instant_query_event(
             |profiler| profiler.query_cache_hit_event_kind,
             query_invocation_id,
             EventFilter::QUERY_CACHE_HITS,
         );
+        if let Some(profiler) = &self.profiler {
+            if let Some(summary) = &profiler.summary {
+                summary.record_cache_hit(query_name);
+            }
+        }
     }
 
     /// Start profiling a query being blocked on a concurrent execution.
@@ -326,13 +375,15 @@ impl SelfProfilerRef {
     /// incremental compilation on-disk cache. Profiling continues until the
     /// TimingGuard returned from this call is dropped.
     #[inline(always)]
-    pub fn incr_cache_loading(&self) -> TimingGuard<'_> {
+    pub fn incr_cache_loading(&self, query_name: &'static str) -> TimingGuard<'_> {
         self.exec(EventFilter::INCR_CACHE_LOADS, |profiler| {
             TimingGuard::start(
                 profiler,
                 profiler.incremental_load_result_event_kind,
                 EventId::INVALID,
             )
+            .with_summary(profiler, SummaryEvent::IncrCacheLoad, query_name)
+            .with_trace(profiler, "incremental load", || query_name.into())
         })
     }
 
@@ -376,6 +427,30 @@ impl SelfProfilerRef {
     pub fn get_self_profiler(&self) -> Option<Arc<SelfProfiler>> {
         self.profiler.clone()
     }
+
+    /// Prints the `-Z self-profile-summary` table, if the summary is enabled.
+    pub fn print_summary_table(&self) {
+        if let Some(summary) = self.profiler.as_ref().and_then(|p| p.summary.as_ref()) {
+            summary.print_table();
+        }
+    }
+
+    /// Prints the `-Z self-profile-summary` data as JSON, if the summary is
+    /// enabled.
+    pub fn print_summary_json(&self) {
+        if let Some(summary) = self.profiler.as_ref().and_then(|p| p.summary.as_ref()) {
+            println!("{}", as_json(&summary.entries()));
+        }
+    }
+
+    /// Writes the events recorded for `-Z self-profile-format=chrome`, if
+    /// any, to their trace file.
+    pub fn write_chrome_trace(&self) -> io::Result<()> {
+        match self.profiler.as_ref().and_then(|p| p.trace.as_ref()) {
+            Some(trace) => trace.write(),
+            None => Ok(()),
+        }
+    }
 }
 
 pub struct SelfProfiler {
@@ -389,17 +464,45 @@ pub struct SelfProfiler {
     incremental_load_result_event_kind: StringId,
     query_blocked_event_kind: StringId,
     query_cache_hit_event_kind: StringId,
+
+    summary: Option<Summary>,
+    trace: Option<ChromeTrace>,
+
+    // Where the event data is recorded if it is not requested in the
+    // `measureme` format. It is removed once `profiler` has been dropped, and
+    // thus written out.
+    _temp_dir: Option<TempDir>,
 }
 
 impl SelfProfiler {
+    /// Creates a profiler recording the events to `output_directory`, or to a
+    /// temporary directory if there is none, in which case only the summary
+    /// and the Chrome trace written to `chrome_trace_directory` will be
+    /// available.
     pub fn new(
-        output_directory: &Path,
+        output_directory: Option<&Path>,
         crate_name: Option<&str>,
         event_filters: &Option<Vec<String>>,
+        summary: bool,
+        chrome_trace_directory: Option<&Path>,
     ) -> Result<SelfProfiler, Box<dyn Error>> {
+        let temp_dir = match output_directory {
+            Some(_) => None,
+            None => Some(tempfile::Builder::new().prefix("rustc-self-profile").tempdir()?),
+        };
+        let output_directory =
+            output_directory.unwrap_or_else(|| temp_dir.as_ref().unwrap().path());
         fs::create_dir_all(output_directory)?;
 
         let crate_name = crate_name.unwrap_or("unknown-crate");
+        let trace = match chrome_trace_directory {
+            Some(directory) => {
+                fs::create_dir_all(directory)?;
+                let filename = format!("{}-{}.json", crate_name, process::id());
+                Some(ChromeTrace::new(directory.join(filename)))
+            }
+            None => None,
+        };
         let filename = format!("{}-{}.rustc_profile", crate_name, process::id());
         let path = output_directory.join(&filename);
         let profiler = Profiler::new(&path)?;
@@ -443,6 +546,10 @@ impl SelfProfiler {
             event_filter_mask = EventFilter::DEFAULT;
         }
 
+        if summary {
+            event_filter_mask |= EventFilter::SUMMARY;
+        }
+
         Ok(SelfProfiler {
             profiler,
             event_filter_mask,
@@ -452,6 +559,9 @@ impl SelfProfiler {
             incremental_load_result_event_kind,
             query_blocked_event_kind,
             query_cache_hit_event_kind,
+            summary: if summary { Some(Summary::default()) } else { None },
+            trace,
+            _temp_dir: temp_dir,
         })
     }
 
@@ -513,7 +623,11 @@ impl SelfProfiler {
 }
 
 #[must_use]
-pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a, SerializationSink>>);
+pub struct TimingGuard<'a> {
+    raw: Option<measureme::TimingGuard<'a, SerializationSink>>,
+    summary: Option<SummaryTimer<'a>>,
+    trace: Option<TraceTimer<'a>>,
+}
 
 impl<'a> TimingGuard<'a> {
     #[inline]
@@ -526,23 +640,55 @@ impl<'a> TimingGuard<'a> {
         let raw_profiler = &profiler.profiler;
         let timing_guard =
             raw_profiler.start_recording_interval_event(event_kind, event_id, thread_id);
-        TimingGuard(Some(timing_guard))
+        TimingGuard { raw: Some(timing_guard), summary: None, trace: None }
+    }
+
+    /// Also accounts for the event in the summary, if it is enabled.
+    #[inline]
+    fn with_summary(
+        mut self,
+        profiler: &'a SelfProfiler,
+        event: SummaryEvent,
+        label: &'static str,
+    ) -> TimingGuard<'a> {
+        if let Some(summary) = &profiler.summary {
+            self.summary = Some(SummaryTimer::start(summary, event, label));
+        }
+        self
+    }
+
+    /// Also records the event in the Chrome trace, if it is enabled. `name` is
+    /// only called in that case.
+    #[inline]
+    pub fn with_trace(
+        mut self,
+        profiler: &'a SelfProfiler,
+        category: &'static str,
+        name: impl FnOnce() -> Cow<'static, str>,
+    ) -> TimingGuard<'a> {
+        if let Some(trace) = &profiler.trace {
+            self.trace = Some(TraceTimer::start(trace, category, name()));
+        }
+        self
     }
 
     #[inline]
     pub fn finish_with_query_invocation_id(self, query_invocation_id: QueryInvocationId) {
-        if let Some(guard) = self.0 {
+        let TimingGuard { raw, summary, trace } = self;
+        if let Some(guard) = raw {
             cold_path(|| {
                 let event_id = StringId::new_virtual(query_invocation_id.0);
                 let event_id = EventId::from_virtual(event_id);
                 guard.finish_with_override_event_id(event_id);
             });
         }
+        drop(summary);
+        drop(trace);
     }
 
     #[inline]
     pub fn none() -> TimingGuard<'a> {
-        TimingGuard(None)
+        TimingGuard { raw: None, summary: None, trace: None }
     }
 
     #[inline(always)]
@@ -552,6 +698,301 @@ impl<'a> TimingGuard<'a> {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum SummaryCategory {
+    Query,
+    GenericActivity,
+}
+
+impl SummaryCategory {
+    fn name(self) -> &'static str {
+        match self {
+            SummaryCategory::Query => "query",
+            SummaryCategory::GenericActivity => "generic activity",
+        }
+    }
+}
+
+/// The kinds of events which are aggregated in the summary.
+#[derive(Clone, Copy)]
+enum SummaryEvent {
+    GenericActivity,
+    QueryProvider,
+    IncrCacheLoad,
+}
+
+impl SummaryEvent {
+    fn category(self) -> SummaryCategory {
+        match self {
+            SummaryEvent::GenericActivity => SummaryCategory::GenericActivity,
+            SummaryEvent::QueryProvider | SummaryEvent::IncrCacheLoad => SummaryCategory::Query,
+        }
+    }
+}
+
+#[derive(Default)]
+struct SummaryTotals {
+    self_time: Duration,
+    total_time: Duration,
+    invocation_count: u64,
+    cache_hits: u64,
+    incremental_load_time: Duration,
+}
+
+/// One line of the summary, with times in nanoseconds.
+#[derive(RustcEncodable)]
+struct SummaryEntry {
+    label: &'static str,
+    category: &'static str,
+    self_time: u64,
+    total_time: u64,
+    invocation_count: u64,
+    cache_hits: u64,
+    incremental_load_time: u64,
+}
+
+/// The events aggregated for `-Z self-profile-summary`.
+#[derive(Default)]
+struct Summary {
+    totals: Mutex<FxHashMap<(SummaryCategory, &'static str), SummaryTotals>>,
+}
+
+thread_local! {
+    // The time spent in the events nested in each of the events currently
+    // running on this thread, innermost last.
+    static SUMMARY_CHILD_TIMES: RefCell<Vec<Duration>> = RefCell::new(vec![]);
+}
+
+impl Summary {
+    fn record(
+        &self,
+        event: SummaryEvent,
+        label: &'static str,
+        time: Duration,
+        self_time: Duration,
+    ) {
+        let mut totals = self.totals.lock();
+        let totals = totals.entry((event.category(), label)).or_default();
+        totals.total_time += time;
+        totals.self_time += self_time;
+        match event {
+            SummaryEvent::IncrCacheLoad => totals.incremental_load_time += time,
+            SummaryEvent::GenericActivity | SummaryEvent::QueryProvider => {
+                totals.invocation_count += 1
+            }
+        }
+    }
+
+    fn record_cache_hit(&self, query_name: &'static str) {
+        let mut totals = self.totals.lock();
+        totals.entry((SummaryCategory::Query, query_name)).or_default().cache_hits += 1;
+    }
+
+    /// The summary entries, sorted by decreasing self time.
+    fn entries(&self) -> Vec<SummaryEntry> {
+        let nanos = |d: Duration| d.as_nanos() as u64;
+        let mut entries: Vec<_> = self
+            .totals
+            .lock()
+            .iter()
+            .map(|(&(category, label), totals)| SummaryEntry {
+                label,
+                category: category.name(),
+                self_time: nanos(totals.self_time),
+                total_time: nanos(totals.total_time),
+                invocation_count: totals.invocation_count,
+                cache_hits: totals.cache_hits,
+                incremental_load_time: nanos(totals.incremental_load_time),
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.self_time
+                .cmp(&a.self_time)
+                .then_with(|| (a.label, a.category).cmp(&(b.label, b.category)))
+        });
+        entries
+    }
+
+    fn print_table(&self) {
+        let entries = self.entries();
+        let total_self_time: u64 = entries.iter().map(|entry| entry.self_time).sum();
+        let secs = |nanos: u64| duration_to_secs_str(Duration::from_nanos(nanos));
+
+        println!(
+            "{:<40} {:<16} {:>10} {:>7} {:>10} {:>11} {:>10} {:>10}",
+            "Item",
+            "Kind",
+            "Self time",
+            "% total",
+            "Time",
+            "Invocations",
+            "Cache hits",
+            "Incr. load"
+        );
+        for entry in &entries {
+            let percent = if total_self_time == 0 {
+                0.0
+            } else {
+                entry.self_time as f64 * 100.0 / total_self_time as f64
+            };
+            println!(
+                "{:<40} {:<16} {:>10} {:>7.2} {:>10} {:>11} {:>10} {:>10}",
+                entry.label,
+                entry.category,
+                secs(entry.self_time),
+                percent,
+                secs(entry.total_time),
+                entry.invocation_count,
+                entry.cache_hits,
+                secs(entry.incremental_load_time),
+            );
+        }
+        println!("Total self time: {}", secs(total_self_time));
+    }
+}
+
+/// Measures an event for the summary, from its creation until it is dropped.
+struct SummaryTimer<'a> {
+    summary: &'a Summary,
+    event: SummaryEvent,
+    label: &'static str,
+    start: Instant,
+}
+
+impl<'a> SummaryTimer<'a> {
+    fn start(summary: &'a Summary, event: SummaryEvent, label: &'static str) -> SummaryTimer<'a> {
+        SUMMARY_CHILD_TIMES.with(|child_times| child_times.borrow_mut().push(Duration::default()));
+        SummaryTimer { summary, event, label, start: Instant::now() }
+    }
+}
+
+impl Drop for SummaryTimer<'_> {
+    fn drop(&mut self) {
+        let time = self.start.elapsed();
+        let child_time = SUMMARY_CHILD_TIMES.with(|child_times| {
+            let mut child_times = child_times.borrow_mut();
+            let child_time = child_times.pop().unwrap();
+            if let Some(parent_child_time) = child_times.last_mut() {
+                *parent_child_time += time;
+            }
+            child_time
+        });
+        let self_time = time.checked_sub(child_time).unwrap_or_default();
+        self.summary.record(self.event, self.label, time, self_time);
+    }
+}
+
+struct TraceEvent {
+    name: Cow<'static, str>,
+    category: &'static str,
+    thread_id: u64,
+    start: Duration,
+    duration: Duration,
+}
+
+#[derive(Default)]
+struct TraceEvents {
+    events: Vec<TraceEvent>,
+    thread_names: FxHashMap<u64, String>,
+}
+
+/// The events recorded for `-Z self-profile-format=chrome`.
+struct ChromeTrace {
+    path: PathBuf,
+    start: Instant,
+    events: Mutex<TraceEvents>,
+}
+
+impl ChromeTrace {
+    fn new(path: PathBuf) -> ChromeTrace {
+        ChromeTrace { path, start: Instant::now(), events: Default::default() }
+    }
+
+    fn record(&self, event: TraceEvent) {
+        let mut events = self.events.lock();
+        if !events.thread_names.contains_key(&event.thread_id) {
+            if let Some(name) = std::thread::current().name() {
+                events.thread_names.insert(event.thread_id, name.to_string());
+            }
+        }
+        events.events.push(event);
+    }
+
+    /// Writes the events as complete (`X`) events, along with the names of the
+    /// threads they were recorded on.
+    fn write(&self) -> io::Result<()> {
+        let events = self.events.lock();
+        let pid = process::id();
+        let micros = |d: Duration| d.as_nanos() as f64 / 1000.0;
+        let mut file = BufWriter::new(fs::File::create(&self.path)?);
+
+        write!(file, "{{\"traceEvents\":[")?;
+        let mut separator = "";
+        for (thread_id, name) in &events.thread_names {
+            write!(
+                file,
+                "{}{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{},\"tid\":{},\
+                 \"args\":{{\"name\":{}}}}}",
+                separator,
+                pid,
+                thread_id,
+                as_json(name)
+            )?;
+            separator = ",\n";
+        }
+        for event in &events.events {
+            let name: &str = &event.name;
+            write!(
+                file,
+                "{}{{\"name\":{},\"cat\":{},\"ph\":\"X\",\"pid\":{},\"tid\":{},\
+                 \"ts\":{:.3},\"dur\":{:.3}}}",
+                separator,
+                as_json(&name),
+                as_json(&event.category),
+                pid,
+                event.thread_id,
+                micros(event.start),
+                micros(event.duration)
+            )?;
+            separator = ",\n";
+        }
+        writeln!(file, "],\"displayTimeUnit\":\"ms\"}}")?;
+        file.flush()
+    }
+}
+
+/// Measures an event for the Chrome trace, from its creation until it is
+/// dropped.
+struct TraceTimer<'a> {
+    trace: &'a ChromeTrace,
+    category: &'static str,
+    name: Cow<'static, str>,
+    start: Instant,
+}
+
+impl<'a> TraceTimer<'a> {
+    fn start(
+        trace: &'a ChromeTrace,
+        category: &'static str,
+        name: Cow<'static, str>,
+    ) -> TraceTimer<'a> {
+        TraceTimer { trace, category, name, start: Instant::now() }
+    }
+}
+
+impl Drop for TraceTimer<'_> {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        self.trace.record(TraceEvent {
+            name: mem::replace(&mut self.name, Cow::Borrowed("")),
+            category: self.category,
+            thread_id: std::thread::current().id().as_u64(),
+            start: self.start.duration_since(self.trace.start),
+            duration,
+        });
+    }
+}
+
 #[must_use]
 pub struct VerboseTimingGuard<'a> {
     start_and_message: Option<(Instant, String)>,
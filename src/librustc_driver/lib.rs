@@ -355,6 +355,13 @@ pub fn run_compiler(
 
             queries.global_ctxt()?;
 
+            if let Some(ref command) = sess.opts.debugging_opts.query_saved_dep_graph {
+                queries.global_ctxt()?.peek_mut().enter(|tcx| {
+                    rustc_incremental::query_saved_dep_graph(tcx, command);
+                });
+                return early_exit();
+            }
+
             if sess.opts.debugging_opts.no_analysis || sess.opts.debugging_opts.ast_json {
                 return early_exit();
             }
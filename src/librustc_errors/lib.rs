@@ -307,6 +307,11 @@ struct HandlerInner {
     /// The stashed diagnostics count towards the total error count.
     /// When `.abort_if_errors()` is called, these are also emitted.
     stashed_diagnostics: FxIndexMap<(Span, StashKey), Diagnostic>,
+
+    /// Diagnostics which have been counted, but which won't be handed to the
+    /// emitter before the end of the outermost `Handler::buffer_diagnostics`.
+    /// This is `None` outside of it.
+    buffered_diagnostics: Option<Vec<Diagnostic>>,
 }
 
 /// A key denoting where from a diagnostic was stashed.
@@ -415,6 +420,7 @@ impl Handler {
                 emitted_diagnostic_codes: Default::default(),
                 emitted_diagnostics: Default::default(),
                 stashed_diagnostics: Default::default(),
+                buffered_diagnostics: None,
             }),
         }
     }
@@ -466,6 +472,38 @@ impl Handler {
         self.inner.borrow_mut().emit_stashed_diagnostics();
     }
 
+    /// Runs `f`, holding back the diagnostics emitted meanwhile, and then emits
+    /// them sorted by their primary span and message, so that their order does
+    /// not depend on the order in which threads happened to report them.
+    ///
+    /// Diagnostics are still deduplicated and counted as soon as they are
+    /// reported, so `has_errors` and `-Z treat-err-as-bug` are unaffected.
+    /// Does nothing with `-Z dont-buffer-diagnostics`, and when nested inside
+    /// another call, in which case the outermost one emits everything.
+    pub fn buffer_diagnostics<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct Flush<'a>(&'a Handler);
+
+        impl Drop for Flush<'_> {
+            fn drop(&mut self) {
+                // Also runs when `f` unwinds, e.g. after a fatal error, so that
+                // the diagnostics which explain it are not lost.
+                self.0.inner.borrow_mut().emit_buffered_diagnostics();
+            }
+        }
+
+        {
+            let mut inner = self.inner.borrow_mut();
+            if self.flags.dont_buffer_diagnostics || inner.buffered_diagnostics.is_some() {
+                drop(inner);
+                return f();
+            }
+            inner.buffered_diagnostics = Some(Vec::new());
+        }
+
+        let _flush = Flush(self);
+        f()
+    }
+
     /// Construct a dummy builder with `Level::Cancelled`.
     ///
     /// Using this will neither report anything to the user (e.g. a warning),
@@ -711,6 +749,19 @@ impl HandlerInner {
         diags.iter().for_each(|diag| self.emit_diagnostic(diag));
     }
 
+    /// Stop buffering diagnostics, and emit the buffered ones in a deterministic order.
+    ///
+    /// The body owners are visited in source order, so sorting by primary span mostly gives the
+    /// order in which a single thread would report the diagnostics, while not depending on which
+    /// thread finished first. The message breaks the ties between diagnostics pointing at the same
+    /// span, which may come from different threads, e.g. for a closure and its parent. The sort is
+    /// stable, so the diagnostics which are equal on both keep the order in which they came.
+    fn emit_buffered_diagnostics(&mut self) {
+        let mut diags = self.buffered_diagnostics.take().unwrap_or_default();
+        diags.sort_by_cached_key(|diag| (diag.sort_span.data(), diag.message()));
+        diags.iter().for_each(|diag| self.emitter.emit_diagnostic(diag));
+    }
+
     fn emit_diagnostic(&mut self, diagnostic: &Diagnostic) {
         if diagnostic.cancelled() {
             return;
@@ -737,7 +788,10 @@ impl HandlerInner {
         // Only emit the diagnostic if we've been asked to deduplicate and
         // haven't already emitted an equivalent diagnostic.
         if !(self.flags.deduplicate_diagnostics && already_emitted(self)) {
-            self.emitter.emit_diagnostic(diagnostic);
+            match self.buffered_diagnostics {
+                Some(ref mut buffered) => buffered.push(diagnostic.clone()),
+                None => self.emitter.emit_diagnostic(diagnostic),
+            }
             if diagnostic.is_error() {
                 self.deduplicated_err_count += 1;
             }
@@ -219,16 +219,23 @@ fn dump_graph(tcx: TyCtxt<'_>) {
     let path: String = env::var("RUST_DEP_GRAPH").unwrap_or_else(|_| "dep_graph".to_string());
     let query = tcx.dep_graph.query();
 
-    let nodes = match env::var("RUST_DEP_GRAPH_FILTER") {
-        Ok(string) => {
+    let edge_filter = env::var("RUST_DEP_GRAPH_FILTER")
+        .ok()
+        .map(|string| EdgeFilter::new(&string).unwrap_or_else(|e| bug!("invalid filter: {}", e)));
+    write_graph(&query, edge_filter.as_ref(), &path);
+}
+
+/// Writes the part of the graph selected by `edge_filter` to `{path}.txt`, as a
+/// list of edges, and to `{path}.dot`, in graphviz format.
+pub fn write_graph(query: &DepGraphQuery, edge_filter: Option<&EdgeFilter>, path: &str) {
+    let nodes = match edge_filter {
+        Some(edge_filter) => {
             // Expect one of: "-> target", "source -> target", or "source ->".
-            let edge_filter =
-                EdgeFilter::new(&string).unwrap_or_else(|e| bug!("invalid filter: {}", e));
             let sources = node_set(&query, &edge_filter.source);
             let targets = node_set(&query, &edge_filter.target);
             filter_nodes(&query, &sources, &targets)
         }
-        Err(_) => query.nodes().into_iter().collect(),
+        None => query.nodes().into_iter().collect(),
     };
     let edges = filter_edges(&query, &nodes);
 
@@ -13,6 +13,7 @@ extern crate log;
 mod assert_dep_graph;
 pub mod assert_module_sources;
 mod persist;
+mod saved_dep_graph;
 
 pub use assert_dep_graph::assert_dep_graph;
 pub use persist::copy_cgu_workproducts_to_incr_comp_cache_dir;
@@ -28,3 +29,4 @@ pub use persist::save_dep_graph;
 pub use persist::save_work_product_index;
 pub use persist::LoadResult;
 pub use persist::{load_dep_graph, DepGraphFuture};
+pub use saved_dep_graph::query_saved_dep_graph;
@@ -50,6 +50,34 @@
 //! any more and will delete those. It will also delete any finalized session
 //! directories for a given crate except for the most recent one.
 //!
+//! ### Size and age limits
+//!
+//! The above only ever deletes session directories of the crate being compiled.
+//! With `-Z incremental-max-size` and `-Z incremental-max-age`, the compiler
+//! also enforces limits on the whole incremental compilation directory, which is
+//! usually shared by all the crates of a project: after finalizing its session
+//! directory, it deletes the finalized session directories of any crate that
+//! are too old, and then the least recently used ones until the directory is
+//! small enough. A session directory is used when it is created, since the
+//! next session of the crate starts with a new copy of it. The session
+//! directory that was just finalized is always kept.
+//!
+//! Only one compiler at a time enforces the limits, holding an exclusive lock
+//! on the `gc.lock` file of the incremental compilation directory; others skip
+//! it. Session directories are deleted following the same locking protocol as
+//! the rest of the garbage collection, described below.
+//!
+//! ### Keeping snapshots
+//!
+//! To make it possible to reproduce a bug in incremental compilation, the
+//! compiler can keep more than the most recent finalized session directory:
+//! with `-Z incremental-keep-sessions=N`, the garbage collection keeps the `N`
+//! most recent ones, and each session directory also gets a manifest listing
+//! the hashes of the source files it was built from. A later session can then
+//! start from any of those directories instead of the most recent one, named
+//! with `-Z incremental-replay`. The directory being replayed is not garbage
+//! collected by that session.
+//!
 //! ## Synchronization
 //!
 //! There is some synchronization needed in order for the compiler to be able to
@@ -89,6 +117,16 @@
 //! so, it will also place a read lock on that the respective session directory
 //! so that it won't be deleted while the metadata hashes are loaded.
 //!
+//! ## Importing
+//!
+//! When the crate directory has no finalized session directory yet, the compiler
+//! can start from one found in another incremental compilation directory, given
+//! with `-Z incremental-import`. That directory is treated as read-only: nothing
+//! in it is locked, modified or garbage collected. This makes it possible to warm
+//! a fresh build with a cache built elsewhere, e.g. by CI. For the cache to be
+//! usable from a different checkout path, it should have been built with
+//! `-Z incremental-relocatable`, which keeps absolute paths out of it.
+//!
 //! ## Preconditions
 //!
 //! This system relies on two features being available in the file system in
@@ -124,6 +162,8 @@ const LOCK_FILE_EXT: &str = ".lock";
 const DEP_GRAPH_FILENAME: &str = "dep-graph.bin";
 const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
 const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
+const GC_LOCK_FILENAME: &str = "gc.lock";
+const SOURCE_MANIFEST_FILENAME: &str = "sources.manifest";
 
 // We encode integers using the following base, so they are shorter than decimal
 // or hexadecimal numbers (we want short file and directory names). Since these
@@ -146,6 +186,10 @@ pub fn query_cache_path(sess: &Session) -> PathBuf {
     in_incr_comp_dir_sess(sess, QUERY_CACHE_FILENAME)
 }
 
+pub fn source_manifest_path(sess: &Session) -> PathBuf {
+    in_incr_comp_dir_sess(sess, SOURCE_MANIFEST_FILENAME)
+}
+
 pub fn lock_file_path(session_dir: &Path) -> PathBuf {
     let crate_dir = session_dir.parent().unwrap();
 
@@ -240,8 +284,24 @@ pub fn prepare_session_directory(
         }
 
         // Find a suitable source directory to copy from. Ignore those that we
-        // have already tried before.
-        let source_directory = find_source_directory(&crate_dir, &source_directories_already_tried);
+        // have already tried before. If there are none, try the imported ones.
+        // When replaying a session directory, only that one is suitable.
+        let (source_directory, imported) = if let Some(ref name) =
+            sess.opts.debugging_opts.incremental_replay
+        {
+            (
+                find_replayed_directory(sess, &crate_dir, name, &source_directories_already_tried),
+                false,
+            )
+        } else {
+            match find_source_directory(&crate_dir, &source_directories_already_tried) {
+                Some(dir) => (Some(dir), false),
+                None => (
+                    find_imported_directory(sess, &crate_dir, &source_directories_already_tried),
+                    true,
+                ),
+            }
+        };
 
         let source_directory = if let Some(dir) = source_directory {
             dir
@@ -259,10 +319,26 @@ pub fn prepare_session_directory(
         debug!("attempting to copy data from source: {}", source_directory.display());
 
         // Try copying over all files from the source directory
-        if let Ok(allows_links) = copy_files(sess, &session_dir, &source_directory) {
+        if let Ok(allows_links) = copy_files(sess, &session_dir, &source_directory, !imported) {
             debug!("successfully copied data from: {}", source_directory.display());
 
-            if !allows_links {
+            if imported && sess.opts.debugging_opts.incremental_info {
+                println!(
+                    "[incremental] session directory: imported from {}",
+                    source_directory.display()
+                );
+            }
+
+            if sess.opts.debugging_opts.incremental_replay.is_some()
+                && sess.opts.debugging_opts.incremental_info
+            {
+                println!(
+                    "[incremental] session directory: replaying {}",
+                    source_directory.display()
+                );
+            }
+
+            if !allows_links && !imported {
                 sess.warn(&format!(
                     "Hard linking files in the incremental \
                                         compilation cache failed. Copying files \
@@ -384,6 +460,7 @@ pub fn finalize_session_directory(sess: &Session, svh: Svh) {
     }
 
     let _ = garbage_collect_session_directories(sess);
+    let _ = enforce_cache_limits(sess);
 }
 
 pub fn delete_all_session_dir_contents(sess: &Session) -> io::Result<()> {
@@ -395,18 +472,25 @@ pub fn delete_all_session_dir_contents(sess: &Session) -> io::Result<()> {
     Ok(())
 }
 
-fn copy_files(sess: &Session, target_dir: &Path, source_dir: &Path) -> Result<bool, ()> {
+fn copy_files(
+    sess: &Session,
+    target_dir: &Path,
+    source_dir: &Path,
+    lock_source: bool,
+) -> Result<bool, ()> {
     // We acquire a shared lock on the lock file of the directory, so that
     // nobody deletes it out from under us while we are reading from it.
-    let lock_file_path = lock_file_path(source_dir);
-    let _lock = if let Ok(lock) = flock::Lock::new(
-        &lock_file_path,
+    // Imported directories aren't garbage collected, so they aren't locked.
+    let _lock = if !lock_source {
+        None
+    } else if let Ok(lock) = flock::Lock::new(
+        &lock_file_path(source_dir),
         false, // don't wait,
         false, // don't create
         false,
     ) {
         // not exclusive
-        lock
+        Some(lock)
     } else {
         // Could not acquire the lock, don't try to copy from here
         return Err(());
@@ -542,6 +626,65 @@ fn find_source_directory(
     find_source_directory_in_iter(iter, source_directories_already_tried)
 }
 
+/// Finds the most recent published session directory of the crate in the
+/// directory given with `-Z incremental-import`, if any.
+fn find_imported_directory(
+    sess: &Session,
+    crate_dir: &Path,
+    source_directories_already_tried: &FxHashSet<PathBuf>,
+) -> Option<PathBuf> {
+    let import_dir = sess.opts.debugging_opts.incremental_import.as_ref()?;
+    let iter = import_dir
+        .join(crate_dir.file_name()?)
+        .read_dir()
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()));
+
+    find_source_directory_in_iter(iter, source_directories_already_tried)
+}
+
+/// Finds the finalized session directory of the crate given with
+/// `-Z incremental-replay`, reporting an error if there is none.
+fn find_replayed_directory(
+    sess: &Session,
+    crate_dir: &Path,
+    name: &str,
+    source_directories_already_tried: &FxHashSet<PathBuf>,
+) -> Option<PathBuf> {
+    let path = crate_dir.join(name);
+    if source_directories_already_tried.contains(&path) {
+        // The error was reported when copying from it failed.
+        return None;
+    }
+
+    if is_session_directory(name) && is_finalized(name) && path.is_dir() {
+        return Some(path);
+    }
+
+    let mut available: Vec<_> = crate_dir
+        .read_dir()
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| is_session_directory(name) && is_finalized(name))
+        .collect();
+    available.sort();
+
+    let mut err = sess.struct_err(&format!(
+        "incremental compilation: no finalized session directory `{}` to replay in `{}`",
+        name,
+        crate_dir.display()
+    ));
+    if available.is_empty() {
+        err.note("there are no finalized session directories for this crate");
+    } else {
+        err.note(&format!("the finalized session directories are: {}", available.join(", ")));
+    }
+    err.emit();
+    None
+}
+
 fn find_source_directory_in_iter<I>(
     iter: I,
     source_directories_already_tried: &FxHashSet<PathBuf>,
@@ -860,8 +1003,16 @@ pub fn garbage_collect_session_directories(sess: &Session) -> io::Result<()> {
         }
     }
 
-    // Delete all but the most recent of the candidates
-    for (path, lock) in all_except_most_recent(deletion_candidates) {
+    // Never delete the directory this session replayed, so that it can be
+    // replayed again.
+    if let Some(ref name) = sess.opts.debugging_opts.incremental_replay {
+        deletion_candidates.retain(|(_, path, _)| path.file_name() != Some(name.as_ref()));
+    }
+
+    // Delete all but the most recent of the candidates, or all but the
+    // `-Z incremental-keep-sessions` most recent ones.
+    let keep = sess.opts.debugging_opts.incremental_keep_sessions.unwrap_or(1).max(1);
+    for (path, lock) in all_except_most_recent(deletion_candidates, keep) {
         debug!("garbage_collect_session_directories() - deleting `{}`", path.display());
 
         if let Err(err) = safe_remove_dir_all(&path) {
@@ -902,20 +1053,177 @@ pub fn garbage_collect_session_directories(sess: &Session) -> io::Result<()> {
     Ok(())
 }
 
+/// Deletes finalized session directories of any crate in the incremental
+/// compilation directory, least recently used first, until it respects
+/// `-Z incremental-max-age` and `-Z incremental-max-size`.
+fn enforce_cache_limits(sess: &Session) -> io::Result<()> {
+    let opts = &sess.opts.debugging_opts;
+    let max_size = opts.incremental_max_size.map(|mib| mib as u64 * 1024 * 1024);
+    let max_age = opts.incremental_max_age.map(|days| Duration::from_secs(days as u64 * 86400));
+    if max_size.is_none() && max_age.is_none() {
+        return Ok(());
+    }
+
+    debug!("enforce_cache_limits() - begin");
+
+    let incr_dir = sess.opts.incremental.as_ref().unwrap();
+
+    // Don't wait for another compiler enforcing the limits, it will do the
+    // same as we would.
+    let _gc_lock = match flock::Lock::new(
+        &incr_dir.join(GC_LOCK_FILENAME),
+        false, // don't wait
+        true,  // create the lock file
+        true,
+    ) {
+        // the lock should be exclusive
+        Ok(lock) => lock,
+        Err(_) => {
+            debug!("enforce_cache_limits() - already in progress");
+            return Ok(());
+        }
+    };
+
+    let current_session_dir = sess.incr_comp_session_dir().clone();
+
+    let mut total_size = 0;
+    let mut candidates = vec![];
+    for crate_dir in incr_dir.read_dir()? {
+        let crate_dir = match crate_dir {
+            Ok(crate_dir) => crate_dir.path(),
+            _ => continue,
+        };
+        let session_dirs = match crate_dir.read_dir() {
+            Ok(session_dirs) => session_dirs,
+            // Not a crate directory
+            _ => continue,
+        };
+
+        for session_dir in session_dirs.filter_map(|e| e.ok()) {
+            let directory_name = session_dir.file_name();
+            let directory_name = directory_name.to_string_lossy();
+            if !is_session_directory(&directory_name) {
+                continue;
+            }
+
+            let path = session_dir.path();
+            let size = dir_size(&path);
+            total_size += size;
+
+            // Directories still in use are left to `garbage_collect_session_directories`.
+            if !is_finalized(&directory_name) || path.file_name() == current_session_dir.file_name()
+            {
+                continue;
+            }
+            if let Ok(timestamp) = extract_timestamp_from_session_dir(&directory_name) {
+                candidates.push((timestamp, size, path));
+            }
+        }
+    }
+
+    // Least recently used first.
+    candidates.sort_by_key(|&(timestamp, ..)| timestamp);
+
+    let now = SystemTime::now();
+    let mut deleted_count = 0;
+    let mut deleted_size = 0;
+    for (timestamp, size, path) in candidates {
+        if !exceeds_cache_limits(timestamp, total_size, max_size, max_age, now) {
+            // Later candidates are more recent, and the size won't grow.
+            break;
+        }
+
+        // Get an exclusive lock, so that we don't delete a directory another
+        // compiler is copying from.
+        let lock_file_path = lock_file_path(&path);
+        let lock = if lock_file_path.exists() {
+            match flock::Lock::new(
+                &lock_file_path,
+                false, // don't wait
+                false, // don't create the lock-file
+                true,
+            ) {
+                Ok(lock) => Some(lock),
+                Err(_) => {
+                    debug!("enforce_cache_limits() - not deleting, still in use");
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+
+        debug!("enforce_cache_limits() - deleting `{}`", path.display());
+
+        if let Err(err) = safe_remove_dir_all(&path) {
+            sess.warn(&format!(
+                "Failed to garbage collect incremental compilation \
+                 session directory `{}`: {}",
+                path.display(),
+                err
+            ));
+            continue;
+        }
+        delete_session_dir_lock_file(sess, &lock_file_path);
+        mem::drop(lock);
+
+        total_size -= size;
+        deleted_count += 1;
+        deleted_size += size;
+    }
+
+    if sess.opts.debugging_opts.incremental_info {
+        println!(
+            "[incremental] cache limits: deleted {} session directories ({} MiB), \
+             {} MiB left",
+            deleted_count,
+            deleted_size / (1024 * 1024),
+            total_size / (1024 * 1024)
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether a finalized session directory created at `timestamp` has to be
+/// deleted, given the current size of the whole incremental compilation
+/// directory.
+fn exceeds_cache_limits(
+    timestamp: SystemTime,
+    total_size: u64,
+    max_size: Option<u64>,
+    max_age: Option<Duration>,
+    now: SystemTime,
+) -> bool {
+    let too_big = max_size.map_or(false, |max_size| total_size > max_size);
+    let too_old = max_age
+        .map_or(false, |max_age| now.duration_since(timestamp).map_or(false, |age| age > max_age));
+    too_big || too_old
+}
+
+/// The total size of the files in a directory, ignoring the ones that can't be read.
+fn dir_size(path: &Path) -> u64 {
+    let entries = match path.read_dir() {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => dir_size(&entry.path()),
+            Ok(_) => entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Returns the candidates other than the `keep` most recent ones.
 fn all_except_most_recent(
-    deletion_candidates: Vec<(SystemTime, PathBuf, Option<flock::Lock>)>,
+    mut deletion_candidates: Vec<(SystemTime, PathBuf, Option<flock::Lock>)>,
+    keep: usize,
 ) -> FxHashMap<PathBuf, Option<flock::Lock>> {
-    let most_recent = deletion_candidates.iter().map(|&(timestamp, ..)| timestamp).max();
-
-    if let Some(most_recent) = most_recent {
-        deletion_candidates
-            .into_iter()
-            .filter(|&(timestamp, ..)| timestamp != most_recent)
-            .map(|(_, path, lock)| (path, lock))
-            .collect()
-    } else {
-        FxHashMap::default()
-    }
+    deletion_candidates.sort_by(|(a, ..), (b, ..)| b.cmp(a));
+    deletion_candidates.into_iter().skip(keep).map(|(_, path, lock)| (path, lock)).collect()
 }
 
 /// Since paths of artifacts within session directories can get quite long, we
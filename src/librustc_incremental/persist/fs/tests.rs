@@ -3,13 +3,16 @@ use super::*;
 #[test]
 fn test_all_except_most_recent() {
     assert_eq!(
-        all_except_most_recent(vec![
-            (UNIX_EPOCH + Duration::new(4, 0), PathBuf::from("4"), None),
-            (UNIX_EPOCH + Duration::new(1, 0), PathBuf::from("1"), None),
-            (UNIX_EPOCH + Duration::new(5, 0), PathBuf::from("5"), None),
-            (UNIX_EPOCH + Duration::new(3, 0), PathBuf::from("3"), None),
-            (UNIX_EPOCH + Duration::new(2, 0), PathBuf::from("2"), None),
-        ])
+        all_except_most_recent(
+            vec![
+                (UNIX_EPOCH + Duration::new(4, 0), PathBuf::from("4"), None),
+                (UNIX_EPOCH + Duration::new(1, 0), PathBuf::from("1"), None),
+                (UNIX_EPOCH + Duration::new(5, 0), PathBuf::from("5"), None),
+                (UNIX_EPOCH + Duration::new(3, 0), PathBuf::from("3"), None),
+                (UNIX_EPOCH + Duration::new(2, 0), PathBuf::from("2"), None),
+            ],
+            1
+        )
         .keys()
         .cloned()
         .collect::<FxHashSet<PathBuf>>(),
@@ -19,7 +22,34 @@ fn test_all_except_most_recent() {
     );
 
     assert_eq!(
-        all_except_most_recent(vec![]).keys().cloned().collect::<FxHashSet<PathBuf>>(),
+        all_except_most_recent(vec![], 1).keys().cloned().collect::<FxHashSet<PathBuf>>(),
+        FxHashSet::default()
+    );
+
+    // Keeping several
+    assert_eq!(
+        all_except_most_recent(
+            vec![
+                (UNIX_EPOCH + Duration::new(4, 0), PathBuf::from("4"), None),
+                (UNIX_EPOCH + Duration::new(1, 0), PathBuf::from("1"), None),
+                (UNIX_EPOCH + Duration::new(5, 0), PathBuf::from("5"), None),
+                (UNIX_EPOCH + Duration::new(3, 0), PathBuf::from("3"), None),
+                (UNIX_EPOCH + Duration::new(2, 0), PathBuf::from("2"), None),
+            ],
+            3
+        )
+        .keys()
+        .cloned()
+        .collect::<FxHashSet<PathBuf>>(),
+        vec![PathBuf::from("1"), PathBuf::from("2")].into_iter().collect::<FxHashSet<PathBuf>>()
+    );
+
+    // Keeping more than there are
+    assert_eq!(
+        all_except_most_recent(vec![(UNIX_EPOCH, PathBuf::from("0"), None)], 3)
+            .keys()
+            .cloned()
+            .collect::<FxHashSet<PathBuf>>(),
         FxHashSet::default()
     );
 }
@@ -82,3 +112,27 @@ fn test_find_source_directory_in_iter() {
         None
     );
 }
+
+#[test]
+fn test_exceeds_cache_limits() {
+    let day = Duration::from_secs(86400);
+    let now = UNIX_EPOCH + 100 * day;
+
+    // No limits
+    assert!(!exceeds_cache_limits(UNIX_EPOCH, 1 << 40, None, None, now));
+
+    // Size
+    assert!(exceeds_cache_limits(now, 2000, Some(1000), None, now));
+    assert!(!exceeds_cache_limits(now, 1000, Some(1000), None, now));
+
+    // Age
+    assert!(exceeds_cache_limits(now - 31 * day, 0, None, Some(30 * day), now));
+    assert!(!exceeds_cache_limits(now - 29 * day, 0, None, Some(30 * day), now));
+
+    // Timestamps in the future (e.g. from another machine) aren't too old
+    assert!(!exceeds_cache_limits(now + day, 0, None, Some(30 * day), now));
+
+    // Either limit
+    assert!(exceeds_cache_limits(now - 31 * day, 0, Some(1000), Some(30 * day), now));
+    assert!(exceeds_cache_limits(now, 2000, Some(1000), Some(30 * day), now));
+}
@@ -7,6 +7,7 @@ mod dirty_clean;
 mod file_format;
 mod fs;
 mod load;
+mod rebuild_report;
 mod save;
 mod work_product;
 
@@ -0,0 +1,201 @@
+//! The `-Z incremental-rebuild-report` output: for every query and codegen unit which had to be
+//! re-executed in this session, the changed inputs which caused it.
+//!
+//! The dep-graph tells us which nodes of the previous session weren't green, and which of their
+//! dependencies weren't green either (see `DepGraph::rebuilt_nodes`). Following those down to the
+//! nodes which have no such dependencies gives the inputs which changed: most of the time the
+//! `Hir` or `HirBody` of an item which was edited, added or removed.
+//!
+//! Codegen units are only reported when codegen ran, using the partitioning it computed: the
+//! report is written while saving the dep-graph, where forcing the partitioning would collect the
+//! mono items of sessions which never codegen, and add new nodes to the dep-graph being saved.
+
+use rustc::dep_graph::{DepKind, DepNode, RebuildState, RebuiltNode};
+use rustc::mir::mono::CodegenUnit;
+use rustc::session::config::RebuildReportFormat;
+use rustc::ty::TyCtxt;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_serialize::json::as_json;
+use std::sync::Arc;
+
+#[derive(RustcEncodable)]
+struct Report {
+    /// The changed inputs which caused the re-executions.
+    inputs: Vec<Input>,
+    codegen_units: Vec<Rebuilt>,
+    queries: Vec<Rebuilt>,
+}
+
+#[derive(RustcEncodable)]
+struct Input {
+    node: String,
+    /// Either `changed` or `removed`.
+    change: &'static str,
+    /// The file defining the item, for inputs like `Hir`.
+    file: Option<String>,
+}
+
+#[derive(RustcEncodable)]
+struct Rebuilt {
+    node: String,
+    /// Whether the re-execution produced a different result.
+    result_changed: bool,
+    /// Why the node was re-executed if it isn't because of changed inputs, e.g. for a new
+    /// codegen unit.
+    reason: Option<&'static str>,
+    /// Indices into `Report::inputs`.
+    inputs: Vec<usize>,
+}
+
+/// Prints the report. `codegen_units` is the partitioning of the crate if codegen ran.
+pub fn report<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    format: RebuildReportFormat,
+    codegen_units: Option<&[Arc<CodegenUnit<'tcx>>]>,
+) {
+    let nodes = tcx.dep_graph.rebuilt_nodes();
+    let changed_inputs = changed_inputs(&nodes);
+    let node_indices: FxHashMap<DepNode, usize> =
+        nodes.iter().enumerate().map(|(index, node)| (node.node, index)).collect();
+
+    let mut report = Report { inputs: vec![], codegen_units: vec![], queries: vec![] };
+    let mut input_indices = FxHashMap::default();
+    let mut inputs_of = |report: &mut Report, index: usize| -> Vec<usize> {
+        changed_inputs[index]
+            .iter()
+            .map(|&input| {
+                *input_indices.entry(input).or_insert_with(|| {
+                    report.inputs.push(describe_input(tcx, &nodes[input]));
+                    report.inputs.len() - 1
+                })
+            })
+            .collect()
+    };
+
+    let mut sorted_codegen_units: Vec<_> = codegen_units.unwrap_or_default().iter().collect();
+    sorted_codegen_units.sort_by_cached_key(|cgu| cgu.name().as_str().to_string());
+    for cgu in &sorted_codegen_units {
+        let dep_node = cgu.codegen_dep_node(tcx);
+        let index = node_indices.get(&dep_node).cloned();
+        let (result_changed, reason, inputs) =
+            if tcx.dep_graph.prev_fingerprint_of(&dep_node).is_none() {
+                (true, Some("new codegen unit"), vec![])
+            } else if let Some(index) = index.filter(|&index| !nodes[index].causes.is_empty()) {
+                (nodes[index].state == RebuildState::Red, None, inputs_of(&mut report, index))
+            } else if tcx.dep_graph.previous_work_product(&cgu.work_product_id()).is_none() {
+                (true, Some("no cached object file"), vec![])
+            } else {
+                continue;
+            };
+        let node = format!("codegen unit `{}`", cgu.name());
+        report.codegen_units.push(Rebuilt { node, result_changed, reason, inputs });
+    }
+
+    let mut queries = vec![];
+    for (index, node) in nodes.iter().enumerate() {
+        if node.causes.is_empty() || node.node.kind == DepKind::CompileCodegenUnit {
+            continue;
+        }
+        let result_changed = node.state == RebuildState::Red;
+        let inputs = inputs_of(&mut report, index);
+        queries.push(Rebuilt {
+            node: format!("{:?}", node.node),
+            result_changed,
+            reason: None,
+            inputs,
+        });
+    }
+    queries.sort_by(|a, b| a.node.cmp(&b.node));
+    report.queries = queries;
+
+    match format {
+        RebuildReportFormat::Json => println!("{}", as_json(&report)),
+        RebuildReportFormat::Tree => {
+            print_tree(&report, codegen_units.map(|codegen_units| codegen_units.len()))
+        }
+    }
+}
+
+/// Finds the changed inputs behind every node: the nodes without causes which can be reached by
+/// following the causes.
+fn changed_inputs(nodes: &[RebuiltNode]) -> Vec<Vec<usize>> {
+    let mut inputs: Vec<Option<Vec<usize>>> = vec![None; nodes.len()];
+    for start in 0..nodes.len() {
+        // The dep-graph can be deep, so don't recurse.
+        let mut stack = vec![start];
+        while let Some(&index) = stack.last() {
+            if inputs[index].is_some() {
+                stack.pop();
+                continue;
+            }
+            let causes = &nodes[index].causes;
+            let pending: Vec<usize> =
+                causes.iter().cloned().filter(|&cause| inputs[cause].is_none()).collect();
+            if !pending.is_empty() {
+                stack.extend(pending);
+                continue;
+            }
+            let mut found: Vec<usize> = if causes.is_empty() {
+                vec![index]
+            } else {
+                causes.iter().flat_map(|&cause| inputs[cause].as_ref().unwrap().clone()).collect()
+            };
+            found.sort();
+            found.dedup();
+            inputs[index] = Some(found);
+            stack.pop();
+        }
+    }
+    inputs.into_iter().map(|inputs| inputs.unwrap()).collect()
+}
+
+fn describe_input(tcx: TyCtxt<'_>, input: &RebuiltNode) -> Input {
+    let (change, file) = match input.state {
+        RebuildState::Missing => ("removed", None),
+        RebuildState::Red | RebuildState::Green => {
+            let file = input
+                .node
+                .extract_def_id(tcx)
+                .and_then(|def_id| tcx.hir().span_if_local(def_id))
+                .map(|span| tcx.sess.source_map().span_to_filename(span).to_string());
+            ("changed", file)
+        }
+    };
+    Input { node: format!("{:?}", input.node), change, file }
+}
+
+fn print_tree(report: &Report, codegen_unit_count: Option<usize>) {
+    println!("[incremental]");
+    match codegen_unit_count {
+        Some(codegen_unit_count) => println!(
+            "[incremental] Rebuild Report: {} of {} codegen units and {} queries re-executed",
+            report.codegen_units.len(),
+            codegen_unit_count,
+            report.queries.len()
+        ),
+        None => {
+            println!("[incremental] Rebuild Report: {} queries re-executed", report.queries.len())
+        }
+    }
+    println!("[incremental]");
+
+    for rebuilt in report.codegen_units.iter().chain(&report.queries) {
+        let note =
+            rebuilt.reason.or(if rebuilt.result_changed { Some("result changed") } else { None });
+        match note {
+            Some(note) => println!("[incremental] {}: {}", rebuilt.node, note),
+            None => println!("[incremental] {}", rebuilt.node),
+        }
+
+        for (i, &input) in rebuilt.inputs.iter().enumerate() {
+            let branch = if i + 1 == rebuilt.inputs.len() { "└─" } else { "├─" };
+            let input = &report.inputs[input];
+            match input.file {
+                Some(ref file) => {
+                    println!("[incremental] {} {} {} ({})", branch, input.node, input.change, file)
+                }
+                None => println!("[incremental] {} {} {}", branch, input.node, input.change),
+            }
+        }
+    }
+}
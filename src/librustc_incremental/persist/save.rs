@@ -1,20 +1,27 @@
 use rustc::dep_graph::{DepGraph, DepKind, WorkProduct, WorkProductId};
+use rustc::mir::mono::CodegenUnit;
 use rustc::session::Session;
 use rustc::ty::TyCtxt;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::sync::join;
 use rustc_serialize::opaque::Encoder;
 use rustc_serialize::Encodable as RustcEncodable;
+use rustc_span::FileName;
+use std::fmt::Write;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::data::*;
 use super::dirty_clean;
 use super::file_format;
 use super::fs::*;
+use super::rebuild_report;
 use super::work_product;
 
-pub fn save_dep_graph(tcx: TyCtxt<'_>) {
+/// Saves the dep-graph and the query result cache. `codegen_units` is the partitioning of the
+/// crate if codegen ran, which `-Z incremental-rebuild-report` uses.
+pub fn save_dep_graph<'tcx>(tcx: TyCtxt<'tcx>, codegen_units: Option<&[Arc<CodegenUnit<'tcx>>]>) {
     debug!("save_dep_graph()");
     tcx.dep_graph.with_ignore(|| {
         let sess = tcx.sess;
@@ -26,6 +33,10 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
             return;
         }
 
+        if let Some(format) = sess.opts.debugging_opts.incremental_rebuild_report {
+            rebuild_report::report(tcx, format, codegen_units);
+        }
+
         let query_cache_path = query_cache_path(sess);
         let dep_graph_path = dep_graph_path(sess);
 
@@ -46,10 +57,72 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
             },
         );
 
+        if sess.opts.debugging_opts.incremental_keep_sessions.is_some() {
+            save_source_manifest(sess);
+        }
+
         dirty_clean::check_dirty_clean_annotations(tcx);
     })
 }
 
+/// Writes the list of the source files of the crate, with their hashes, so
+/// that it is possible to tell which version of the sources a kept session
+/// directory corresponds to. With `-Z incremental-info`, also prints the files
+/// which changed since the session this one started from, if it wrote one.
+fn save_source_manifest(sess: &Session) {
+    let path_buf = source_manifest_path(sess);
+
+    let files: Vec<_> = sess
+        .source_map()
+        .files()
+        .iter()
+        .filter(|file| !file.is_imported())
+        .filter_map(|file| match file.name {
+            FileName::Real(ref path) => Some((format!("{:032x}", file.src_hash), path.clone())),
+            _ => None,
+        })
+        .collect();
+
+    if sess.opts.debugging_opts.incremental_info {
+        if let Ok(previous) = fs::read_to_string(&path_buf) {
+            let previous: FxHashMap<&str, &str> = previous
+                .lines()
+                .filter_map(|line| {
+                    let space = line.find(' ')?;
+                    Some((&line[space + 1..], &line[..space]))
+                })
+                .collect();
+            for (hash, path) in &files {
+                if previous.get(&*path.to_string_lossy()) != Some(&&hash[..]) {
+                    println!("[incremental] source manifest: {} changed", path.display());
+                }
+            }
+        }
+    }
+
+    let mut manifest = String::new();
+    for (hash, path) in &files {
+        writeln!(manifest, "{} {}", hash, path.display()).unwrap();
+    }
+
+    // The old manifest may be a hard-link to the one of the session directory
+    // we started from, which must not be modified.
+    if path_buf.exists() {
+        if let Err(err) = fs::remove_file(&path_buf) {
+            sess.warn(&format!(
+                "unable to delete old source manifest at `{}`: {}",
+                path_buf.display(),
+                err
+            ));
+            return;
+        }
+    }
+
+    if let Err(err) = fs::write(&path_buf, manifest) {
+        sess.warn(&format!("failed to write source manifest to `{}`: {}", path_buf.display(), err));
+    }
+}
+
 pub fn save_work_product_index(
     sess: &Session,
     dep_graph: &DepGraph,
@@ -0,0 +1,203 @@
+//! Support for `-Z query-saved-dep-graph`, which answers questions about the
+//! dep-graph that the previous incremental session saved to disk, instead of
+//! compiling the crate. This is meant for people investigating why some
+//! change causes so much (or so little) to be recomputed.
+//!
+//! The following commands are understood, where a node filter is a list of
+//! `&`-separated substrings, as for `RUST_DEP_GRAPH_FILTER`:
+//!
+//! - `dependents FILTER`: all the nodes which (transitively) read a node
+//!   matching `FILTER`, i.e., which may be recomputed if it changes;
+//! - `dependencies FILTER`: all the nodes which a node matching `FILTER`
+//!   (transitively) read;
+//! - `path SOURCE -> TARGET`: a shortest chain of reads through which a
+//!   change to `SOURCE` reaches `TARGET`;
+//! - `fan-in [N]`: the `N` (default 20) nodes which are read by the largest
+//!   number of other nodes;
+//! - `dot SOURCE -> TARGET`: writes the subgraph between `SOURCE` and `TARGET`
+//!   to `$RUST_DEP_GRAPH` (default: `dep_graph`), in the same formats as
+//!   `-Z dump-dep-graph`. Either side of the arrow may be left empty.
+
+use crate::assert_dep_graph::write_graph;
+use rustc::dep_graph::debug::{DepNodeFilter, EdgeFilter};
+use rustc::dep_graph::{DepGraphQuery, DepNode};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::graph::implementation::{NodeIndex, INCOMING};
+
+use std::collections::VecDeque;
+use std::env;
+
+const DEFAULT_FAN_IN_COUNT: usize = 20;
+
+pub fn query_saved_dep_graph(tcx: TyCtxt<'_>, command: &str) {
+    tcx.dep_graph.with_ignore(|| {
+        if !tcx.dep_graph.is_fully_enabled() {
+            tcx.sess.err("`-Z query-saved-dep-graph` requires `-C incremental`");
+            return;
+        }
+
+        let query = tcx.dep_graph.previous_query();
+        if query.graph.len_nodes() == 0 {
+            tcx.sess
+                .err("no dep-graph was saved by a previous session in the incremental directory");
+            return;
+        }
+
+        let command = command.trim();
+        let (verb, args) = match command.find(char::is_whitespace) {
+            Some(i) => (&command[..i], command[i..].trim()),
+            None => (command, ""),
+        };
+
+        match verb {
+            "dependents" => print_reachable(tcx, &query, args, true),
+            "dependencies" => print_reachable(tcx, &query, args, false),
+            "path" => print_path(tcx, &query, args),
+            "fan-in" => print_fan_in(tcx, &query, args),
+            "dot" => write_dot(tcx, &query, args),
+            _ => {
+                tcx.sess
+                    .struct_err(&format!("unknown dep-graph query `{}`", verb))
+                    .note("expected one of `dependents`, `dependencies`, `path`, `fan-in` or `dot`")
+                    .emit();
+            }
+        }
+    })
+}
+
+fn matching_nodes<'q>(
+    tcx: TyCtxt<'_>,
+    query: &'q DepGraphQuery,
+    text: &str,
+) -> Option<Vec<&'q DepNode>> {
+    let filter = DepNodeFilter::new(text);
+    if filter.accepts_all() {
+        tcx.sess.err("expected a non-empty node filter");
+        return None;
+    }
+
+    let nodes: Vec<_> = query.nodes().into_iter().filter(|n| filter.test(n)).collect();
+    if nodes.is_empty() {
+        tcx.sess.err(&format!("no node in the saved dep-graph matches `{}`", text.trim()));
+        return None;
+    }
+    Some(nodes)
+}
+
+fn print_reachable(tcx: TyCtxt<'_>, query: &DepGraphQuery, args: &str, dependents: bool) {
+    let starts = match matching_nodes(tcx, query, args) {
+        Some(starts) => starts,
+        None => return,
+    };
+
+    for start in starts {
+        // Edges go from each node to the nodes it read, so the nodes which
+        // depend on `start` are its predecessors.
+        let mut reached = if dependents {
+            query.transitive_predecessors(start)
+        } else {
+            query.transitive_successors(start)
+        };
+        reached.retain(|&n| n != start);
+        reached.sort_by_cached_key(|n| format!("{:?}", n));
+
+        let what = if dependents { "dependents" } else { "dependencies" };
+        println!("{:?}: {} {}", start, reached.len(), what);
+        for node in reached {
+            println!("    {:?}", node);
+        }
+    }
+}
+
+fn print_path(tcx: TyCtxt<'_>, query: &DepGraphQuery, args: &str) {
+    let (source, target) = match args.find("->") {
+        Some(i) => (&args[..i], &args[i + 2..]),
+        None => {
+            tcx.sess.err(&format!("expected a path query like `a&b -> c&d`, not `{}`", args));
+            return;
+        }
+    };
+    let sources = match matching_nodes(tcx, query, source) {
+        Some(sources) => sources,
+        None => return,
+    };
+    let targets: FxHashSet<_> = match matching_nodes(tcx, query, target) {
+        Some(targets) => targets.into_iter().map(|n| query.indices[n]).collect(),
+        None => return,
+    };
+
+    // Breadth-first search from the sources, along the edges *into* each
+    // node, so that every step goes from a node to one which read it.
+    let mut parents: FxHashMap<NodeIndex, Option<NodeIndex>> = FxHashMap::default();
+    let mut queue = VecDeque::new();
+    for source in sources {
+        let index = query.indices[source];
+        parents.insert(index, None);
+        queue.push_back(index);
+    }
+
+    while let Some(index) = queue.pop_front() {
+        if targets.contains(&index) {
+            let mut path = vec![index];
+            while let Some(&Some(parent)) = parents.get(path.last().unwrap()) {
+                path.push(parent);
+            }
+
+            println!("path of length {}:", path.len() - 1);
+            for index in path.into_iter().rev() {
+                println!("    {:?}", query.graph.node_data(index));
+            }
+            return;
+        }
+
+        for (_, edge) in query.graph.adjacent_edges(index, INCOMING) {
+            let neighbor = edge.source_or_target(INCOMING);
+            if !parents.contains_key(&neighbor) {
+                parents.insert(neighbor, Some(index));
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    println!("no path from `{}` to `{}`", source.trim(), target.trim());
+}
+
+fn print_fan_in(tcx: TyCtxt<'_>, query: &DepGraphQuery, args: &str) {
+    let count = if args.is_empty() {
+        DEFAULT_FAN_IN_COUNT
+    } else {
+        match args.parse() {
+            Ok(count) => count,
+            Err(_) => {
+                tcx.sess.err(&format!("expected a number of nodes, found `{}`", args));
+                return;
+            }
+        }
+    };
+
+    let mut fan_in: Vec<_> = query
+        .graph
+        .enumerated_nodes()
+        .map(|(index, node)| (query.graph.incoming_edges(index).count(), &node.data))
+        .collect();
+    fan_in.sort_by_cached_key(|&(edges, node)| (std::cmp::Reverse(edges), format!("{:?}", node)));
+
+    for (edges, node) in fan_in.into_iter().take(count) {
+        println!("{:>8} {:?}", edges, node);
+    }
+}
+
+fn write_dot(tcx: TyCtxt<'_>, query: &DepGraphQuery, args: &str) {
+    let edge_filter = match EdgeFilter::new(args) {
+        Ok(edge_filter) => edge_filter,
+        Err(e) => {
+            tcx.sess.err(&format!("invalid subgraph `{}`: {}", args, e));
+            return;
+        }
+    };
+
+    let path = env::var("RUST_DEP_GRAPH").unwrap_or_else(|_| "dep_graph".to_string());
+    write_graph(query, Some(&edge_filter), &path);
+    println!("wrote `{}.txt` and `{}.dot`", path, path);
+}
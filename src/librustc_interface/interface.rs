@@ -2,8 +2,10 @@ pub use crate::passes::BoxedResolver;
 use crate::util;
 
 use rustc::lint;
-use rustc::session::config::{self, ErrorOutputType, Input, OutputFilenames};
-use rustc::session::early_error;
+use rustc::session::config::{
+    self, ErrorOutputType, Input, OutputFilenames, SelfProfileSummaryFormat,
+};
+use rustc::session::{early_error, early_warn};
 use rustc::session::{DiagnosticOutput, Session};
 use rustc::ty;
 use rustc::util::common::ErrorReported;
@@ -200,7 +202,18 @@ pub fn run_compiler_in_existing_thread_pool<R>(
     };
 
     let prof = compiler.sess.prof.clone();
+    let summary_format = compiler.sess.opts.debugging_opts.self_profile_summary;
+    let error_format = compiler.sess.opts.error_format;
     prof.generic_activity("drop_compiler").run(move || drop(compiler));
+
+    match summary_format {
+        Some(SelfProfileSummaryFormat::Table) => prof.print_summary_table(),
+        Some(SelfProfileSummaryFormat::Json) => prof.print_summary_json(),
+        None => {}
+    }
+    if let Err(e) = prof.write_chrome_trace() {
+        early_warn(error_format, &format!("failed to write the self-profile trace: {}", e));
+    }
     r
 }
 
@@ -820,7 +820,9 @@ fn analysis(tcx: TyCtxt<'_>, cnum: CrateNum) -> Result<()> {
     });
 
     sess.time("MIR_borrow_checking", || {
-        tcx.par_body_owners(|def_id| tcx.ensure().mir_borrowck(def_id));
+        tcx.sess.with_deterministic_diagnostics(|| {
+            tcx.par_body_owners(|def_id| tcx.ensure().mir_borrowck(def_id));
+        });
     });
 
     sess.time("dumping_chalk_like_clauses", || {
@@ -59,6 +59,9 @@ enum QueryModifier {
 
     /// Always evaluate the query, ignoring its depdendencies
     EvalAlways,
+
+    /// The query reads results which can be stolen
+    UsesSteal,
 }
 
 impl Parse for QueryModifier {
@@ -124,6 +127,8 @@ impl Parse for QueryModifier {
             Ok(QueryModifier::Anon)
         } else if modifier == "eval_always" {
             Ok(QueryModifier::EvalAlways)
+        } else if modifier == "uses_steal" {
+            Ok(QueryModifier::UsesSteal)
         } else {
             Err(Error::new(modifier.span(), "unknown query modifier"))
         }
@@ -230,6 +235,10 @@ struct QueryModifiers {
 
     // Always evaluate the query, ignoring its depdendencies
     eval_always: bool,
+
+    /// Don't recompute the query after its result was loaded from disk, since
+    /// what it reads may have been stolen by then
+    uses_steal: bool,
 }
 
 /// Process query modifiers into a struct, erroring on duplicates
@@ -244,6 +253,7 @@ fn process_modifiers(query: &mut Query) -> QueryModifiers {
     let mut no_force = false;
     let mut anon = false;
     let mut eval_always = false;
+    let mut uses_steal = false;
     for modifier in query.modifiers.0.drain(..) {
         match modifier {
             QueryModifier::LoadCached(tcx, id, block) => {
@@ -306,6 +316,12 @@ fn process_modifiers(query: &mut Query) -> QueryModifiers {
                 }
                 eval_always = true;
             }
+            QueryModifier::UsesSteal => {
+                if uses_steal {
+                    panic!("duplicate modifier `uses_steal` for query `{}`", query.name);
+                }
+                uses_steal = true;
+            }
         }
     }
     QueryModifiers {
@@ -319,6 +335,7 @@ fn process_modifiers(query: &mut Query) -> QueryModifiers {
         no_force,
         anon,
         eval_always,
+        uses_steal,
     }
 }
 
@@ -488,6 +505,10 @@ pub fn rustc_queries(input: TokenStream) -> TokenStream {
             if modifiers.eval_always {
                 attributes.push(quote! { eval_always });
             };
+            // Pass on the uses_steal modifier
+            if modifiers.uses_steal {
+                attributes.push(quote! { uses_steal });
+            };
 
             let attribute_stream = quote! {#(#attributes),*};
 
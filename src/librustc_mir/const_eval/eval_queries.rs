@@ -72,6 +72,56 @@ fn eval_body_using_ecx<'mir, 'tcx>(
     Ok(ret)
 }
 
+/// Evaluates a call of the `const fn` `instance` with the given arguments, and returns the
+/// result, whose layout is `ret_layout`, if it is an immediate. Used by `ConstProp` to propagate
+/// constants through calls, so the arguments and the result must not hold pointers, which would
+/// refer to the memory of another interpreter.
+///
+/// The callee may not terminate for these arguments, so the evaluation gives up after
+/// `step_limit` steps.
+pub(crate) fn eval_const_fn_call<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    span: Span,
+    param_env: ty::ParamEnv<'tcx>,
+    instance: ty::Instance<'tcx>,
+    args: &[Immediate],
+    ret_layout: layout::TyLayout<'tcx>,
+    step_limit: usize,
+) -> InterpResult<'tcx, Immediate> {
+    debug!("eval_const_fn_call: {:?}, {:?}", instance, args);
+    let mut ecx = mk_eval_cx(tcx, span, param_env, false);
+    let body = ecx.load_mir(instance.def, None)?;
+    if body.arg_count != args.len() {
+        throw_unsup!(ConstPropUnsupported("calls with untupled arguments aren't supported"));
+    }
+
+    let ret = ecx.allocate(ret_layout, MemoryKind::Stack);
+    ecx.push_stack_frame(
+        instance,
+        span,
+        *body,
+        Some(ret.into()),
+        StackPopCleanup::None { cleanup: false },
+    )?;
+    for (local, &arg) in body.args_iter().zip(args) {
+        let dest = ecx.eval_place(&mir::Place::from(local))?;
+        ecx.write_immediate(arg, dest)?;
+    }
+
+    let mut steps = 0;
+    while ecx.step()? {
+        steps += 1;
+        if steps > step_limit {
+            throw_exhaust!(InfiniteLoop);
+        }
+    }
+
+    match ecx.try_read_immediate(ret.into())? {
+        Ok(imm) => Ok(*imm),
+        Err(_) => throw_unsup!(ConstPropUnsupported("the result of the call isn't an immediate")),
+    }
+}
+
 /// The `InterpCx` is only meant to be used to do field and index projections into constants for
 /// `simd_shuffle` and const patterns in match arms.
 ///
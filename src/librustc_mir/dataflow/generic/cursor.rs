@@ -5,7 +5,7 @@ use std::borrow::Borrow;
 use rustc::mir::{self, BasicBlock, Location};
 use rustc_index::bit_set::BitSet;
 
-use super::{Analysis, Results};
+use super::{Analysis, Direction, Results};
 
 /// A `ResultsCursor` that borrows the underlying `Results`.
 pub type ResultsRefCursor<'a, 'mir, 'tcx, A> = ResultsCursor<'mir, 'tcx, A, &'a Results<'tcx, A>>;
@@ -19,6 +19,8 @@ pub type ResultsRefCursor<'a, 'mir, 'tcx, A> = ResultsCursor<'mir, 'tcx, A, &'a
 ///
 /// A `ResultsCursor` can either own (the default) or borrow the dataflow results it inspects. The
 /// type of ownership is determined by `R` (see `ResultsRefCursor` above).
+///
+/// Only the results of forward analyses can be inspected; use `visit_results` for backward ones.
 pub struct ResultsCursor<'mir, 'tcx, A, R = Results<'tcx, A>>
 where
     A: Analysis<'tcx>,
@@ -46,6 +48,7 @@ where
 {
     /// Returns a new cursor for `results` that points to the start of the `START_BLOCK`.
     pub fn new(body: &'mir mir::Body<'tcx>, results: R) -> Self {
+        assert!(A::Direction::is_forward(), "`ResultsCursor` does not support backward analyses");
         ResultsCursor {
             body,
             pos: CursorPosition::BlockStart(mir::START_BLOCK),
@@ -0,0 +1,211 @@
+//! The direction in which a dataflow analysis propagates its state through the CFG.
+
+use rustc::mir::{self, BasicBlock, Location};
+use rustc_index::bit_set::BitSet;
+
+use super::visitor::{ResultsVisitable, ResultsVisitor};
+use super::{Analysis, GenKillAnalysis, GenKillSet};
+
+/// The direction of a dataflow analysis.
+///
+/// Backward analyses are only partly supported:
+///
+/// - The `dead_unwinds` given to the `Engine` are ignored.
+/// - The edge-specific effects of `SwitchInt` terminators are not applied.
+/// - `ResultsCursor` and the graphviz output only work for forward analyses. Backward results
+///   can be visited with `visit_results`.
+pub trait Direction {
+    fn is_forward() -> bool;
+
+    /// Applies all the effects of the statements and the terminator of `block`, in the order in
+    /// which this direction goes through them. The call return effect is not applied.
+    fn apply_effects_in_block<'tcx, A>(
+        analysis: &A,
+        state: &mut BitSet<A::Idx>,
+        block: BasicBlock,
+        block_data: &mir::BasicBlockData<'tcx>,
+    ) where
+        A: Analysis<'tcx>;
+
+    /// Like `apply_effects_in_block`, but accumulates the effects in a transfer function.
+    fn gen_kill_effects_in_block<'tcx, A>(
+        analysis: &A,
+        trans: &mut GenKillSet<A::Idx>,
+        block: BasicBlock,
+        block_data: &mir::BasicBlockData<'tcx>,
+    ) where
+        A: GenKillAnalysis<'tcx>;
+
+    /// Calls the methods of `vis` for each statement and the terminator of `block`, with `state`
+    /// starting from the entry set of `block`.
+    fn visit_results_in_block<'mir, 'tcx, F, R>(
+        state: &mut F,
+        block: BasicBlock,
+        block_data: &'mir mir::BasicBlockData<'tcx>,
+        results: &R,
+        vis: &mut impl ResultsVisitor<'mir, 'tcx, FlowState = F>,
+    ) where
+        R: ResultsVisitable<'tcx, FlowState = F>;
+}
+
+/// Dataflow from the start of the function to its exits. The entry set of a block holds the
+/// state at the start of that block.
+pub struct Forward;
+
+impl Direction for Forward {
+    fn is_forward() -> bool {
+        true
+    }
+
+    fn apply_effects_in_block<'tcx, A>(
+        analysis: &A,
+        state: &mut BitSet<A::Idx>,
+        block: BasicBlock,
+        block_data: &mir::BasicBlockData<'tcx>,
+    ) where
+        A: Analysis<'tcx>,
+    {
+        for (statement_index, statement) in block_data.statements.iter().enumerate() {
+            let location = Location { block, statement_index };
+            analysis.apply_before_statement_effect(state, statement, location);
+            analysis.apply_statement_effect(state, statement, location);
+        }
+
+        let terminator = block_data.terminator();
+        let location = Location { block, statement_index: block_data.statements.len() };
+        analysis.apply_before_terminator_effect(state, terminator, location);
+        analysis.apply_terminator_effect(state, terminator, location);
+    }
+
+    fn gen_kill_effects_in_block<'tcx, A>(
+        analysis: &A,
+        trans: &mut GenKillSet<A::Idx>,
+        block: BasicBlock,
+        block_data: &mir::BasicBlockData<'tcx>,
+    ) where
+        A: GenKillAnalysis<'tcx>,
+    {
+        for (statement_index, statement) in block_data.statements.iter().enumerate() {
+            let location = Location { block, statement_index };
+            analysis.before_statement_effect(trans, statement, location);
+            analysis.statement_effect(trans, statement, location);
+        }
+
+        let terminator = block_data.terminator();
+        let location = Location { block, statement_index: block_data.statements.len() };
+        analysis.before_terminator_effect(trans, terminator, location);
+        analysis.terminator_effect(trans, terminator, location);
+    }
+
+    fn visit_results_in_block<'mir, 'tcx, F, R>(
+        state: &mut F,
+        block: BasicBlock,
+        block_data: &'mir mir::BasicBlockData<'tcx>,
+        results: &R,
+        vis: &mut impl ResultsVisitor<'mir, 'tcx, FlowState = F>,
+    ) where
+        R: ResultsVisitable<'tcx, FlowState = F>,
+    {
+        for (statement_index, stmt) in block_data.statements.iter().enumerate() {
+            let loc = Location { block, statement_index };
+
+            results.reconstruct_before_statement_effect(state, stmt, loc);
+            vis.visit_statement(state, stmt, loc);
+
+            results.reconstruct_statement_effect(state, stmt, loc);
+            vis.visit_statement_exit(state, stmt, loc);
+        }
+
+        let loc = Location { block, statement_index: block_data.statements.len() };
+        let term = block_data.terminator();
+
+        results.reconstruct_before_terminator_effect(state, term, loc);
+        vis.visit_terminator(state, term, loc);
+
+        results.reconstruct_terminator_effect(state, term, loc);
+        vis.visit_terminator_exit(state, term, loc);
+    }
+}
+
+/// Dataflow from the exits of the function to its start, as for liveness. The entry set of a
+/// block holds the state at the *end* of that block, and the "before" effect of a statement is
+/// the one which is applied first, i.e., the one closest to the next statement.
+///
+/// The call return effect is applied on the edge from the return target of a call to the block
+/// of the call. See `Direction` for what is not supported.
+pub struct Backward;
+
+impl Direction for Backward {
+    fn is_forward() -> bool {
+        false
+    }
+
+    fn apply_effects_in_block<'tcx, A>(
+        analysis: &A,
+        state: &mut BitSet<A::Idx>,
+        block: BasicBlock,
+        block_data: &mir::BasicBlockData<'tcx>,
+    ) where
+        A: Analysis<'tcx>,
+    {
+        let terminator = block_data.terminator();
+        let location = Location { block, statement_index: block_data.statements.len() };
+        analysis.apply_before_terminator_effect(state, terminator, location);
+        analysis.apply_terminator_effect(state, terminator, location);
+
+        for (statement_index, statement) in block_data.statements.iter().enumerate().rev() {
+            let location = Location { block, statement_index };
+            analysis.apply_before_statement_effect(state, statement, location);
+            analysis.apply_statement_effect(state, statement, location);
+        }
+    }
+
+    fn gen_kill_effects_in_block<'tcx, A>(
+        analysis: &A,
+        trans: &mut GenKillSet<A::Idx>,
+        block: BasicBlock,
+        block_data: &mir::BasicBlockData<'tcx>,
+    ) where
+        A: GenKillAnalysis<'tcx>,
+    {
+        let terminator = block_data.terminator();
+        let location = Location { block, statement_index: block_data.statements.len() };
+        analysis.before_terminator_effect(trans, terminator, location);
+        analysis.terminator_effect(trans, terminator, location);
+
+        for (statement_index, statement) in block_data.statements.iter().enumerate().rev() {
+            let location = Location { block, statement_index };
+            analysis.before_statement_effect(trans, statement, location);
+            analysis.statement_effect(trans, statement, location);
+        }
+    }
+
+    fn visit_results_in_block<'mir, 'tcx, F, R>(
+        state: &mut F,
+        block: BasicBlock,
+        block_data: &'mir mir::BasicBlockData<'tcx>,
+        results: &R,
+        vis: &mut impl ResultsVisitor<'mir, 'tcx, FlowState = F>,
+    ) where
+        R: ResultsVisitable<'tcx, FlowState = F>,
+    {
+        let loc = Location { block, statement_index: block_data.statements.len() };
+        let term = block_data.terminator();
+
+        results.reconstruct_before_terminator_effect(state, term, loc);
+        vis.visit_terminator(state, term, loc);
+
+        results.reconstruct_terminator_effect(state, term, loc);
+        vis.visit_terminator_exit(state, term, loc);
+
+        for (statement_index, stmt) in block_data.statements.iter().enumerate().rev() {
+            let loc = Location { block, statement_index };
+
+            results.reconstruct_before_statement_effect(state, stmt, loc);
+            vis.visit_statement(state, stmt, loc);
+
+            results.reconstruct_statement_effect(state, stmt, loc);
+            vis.visit_statement_exit(state, stmt, loc);
+        }
+    }
+}
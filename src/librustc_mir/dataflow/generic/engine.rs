@@ -4,7 +4,7 @@ use std::ffi::OsString;
 use std::fs;
 use std::path::PathBuf;
 
-use rustc::mir::{self, traversal, BasicBlock, Location};
+use rustc::mir::{self, traversal, BasicBlock};
 use rustc::ty::{self, TyCtxt};
 use rustc_ast::ast;
 use rustc_data_structures::work_queue::WorkQueue;
@@ -14,7 +14,7 @@ use rustc_index::vec::IndexVec;
 use rustc_span::symbol::{sym, Symbol};
 
 use super::graphviz;
-use super::{Analysis, GenKillAnalysis, GenKillSet, Results};
+use super::{Analysis, Direction, GenKillAnalysis, GenKillSet, Results};
 
 /// A solver for dataflow problems.
 pub struct Engine<'a, 'tcx, A>
@@ -45,7 +45,8 @@ where
         analysis: A,
     ) -> Self {
         // If there are no back-edges in the control-flow graph, we only ever need to apply the
-        // transfer function for each block exactly once (assuming that we process blocks in RPO).
+        // transfer function for each block exactly once (assuming that we process blocks in RPO,
+        // or in postorder for backward analyses).
         //
         // In this case, there's no need to compute the block transfer functions ahead of time.
         if !body.is_cfg_cyclic() {
@@ -60,17 +61,7 @@ where
 
         for (block, block_data) in body.basic_blocks().iter_enumerated() {
             let trans = &mut trans_for_block[block];
-
-            for (i, statement) in block_data.statements.iter().enumerate() {
-                let loc = Location { block, statement_index: i };
-                analysis.before_statement_effect(trans, statement, loc);
-                analysis.statement_effect(trans, statement, loc);
-            }
-
-            let terminator = block_data.terminator();
-            let loc = Location { block, statement_index: block_data.statements.len() };
-            analysis.before_terminator_effect(trans, terminator, loc);
-            analysis.terminator_effect(trans, terminator, loc);
+            A::Direction::gen_kill_effects_in_block(&analysis, trans, block, block_data);
         }
 
         Self::new(tcx, body, def_id, analysis, Some(trans_for_block))
@@ -111,7 +102,9 @@ where
         };
 
         let mut entry_sets = IndexVec::from_elem(bottom_value_set, body.basic_blocks());
-        analysis.initialize_start_block(body, &mut entry_sets[mir::START_BLOCK]);
+        if A::Direction::is_forward() {
+            analysis.initialize_start_block(body, &mut entry_sets[mir::START_BLOCK]);
+        }
 
         Engine {
             analysis,
@@ -130,6 +123,8 @@ where
     ///
     /// You must take care that `dead_unwinds` does not contain a `BasicBlock` that *can* actually
     /// unwind during execution. Otherwise, your dataflow results will not be correct.
+    ///
+    /// This is ignored by backward analyses.
     pub fn dead_unwinds(mut self, dead_unwinds: &'a BitSet<BasicBlock>) -> Self {
         self.dead_unwinds = Some(dead_unwinds);
         self
@@ -142,9 +137,18 @@ where
         let mut dirty_queue: WorkQueue<BasicBlock> =
             WorkQueue::with_none(self.body.basic_blocks().len());
 
-        for (bb, _) in traversal::reverse_postorder(self.body) {
-            dirty_queue.insert(bb);
-        }
+        // Backward analyses start from the exits, and need the predecessors of each block.
+        let predecessors = if A::Direction::is_forward() {
+            for (bb, _) in traversal::reverse_postorder(self.body) {
+                dirty_queue.insert(bb);
+            }
+            None
+        } else {
+            for (bb, _) in traversal::postorder(self.body) {
+                dirty_queue.insert(bb);
+            }
+            Some(predecessors(self.body))
+        };
 
         // Add blocks that are not reachable from START_BLOCK to the work queue. These blocks will
         // be processed after the ones added above.
@@ -159,11 +163,19 @@ where
             temp_state.overwrite(on_entry);
             self.apply_whole_block_effect(&mut temp_state, bb, bb_data);
 
-            self.propagate_bits_into_graph_successors_of(
-                &mut temp_state,
-                (bb, bb_data),
-                &mut dirty_queue,
-            );
+            match &predecessors {
+                None => self.propagate_bits_into_graph_successors_of(
+                    &mut temp_state,
+                    (bb, bb_data),
+                    &mut dirty_queue,
+                ),
+                Some(predecessors) => self.propagate_bits_into_graph_predecessors_of(
+                    &temp_state,
+                    bb,
+                    &predecessors[bb],
+                    &mut dirty_queue,
+                ),
+            }
         }
 
         let Engine { tcx, body, def_id, trans_for_block, entry_sets, analysis, .. } = self;
@@ -192,17 +204,36 @@ where
         }
 
         // Otherwise apply effects one-by-one.
+        A::Direction::apply_effects_in_block(&self.analysis, state, block, block_data);
+    }
 
-        for (statement_index, statement) in block_data.statements.iter().enumerate() {
-            let location = Location { block, statement_index };
-            self.analysis.apply_before_statement_effect(state, statement, location);
-            self.analysis.apply_statement_effect(state, statement, location);
-        }
+    /// Propagates the state at the start of `bb` to the end of each of its predecessors, for
+    /// backward analyses.
+    fn propagate_bits_into_graph_predecessors_of(
+        &mut self,
+        in_out: &BitSet<A::Idx>,
+        bb: BasicBlock,
+        predecessors: &[BasicBlock],
+        dirty_queue: &mut WorkQueue<BasicBlock>,
+    ) {
+        let body = self.body;
+        for &pred in predecessors {
+            match &body[pred].terminator().kind {
+                // The destination of a call is only written when it returns to `bb`.
+                mir::TerminatorKind::Call {
+                    destination: Some((dest_place, dest_bb)),
+                    func,
+                    args,
+                    ..
+                } if *dest_bb == bb => {
+                    let mut tmp = in_out.clone();
+                    self.analysis.apply_call_return_effect(&mut tmp, pred, func, args, dest_place);
+                    self.propagate_bits_into_entry_set_for(&tmp, pred, dirty_queue);
+                }
 
-        let terminator = block_data.terminator();
-        let location = Location { block, statement_index: block_data.statements.len() };
-        self.analysis.apply_before_terminator_effect(state, terminator, location);
-        self.analysis.apply_terminator_effect(state, terminator, location);
+                _ => self.propagate_bits_into_entry_set_for(in_out, pred, dirty_queue),
+            }
+        }
     }
 
     fn propagate_bits_into_graph_successors_of(
@@ -342,6 +373,18 @@ where
     }
 }
 
+/// Computes the predecessors of each block. A block appears once for each of its edges to a given
+/// successor.
+fn predecessors(body: &mir::Body<'_>) -> IndexVec<BasicBlock, Vec<BasicBlock>> {
+    let mut predecessors = IndexVec::from_elem(vec![], body.basic_blocks());
+    for (bb, data) in body.basic_blocks().iter_enumerated() {
+        for &succ in data.terminator().successors() {
+            predecessors[succ].push(bb);
+        }
+    }
+    predecessors
+}
+
 /// Look at the last statement of a block that ends with  to see if it is an assignment of an enum
 /// discriminant to the local that determines the target of a `SwitchInt` like so:
 ///   _42 = discriminant(..)
@@ -398,6 +441,12 @@ where
         None => return Ok(()),
     };
 
+    // FIXME: The formatters walk each block forward.
+    if !A::Direction::is_forward() {
+        tcx.sess.warn(&format!("cannot write graphviz output for backward analysis `{}`", A::NAME));
+        return Ok(());
+    }
+
     let bits_per_block = results.analysis.bits_per_block(body);
 
     let mut formatter: Box<dyn graphviz::StateFormatter<'tcx, _>> = match attrs.formatter {
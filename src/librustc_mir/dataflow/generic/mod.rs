@@ -44,11 +44,13 @@ use rustc_index::vec::{Idx, IndexVec};
 use crate::dataflow::BottomValue;
 
 mod cursor;
+mod direction;
 mod engine;
 mod graphviz;
 mod visitor;
 
 pub use self::cursor::{ResultsCursor, ResultsRefCursor};
+pub use self::direction::{Backward, Direction, Forward};
 pub use self::engine::Engine;
 pub use self::visitor::{visit_results, ResultsVisitor};
 pub use self::visitor::{BorrowckFlowState, BorrowckResults};
@@ -59,6 +61,7 @@ where
     A: Analysis<'tcx>,
 {
     pub analysis: A,
+    /// The state at the start of each block, or at its end for backward analyses.
     entry_sets: IndexVec<BasicBlock, BitSet<A::Idx>>,
 }
 
@@ -71,7 +74,8 @@ where
         ResultsCursor::new(body, self)
     }
 
-    /// Gets the entry set for the given block.
+    /// Gets the entry set for the given block, which holds the state at its end for backward
+    /// analyses.
     pub fn entry_set_for_block(&self, block: BasicBlock) -> &BitSet<A::Idx> {
         &self.entry_sets[block]
     }
@@ -107,6 +111,9 @@ pub trait AnalysisDomain<'tcx>: BottomValue {
     /// The type of the elements in the state vector.
     type Idx: Idx;
 
+    /// The direction of this analysis, `Forward` unless it is a `Backward` one like liveness.
+    type Direction: Direction = Forward;
+
     /// A descriptive name for this analysis. Used only for debugging.
     ///
     /// This name should be brief and contain no spaces, periods or other characters that are not
@@ -117,7 +124,7 @@ pub trait AnalysisDomain<'tcx>: BottomValue {
     fn bits_per_block(&self, body: &mir::Body<'tcx>) -> usize;
 
     /// Mutates the entry set of the `START_BLOCK` to contain the initial state for dataflow
-    /// analysis.
+    /// analysis. This is not called for backward analyses.
     fn initialize_start_block(&self, body: &mir::Body<'tcx>, state: &mut BitSet<Self::Idx>);
 
     /// Prints an element in the state vector for debugging.
@@ -0,0 +1,120 @@
+use rustc::mir::visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor};
+use rustc::mir::{self, Local, Location};
+use rustc_index::bit_set::BitSet;
+
+use crate::dataflow::generic::{self as dataflow, GenKill};
+use crate::dataflow::BottomValue;
+
+/// Tracks which locals may hold a value, ignoring which parts of them were moved out.
+///
+/// A write to a local, or to any part of it, initializes it, and so does borrowing it. Moving
+/// the whole local out, dropping it, or marking its storage as live or dead, uninitializes it.
+/// Unlike `MaybeInitializedPlaces`, this doesn't need move paths, and works on MIR whose drops
+/// have been elaborated.
+pub struct MaybeInitializedLocals;
+
+impl BottomValue for MaybeInitializedLocals {
+    /// bottom = uninit
+    const BOTTOM_VALUE: bool = false;
+}
+
+impl dataflow::AnalysisDomain<'tcx> for MaybeInitializedLocals {
+    type Idx = Local;
+
+    const NAME: &'static str = "maybe_init_locals";
+
+    fn bits_per_block(&self, body: &mir::Body<'tcx>) -> usize {
+        body.local_decls.len()
+    }
+
+    fn initialize_start_block(&self, body: &mir::Body<'tcx>, entry_set: &mut BitSet<Self::Idx>) {
+        // Function arguments are initialized to begin with.
+        for arg in body.args_iter() {
+            entry_set.insert(arg);
+        }
+    }
+}
+
+impl dataflow::GenKillAnalysis<'tcx> for MaybeInitializedLocals {
+    fn statement_effect(
+        &self,
+        trans: &mut impl GenKill<Self::Idx>,
+        statement: &mir::Statement<'tcx>,
+        loc: Location,
+    ) {
+        TransferFunction { trans }.visit_statement(statement, loc)
+    }
+
+    fn terminator_effect(
+        &self,
+        trans: &mut impl GenKill<Self::Idx>,
+        terminator: &mir::Terminator<'tcx>,
+        loc: Location,
+    ) {
+        TransferFunction { trans }.visit_terminator(terminator, loc)
+    }
+
+    fn call_return_effect(
+        &self,
+        trans: &mut impl GenKill<Self::Idx>,
+        _block: mir::BasicBlock,
+        _func: &mir::Operand<'tcx>,
+        _args: &[mir::Operand<'tcx>],
+        return_place: &mir::Place<'tcx>,
+    ) {
+        // Writing through a pointer doesn't initialize the local holding it.
+        if !return_place.is_indirect() {
+            trans.gen(return_place.local)
+        }
+    }
+}
+
+struct TransferFunction<'a, T> {
+    trans: &'a mut T,
+}
+
+impl<T> Visitor<'tcx> for TransferFunction<'a, T>
+where
+    T: GenKill<Local>,
+{
+    fn visit_place(&mut self, place: &mir::Place<'tcx>, context: PlaceContext, _: Location) {
+        // Writing through a pointer doesn't initialize the local holding it.
+        if !place.is_indirect() {
+            match context {
+                // The destination of a call is only initialized when it returns, which is handled
+                // by `call_return_effect`.
+                PlaceContext::MutatingUse(MutatingUseContext::Call) => {}
+
+                PlaceContext::MutatingUse(MutatingUseContext::Drop)
+                    if place.projection.is_empty() =>
+                {
+                    self.trans.kill(place.local)
+                }
+
+                PlaceContext::MutatingUse(MutatingUseContext::Store)
+                | PlaceContext::MutatingUse(MutatingUseContext::AsmOutput)
+                | PlaceContext::MutatingUse(MutatingUseContext::Borrow)
+                | PlaceContext::MutatingUse(MutatingUseContext::AddressOf) => {
+                    self.trans.gen(place.local)
+                }
+
+                PlaceContext::NonMutatingUse(NonMutatingUseContext::Move)
+                    if place.projection.is_empty() =>
+                {
+                    self.trans.kill(place.local)
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_statement(&mut self, statement: &mir::Statement<'tcx>, location: Location) {
+        match statement.kind {
+            mir::StatementKind::StorageLive(local) | mir::StatementKind::StorageDead(local) => {
+                self.trans.kill(local)
+            }
+            _ => self.super_statement(statement, location),
+        }
+    }
+}
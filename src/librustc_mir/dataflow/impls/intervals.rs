@@ -0,0 +1,857 @@
+//! An interval analysis of the integer locals of a MIR body.
+//!
+//! At each point of the body, this computes the range of values each integer local may hold,
+//! along with a few relations between locals: which ones hold the `Len` of a slice, which ones
+//! are known to be smaller than such a length, and which booleans hold the result of comparing
+//! two integers. A `switchInt` or an `Assert` on such a boolean refines the ranges of the
+//! compared locals on the edges it guards:
+//!
+//!     _5 = Len((*_1));                    // _5: 0..=N, and _5 is `Len(*_1)`
+//!     _6 = Lt(_3, _5);                    // _6 is `Lt(_3, _5)`
+//!     assert(move _6, ...) -> bb2;        // in `bb2`, `_3 < Len(*_1)`
+//!
+//! Intervals don't form a powerset lattice, so unlike the other analyses of this module, this one
+//! doesn't use the generic dataflow framework and computes its own fixpoint. To terminate on
+//! loops, the range of a local is dropped as soon as it keeps growing at the start of a block.
+//!
+//! Booleans are tracked as integers in `0..=1`. Only the locals whose address is never taken are
+//! tracked, as a write through a pointer could change their value behind the analysis' back.
+
+use std::fmt;
+
+use rustc::mir::interpret::sign_extend;
+use rustc::mir::visit::{MutatingUseContext, NonMutatingUseContext, NonUseContext};
+use rustc::mir::visit::{PlaceContext, Visitor};
+use rustc::mir::*;
+use rustc::ty::layout::Size;
+use rustc::ty::{self, ParamEnv, Ty, TyCtxt};
+use rustc_data_structures::work_queue::WorkQueue;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+
+use crate::transform::MirSource;
+use crate::util::{dump_mir, PassWhere};
+
+/// The number of times the state at the start of a block may grow before the ranges which still
+/// grow are dropped.
+const WIDENING_THRESHOLD: usize = 2;
+
+/// An inclusive range of integer values.
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct Interval {
+    lo: i128,
+    hi: i128,
+}
+
+impl Interval {
+    fn new(lo: i128, hi: i128) -> Self {
+        Interval { lo, hi }
+    }
+
+    fn singleton(value: i128) -> Self {
+        Interval { lo: value, hi: value }
+    }
+
+    fn is_empty(self) -> bool {
+        self.lo > self.hi
+    }
+
+    fn contains(self, other: Interval) -> bool {
+        self.lo <= other.lo && other.hi <= self.hi
+    }
+
+    fn hull(self, other: Interval) -> Self {
+        Interval::new(self.lo.min(other.lo), self.hi.max(other.hi))
+    }
+
+    fn intersect(self, other: Interval) -> Self {
+        Interval::new(self.lo.max(other.lo), self.hi.min(other.hi))
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.lo == self.hi {
+            write!(f, "{}", self.lo)
+        } else {
+            write!(f, "{}..={}", self.lo, self.hi)
+        }
+    }
+}
+
+/// An integer operand of a comparison.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Value {
+    Local(Local),
+    Const(i128),
+}
+
+impl Value {
+    fn is(self, local: Local) -> bool {
+        self == Value::Local(local)
+    }
+}
+
+/// The comparison of two integers, whose result is held by a boolean local.
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct Comparison {
+    op: BinOp,
+    lhs: Value,
+    rhs: Value,
+}
+
+impl Comparison {
+    /// Returns the equivalent comparison using only `Lt`, `Le`, `Eq` and `Ne`, or its negation
+    /// when `holds` is false.
+    fn normalize(self, holds: bool) -> (BinOp, Value, Value) {
+        let op = if holds {
+            self.op
+        } else {
+            match self.op {
+                BinOp::Lt => BinOp::Ge,
+                BinOp::Le => BinOp::Gt,
+                BinOp::Gt => BinOp::Le,
+                BinOp::Ge => BinOp::Lt,
+                BinOp::Eq => BinOp::Ne,
+                BinOp::Ne => BinOp::Eq,
+                op => bug!("{:?} is not a comparison", op),
+            }
+        };
+        match op {
+            BinOp::Gt => (BinOp::Lt, self.rhs, self.lhs),
+            BinOp::Ge => (BinOp::Le, self.rhs, self.lhs),
+            op => (op, self.lhs, self.rhs),
+        }
+    }
+}
+
+/// What is known about the locals of a body at some point.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IntervalState {
+    /// Whether this point may be reached at all.
+    reachable: bool,
+    /// The range of each integer local, or `None` if it may hold any value of its type.
+    ranges: IndexVec<Local, Option<Interval>>,
+    /// The range of the result of the `CheckedBinaryOp` held by a local, if it can't overflow.
+    checked: IndexVec<Local, Option<Interval>>,
+    /// The comparison whose result is held by a boolean local.
+    comparisons: IndexVec<Local, Option<Comparison>>,
+    /// The slice pointer whose pointee's `Len` is held by a local.
+    lengths: IndexVec<Local, Option<Local>>,
+    /// The pairs `(i, s)` of locals such that `i < Len(*s)`.
+    below_len: Vec<(Local, Local)>,
+    /// The local this one was copied from, if both still hold the same value.
+    copy_of: IndexVec<Local, Option<Local>>,
+}
+
+impl IntervalState {
+    fn unreachable(local_count: usize) -> Self {
+        IntervalState {
+            reachable: false,
+            ranges: IndexVec::from_elem_n(None, local_count),
+            checked: IndexVec::from_elem_n(None, local_count),
+            comparisons: IndexVec::from_elem_n(None, local_count),
+            lengths: IndexVec::from_elem_n(None, local_count),
+            below_len: vec![],
+            copy_of: IndexVec::from_elem_n(None, local_count),
+        }
+    }
+
+    /// Returns the local the value of `local` was first copied from.
+    fn root(&self, local: Local) -> Local {
+        self.copy_of[local].unwrap_or(local)
+    }
+
+    /// Returns the locals holding the same value as `local`, including itself.
+    fn copies(&self, local: Local) -> impl Iterator<Item = Local> + '_ {
+        let root = self.root(local);
+        self.copy_of.iter_enumerated().filter_map(move |(other, copy_of)| {
+            if other == root || *copy_of == Some(root) { Some(other) } else { None }
+        })
+    }
+
+    /// Forgets everything about `local`, which is about to be overwritten.
+    fn kill(&mut self, local: Local) {
+        self.ranges[local] = None;
+        self.checked[local] = None;
+        self.comparisons[local] = None;
+        self.lengths[local] = None;
+        self.copy_of[local] = None;
+        for comparison in self.comparisons.iter_mut() {
+            if comparison.map_or(false, |c| c.lhs.is(local) || c.rhs.is(local)) {
+                *comparison = None;
+            }
+        }
+        for slice in self.lengths.iter_mut().chain(self.copy_of.iter_mut()) {
+            if *slice == Some(local) {
+                *slice = None;
+            }
+        }
+        self.below_len.retain(|&(index, slice)| index != local && slice != local);
+    }
+
+    /// Records that `dest`, which was just killed, now holds the value of `src`.
+    fn copy(&mut self, src: Local, dest: Local) {
+        self.ranges[dest] = self.ranges[src];
+        self.checked[dest] = self.checked[src];
+        self.comparisons[dest] = self.comparisons[src];
+        self.lengths[dest] = self.lengths[src];
+        for i in 0..self.below_len.len() {
+            let (index, slice) = self.below_len[i];
+            if index == src {
+                self.below_len.push((dest, slice));
+            }
+        }
+        self.copy_of[dest] = Some(self.root(src));
+    }
+
+    /// Joins `other` into this state, and returns whether this state changed. When `widen` is
+    /// set, the ranges which grow are dropped instead.
+    fn join(&mut self, other: &IntervalState, widen: bool) -> bool {
+        if !other.reachable {
+            return false;
+        }
+        if !self.reachable {
+            *self = other.clone();
+            return true;
+        }
+
+        let old = self.clone();
+        let join_ranges = |ranges: &mut IndexVec<Local, Option<Interval>>, other: &IndexVec<_, _>| {
+            for (range, other) in ranges.iter_mut().zip(other.iter()) {
+                *range = match (*range, *other) {
+                    (Some(a), Some(b)) if !widen || a.contains(b) => Some(a.hull(b)),
+                    _ => None,
+                };
+            }
+        };
+        join_ranges(&mut self.ranges, &other.ranges);
+        join_ranges(&mut self.checked, &other.checked);
+        for (comparison, other) in self.comparisons.iter_mut().zip(other.comparisons.iter()) {
+            if comparison != other {
+                *comparison = None;
+            }
+        }
+        for (slice, other) in self.lengths.iter_mut().zip(other.lengths.iter()) {
+            if slice != other {
+                *slice = None;
+            }
+        }
+        for (copy_of, other) in self.copy_of.iter_mut().zip(other.copy_of.iter()) {
+            if copy_of != other {
+                *copy_of = None;
+            }
+        }
+        self.below_len.retain(|fact| other.below_len.contains(fact));
+        *self != old
+    }
+}
+
+impl fmt::Debug for IntervalState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.reachable {
+            return write!(f, "unreachable");
+        }
+        let mut facts = vec![];
+        for (local, range) in self.ranges.iter_enumerated() {
+            if let Some(range) = range {
+                facts.push(format!("{:?}: {:?}", local, range));
+            }
+        }
+        for (local, range) in self.checked.iter_enumerated() {
+            if let Some(range) = range {
+                facts.push(format!("{:?}.0: {:?}", local, range));
+            }
+        }
+        for (local, slice) in self.lengths.iter_enumerated() {
+            if let Some(slice) = slice {
+                facts.push(format!("{:?} = Len(*{:?})", local, slice));
+            }
+        }
+        for (index, slice) in &self.below_len {
+            facts.push(format!("{:?} < Len(*{:?})", index, slice));
+        }
+        write!(f, "{{{}}}", facts.join(", "))
+    }
+}
+
+/// The fixpoint of the interval analysis of a body.
+pub struct Intervals<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    body: &'a Body<'tcx>,
+    /// The range of the type of each tracked integer local.
+    bounds: IndexVec<Local, Option<Interval>>,
+    /// The locals whose address is taken somewhere, which are not tracked.
+    borrowed: BitSet<Local>,
+    entry_states: IndexVec<BasicBlock, IntervalState>,
+}
+
+impl Intervals<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, param_env: ParamEnv<'tcx>, body: &'a Body<'tcx>) -> Self {
+        let mut borrowed = BorrowedLocals(BitSet::new_empty(body.local_decls.len()));
+        for (bb, data) in body.basic_blocks().iter_enumerated() {
+            borrowed.visit_basic_block_data(bb, data);
+        }
+        let borrowed = borrowed.0;
+
+        let mut intervals = Intervals {
+            tcx,
+            param_env,
+            body,
+            bounds: IndexVec::from_elem_n(None, 0),
+            borrowed,
+            entry_states: IndexVec::new(),
+        };
+        intervals.bounds = body
+            .local_decls
+            .iter_enumerated()
+            .map(|(local, decl)| {
+                if intervals.borrowed.contains(local) {
+                    None
+                } else {
+                    intervals.int_bounds(decl.ty)
+                }
+            })
+            .collect();
+        intervals.entry_states = intervals.iterate_to_fixpoint();
+        intervals
+    }
+
+    fn iterate_to_fixpoint(&self) -> IndexVec<BasicBlock, IntervalState> {
+        let local_count = self.body.local_decls.len();
+        let mut entry_states =
+            IndexVec::from_elem(IntervalState::unreachable(local_count), self.body.basic_blocks());
+        entry_states[START_BLOCK].reachable = true;
+        let mut updates = IndexVec::from_elem(0, self.body.basic_blocks());
+
+        let mut dirty_queue: WorkQueue<BasicBlock> =
+            WorkQueue::with_none(self.body.basic_blocks().len());
+        for (bb, _) in traversal::reverse_postorder(self.body) {
+            dirty_queue.insert(bb);
+        }
+
+        while let Some(bb) = dirty_queue.pop() {
+            let mut state = entry_states[bb].clone();
+            if !state.reachable {
+                continue;
+            }
+            let data = &self.body[bb];
+            for (statement_index, statement) in data.statements.iter().enumerate() {
+                let location = Location { block: bb, statement_index };
+                self.apply_statement_effect(&mut state, statement, location);
+            }
+            let location = Location { block: bb, statement_index: data.statements.len() };
+            self.apply_terminator_effect(state, data.terminator(), location, |target, state| {
+                let widen = updates[target] >= WIDENING_THRESHOLD;
+                if entry_states[target].join(&state, widen) {
+                    updates[target] += 1;
+                    dirty_queue.insert(target);
+                }
+            });
+        }
+
+        entry_states
+    }
+
+    /// Returns the state right before the terminator of `block`.
+    pub fn state_before_terminator(&self, block: BasicBlock) -> IntervalState {
+        let mut state = self.entry_states[block].clone();
+        for (statement_index, statement) in self.body[block].statements.iter().enumerate() {
+            self.apply_statement_effect(&mut state, statement, Location { block, statement_index });
+        }
+        state
+    }
+
+    /// Returns the value of the boolean operand `cond` in `state`, if it is always the same.
+    pub fn eval_condition(&self, state: &IntervalState, cond: &Operand<'tcx>) -> Option<bool> {
+        if !state.reachable {
+            return None;
+        }
+        let place = cond.place()?;
+        if let Some(local) = place.as_local() {
+            let comparison = state.comparisons[local];
+            if let Some(value) = comparison.and_then(|c| self.eval_comparison(state, c)) {
+                return Some(value);
+            }
+            return match state.ranges[local] {
+                Some(range) if range.lo == range.hi => Some(range.lo != 0),
+                _ => None,
+            };
+        }
+        // The overflow flag of a `CheckedBinaryOp` which can't overflow.
+        match place.projection.as_ref() {
+            [ProjectionElem::Field(field, _)]
+                if field.index() == 1 && state.checked[place.local].is_some() =>
+            {
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    /// Dumps the body, annotated with the state before each statement, with `-Z dump-mir`.
+    pub fn dump_mir(&self, source: MirSource<'tcx>) {
+        let mut state = IntervalState::unreachable(0);
+        dump_mir(self.tcx, None, "intervals", &0, source, self.body, |pass_where, w| {
+            match pass_where {
+                PassWhere::BeforeBlock(bb) => state = self.entry_states[bb].clone(),
+                PassWhere::BeforeLocation(_) if state.reachable => {
+                    writeln!(w, "        // {:?}", state)?;
+                }
+                PassWhere::AfterLocation(location) => {
+                    let statements = &self.body[location.block].statements;
+                    if let Some(statement) = statements.get(location.statement_index) {
+                        self.apply_statement_effect(&mut state, statement, location);
+                    }
+                }
+                _ => {}
+            }
+            Ok(())
+        });
+    }
+
+    fn apply_statement_effect(
+        &self,
+        state: &mut IntervalState,
+        statement: &Statement<'tcx>,
+        location: Location,
+    ) {
+        if !state.reachable {
+            return;
+        }
+        if let StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+            if let Some(local) = place.as_local() {
+                self.assign(state, local, rvalue);
+                return;
+            }
+        }
+        let mut mutated = MutatedLocals(vec![]);
+        mutated.visit_statement(statement, location);
+        for local in mutated.0 {
+            state.kill(local);
+        }
+    }
+
+    /// Applies the effect of `terminator` to `state`, and calls `propagate` with the state at the
+    /// start of each of its successors.
+    fn apply_terminator_effect(
+        &self,
+        mut state: IntervalState,
+        terminator: &Terminator<'tcx>,
+        location: Location,
+        mut propagate: impl FnMut(BasicBlock, IntervalState),
+    ) {
+        let mut mutated = MutatedLocals(vec![]);
+        mutated.visit_terminator(terminator, location);
+        for local in mutated.0 {
+            state.kill(local);
+        }
+
+        match &terminator.kind {
+            TerminatorKind::SwitchInt { discr, switch_ty, values, targets } => {
+                let discr = discr.place().and_then(|place| place.as_local());
+                let comparison = discr.and_then(|discr| state.comparisons[discr]);
+                // Compares the operand with the value of each target.
+                let discr_comparison = |op, value| match (discr, self.int_size(*switch_ty)) {
+                    (Some(discr), Some((size, signed))) if self.bounds[discr].is_some() => {
+                        let lhs = Value::Local(state.root(discr));
+                        let rhs = Value::Const(int_value(value, size, signed));
+                        Some(Comparison { op, lhs, rhs })
+                    }
+                    _ => None,
+                };
+                for (&value, &target) in values.iter().zip(targets.iter()) {
+                    let mut target_state = state.clone();
+                    if let Some(comparison) = comparison {
+                        self.refine(&mut target_state, comparison, value != 0);
+                    }
+                    if let Some(discr_comparison) = discr_comparison(BinOp::Eq, value) {
+                        self.refine(&mut target_state, discr_comparison, true);
+                    }
+                    propagate(target, target_state);
+                }
+                let otherwise = *targets.last().unwrap();
+                if let [value] = values[..] {
+                    let mut target_state = state.clone();
+                    if let Some(comparison) = comparison {
+                        self.refine(&mut target_state, comparison, value == 0);
+                    }
+                    if let Some(discr_comparison) = discr_comparison(BinOp::Ne, value) {
+                        self.refine(&mut target_state, discr_comparison, true);
+                    }
+                    propagate(otherwise, target_state);
+                } else {
+                    propagate(otherwise, state);
+                }
+            }
+            TerminatorKind::Assert { cond, expected, target, cleanup, .. } => {
+                if let Some(cleanup) = *cleanup {
+                    propagate(cleanup, state.clone());
+                }
+                if let Some(cond) = cond.place().and_then(|place| place.as_local()) {
+                    if let Some(comparison) = state.comparisons[cond] {
+                        self.refine(&mut state, comparison, *expected);
+                    }
+                    if self.bounds[cond].is_some() {
+                        let expected = Interval::singleton(*expected as i128);
+                        self.narrow(&mut state, Value::Local(cond), expected);
+                    }
+                }
+                propagate(*target, state);
+            }
+            _ => {
+                for &target in terminator.successors() {
+                    propagate(target, state.clone());
+                }
+            }
+        }
+    }
+
+    fn assign(&self, state: &mut IntervalState, local: Local, rvalue: &Rvalue<'tcx>) {
+        if self.borrowed.contains(local) {
+            return;
+        }
+
+        let src = match rvalue {
+            Rvalue::Use(operand) => operand.place().and_then(|place| place.as_local()),
+            _ => None,
+        };
+        if let Some(src) = src.filter(|&src| src != local && !self.borrowed.contains(src)) {
+            state.kill(local);
+            state.copy(src, local);
+            return;
+        }
+
+        let mut range = None;
+        let mut checked = None;
+        let mut comparison = None;
+        let mut length = None;
+        let mut copy_of = None;
+        match rvalue {
+            Rvalue::Use(operand) => range = self.operand_range(state, operand),
+            Rvalue::Len(place) => {
+                let (len, slice) = self.len(state, place);
+                range = Some(len);
+                length = slice;
+            }
+            // A reborrow of a slice has the same length.
+            Rvalue::Ref(_, _, place) => match place.projection.as_ref() {
+                [ProjectionElem::Deref] if !self.borrowed.contains(place.local) => {
+                    copy_of = Some(state.root(place.local));
+                }
+                _ => {}
+            },
+            Rvalue::Cast(CastKind::Misc, operand, _) => range = self.operand_range(state, operand),
+            Rvalue::BinaryOp(op, lhs, rhs) => match op {
+                BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge | BinOp::Eq | BinOp::Ne => {
+                    let lhs = self.value(state, lhs);
+                    let rhs = self.value(state, rhs);
+                    if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+                        let c = Comparison { op: *op, lhs, rhs };
+                        let result = self.eval_comparison(state, c);
+                        range = result.map(|result| Interval::singleton(result as i128));
+                        comparison = Some(c);
+                    }
+                }
+                _ => {
+                    if let Some(bounds) = self.bounds[local] {
+                        range = self.arithmetic(state, *op, lhs, rhs, bounds.lo < 0);
+                    }
+                }
+            },
+            Rvalue::CheckedBinaryOp(op @ BinOp::Add, lhs, rhs)
+            | Rvalue::CheckedBinaryOp(op @ BinOp::Sub, lhs, rhs)
+            | Rvalue::CheckedBinaryOp(op @ BinOp::Mul, lhs, rhs) => {
+                let bounds = self.int_bounds(lhs.ty(self.body, self.tcx));
+                if let Some(bounds) = bounds {
+                    checked = self
+                        .arithmetic(state, *op, lhs, rhs, bounds.lo < 0)
+                        .filter(|&result| bounds.contains(result));
+                }
+            }
+            _ => {}
+        }
+
+        // The facts computed from the old value of `local` don't hold for its new value.
+        let comparison = comparison.filter(|c| !c.lhs.is(local) && !c.rhs.is(local));
+        let copy_of = copy_of.filter(|&root| root != local);
+
+        state.kill(local);
+        state.ranges[local] = match (range, self.bounds[local]) {
+            (Some(range), Some(bounds)) if bounds.contains(range) => Some(range),
+            _ => None,
+        };
+        state.checked[local] = checked;
+        state.comparisons[local] = comparison;
+        state.lengths[local] = length;
+        state.copy_of[local] = copy_of;
+    }
+
+    /// Restricts `state` to the executions in which `comparison` evaluates to `holds`.
+    fn refine(&self, state: &mut IntervalState, comparison: Comparison, holds: bool) {
+        let (op, lhs, rhs) = comparison.normalize(holds);
+        let a = self.value_range(state, lhs);
+        let b = self.value_range(state, rhs);
+        match op {
+            BinOp::Lt => {
+                self.narrow(state, lhs, Interval::new(a.lo, a.hi.min(b.hi - 1)));
+                self.narrow(state, rhs, Interval::new(b.lo.max(a.lo + 1), b.hi));
+                if let (Value::Local(index), Value::Local(len)) = (lhs, rhs) {
+                    if let Some(slice) = state.lengths[len] {
+                        let copies: Vec<_> = state.copies(index).collect();
+                        for index in copies {
+                            if !state.below_len.contains(&(index, slice)) {
+                                state.below_len.push((index, slice));
+                            }
+                        }
+                    }
+                }
+            }
+            BinOp::Le => {
+                self.narrow(state, lhs, Interval::new(a.lo, a.hi.min(b.hi)));
+                self.narrow(state, rhs, Interval::new(b.lo.max(a.lo), b.hi));
+            }
+            BinOp::Eq => {
+                self.narrow(state, lhs, b);
+                self.narrow(state, rhs, a);
+            }
+            BinOp::Ne => {
+                // Only a value at the end of a range can be removed from it.
+                if b.lo == b.hi {
+                    let lo = if a.lo == b.lo { a.lo + 1 } else { a.lo };
+                    let hi = if a.hi == b.lo { a.hi - 1 } else { a.hi };
+                    self.narrow(state, lhs, Interval::new(lo, hi));
+                }
+                if a.lo == a.hi {
+                    let lo = if b.lo == a.lo { b.lo + 1 } else { b.lo };
+                    let hi = if b.hi == a.lo { b.hi - 1 } else { b.hi };
+                    self.narrow(state, rhs, Interval::new(lo, hi));
+                }
+            }
+            _ => bug!("{:?} is not a normalized comparison", op),
+        }
+    }
+
+    /// Restricts `state` to the executions in which `value` is in `range`.
+    fn narrow(&self, state: &mut IntervalState, value: Value, range: Interval) {
+        match value {
+            Value::Const(value) => {
+                if !range.contains(Interval::singleton(value)) {
+                    state.reachable = false;
+                }
+            }
+            Value::Local(local) => {
+                let copies: Vec<_> = state.copies(local).collect();
+                for local in copies {
+                    if let Some(bounds) = self.bounds[local] {
+                        let narrowed = state.ranges[local].unwrap_or(bounds).intersect(range);
+                        if narrowed.is_empty() {
+                            state.reachable = false;
+                        }
+                        state.ranges[local] = Some(narrowed);
+                    }
+                }
+            }
+        }
+    }
+
+    fn eval_comparison(&self, state: &IntervalState, comparison: Comparison) -> Option<bool> {
+        let (op, lhs, rhs) = comparison.normalize(true);
+        let a = self.value_range(state, lhs);
+        let b = self.value_range(state, rhs);
+        let below_len = match (lhs, rhs) {
+            (Value::Local(index), Value::Local(len)) => state.lengths[len]
+                .map_or(false, |slice| state.below_len.contains(&(index, slice))),
+            _ => false,
+        };
+        match op {
+            BinOp::Lt if a.hi < b.lo || below_len => Some(true),
+            BinOp::Lt if a.lo >= b.hi => Some(false),
+            BinOp::Le if a.hi <= b.lo || below_len => Some(true),
+            BinOp::Le if a.lo > b.hi => Some(false),
+            BinOp::Eq | BinOp::Ne => {
+                let equal = if a.lo == a.hi && a == b {
+                    true
+                } else if a.intersect(b).is_empty() {
+                    false
+                } else {
+                    return None;
+                };
+                Some(equal == (op == BinOp::Eq))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the result range of `lhs op rhs`, if it is computed without overflow.
+    fn arithmetic(
+        &self,
+        state: &IntervalState,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+        signed: bool,
+    ) -> Option<Interval> {
+        let a = self.operand_range(state, lhs)?;
+        let b = self.operand_range(state, rhs)?;
+        match op {
+            BinOp::Add => Some(Interval::new(a.lo.checked_add(b.lo)?, a.hi.checked_add(b.hi)?)),
+            BinOp::Sub => Some(Interval::new(a.lo.checked_sub(b.hi)?, a.hi.checked_sub(b.lo)?)),
+            BinOp::Mul => {
+                let products = [
+                    a.lo.checked_mul(b.lo)?,
+                    a.lo.checked_mul(b.hi)?,
+                    a.hi.checked_mul(b.lo)?,
+                    a.hi.checked_mul(b.hi)?,
+                ];
+                let lo = products.iter().min().unwrap();
+                let hi = products.iter().max().unwrap();
+                Some(Interval::new(*lo, *hi))
+            }
+            BinOp::Div if !signed && b.lo > 0 => Some(Interval::new(a.lo / b.hi, a.hi / b.lo)),
+            BinOp::Rem if !signed && b.lo > 0 => Some(Interval::new(0, a.hi.min(b.hi - 1))),
+            BinOp::BitAnd if !signed => Some(Interval::new(0, a.hi.min(b.hi))),
+            _ => None,
+        }
+    }
+
+    /// Returns the range of `Len(place)`, and the slice pointer it is the length of, if any.
+    fn len(&self, state: &IntervalState, place: &Place<'tcx>) -> (Interval, Option<Local>) {
+        let usize_bounds = self.int_bounds(self.tcx.types.usize).unwrap();
+        let elem_ty = match place.ty(self.body, self.tcx).ty.kind {
+            ty::Array(elem_ty, len) => match len.try_eval_usize(self.tcx, self.param_env) {
+                Some(len) => return (Interval::singleton(len as i128), None),
+                None => elem_ty,
+            },
+            ty::Slice(elem_ty) => elem_ty,
+            _ => return (usize_bounds, None),
+        };
+
+        // No object is larger than `obj_size_bound`, which limits the length of non-ZST slices.
+        let max_len = match self.tcx.layout_of(self.param_env.and(elem_ty)) {
+            Ok(layout) if layout.size.bytes() > 0 => {
+                (self.tcx.data_layout.obj_size_bound() / layout.size.bytes()) as i128
+            }
+            _ => usize_bounds.hi,
+        };
+        let slice = match place.projection.as_ref() {
+            [ProjectionElem::Deref] if !self.borrowed.contains(place.local) => {
+                Some(state.root(place.local))
+            }
+            _ => None,
+        };
+        (Interval::new(0, max_len), slice)
+    }
+
+    /// Returns `operand` as a tracked local, or as an integer constant.
+    fn value(&self, state: &IntervalState, operand: &Operand<'tcx>) -> Option<Value> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                let local = place.as_local().filter(|&local| self.bounds[local].is_some())?;
+                Some(Value::Local(state.root(local)))
+            }
+            Operand::Constant(constant) => {
+                let ty = constant.literal.ty;
+                let (size, signed) = self.int_size(ty)?;
+                let bits = constant.literal.try_eval_bits(self.tcx, self.param_env, ty)?;
+                Some(Value::Const(int_value(bits, size, signed)))
+            }
+        }
+    }
+
+    fn value_range(&self, state: &IntervalState, value: Value) -> Interval {
+        match value {
+            Value::Local(local) => state.ranges[local].or(self.bounds[local]).unwrap(),
+            Value::Const(value) => Interval::singleton(value),
+        }
+    }
+
+    fn operand_range(&self, state: &IntervalState, operand: &Operand<'tcx>) -> Option<Interval> {
+        if let Some(value) = self.value(state, operand) {
+            return Some(self.value_range(state, value));
+        }
+        if let Some(place) = operand.place() {
+            // The result of a `CheckedBinaryOp`.
+            if let [ProjectionElem::Field(field, _)] = place.projection.as_ref() {
+                if field.index() == 0 && state.checked[place.local].is_some() {
+                    return state.checked[place.local];
+                }
+            }
+        }
+        self.int_bounds(operand.ty(self.body, self.tcx))
+    }
+
+    /// Returns the size and signedness of the boolean or integer type `ty`, if it is at most 64
+    /// bits wide.
+    fn int_size(&self, ty: Ty<'tcx>) -> Option<(Size, bool)> {
+        let pointer_bits = self.tcx.data_layout.pointer_size.bits() as usize;
+        let (bits, signed) = match ty.kind {
+            ty::Bool => (8, false),
+            ty::Int(ity) => (ity.bit_width().unwrap_or(pointer_bits), true),
+            ty::Uint(uty) => (uty.bit_width().unwrap_or(pointer_bits), false),
+            _ => return None,
+        };
+        if bits > 64 { None } else { Some((Size::from_bits(bits as u64), signed)) }
+    }
+
+    fn int_bounds(&self, ty: Ty<'tcx>) -> Option<Interval> {
+        if ty.is_bool() {
+            return Some(Interval::new(0, 1));
+        }
+        let (size, signed) = self.int_size(ty)?;
+        let bits = size.bits();
+        Some(if signed {
+            Interval::new(-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+        } else {
+            Interval::new(0, (1 << bits) - 1)
+        })
+    }
+}
+
+/// Returns the value of the integer of the given size and signedness whose bits are `bits`.
+fn int_value(bits: u128, size: Size, signed: bool) -> i128 {
+    if signed { sign_extend(bits, size) as i128 } else { bits as i128 }
+}
+
+/// Collects the locals whose address is taken somewhere in the body.
+struct BorrowedLocals(BitSet<Local>);
+
+impl<'tcx> Visitor<'tcx> for BorrowedLocals {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, _: Location) {
+        // Borrowing something behind a pointer doesn't borrow the local holding the pointer.
+        if !place.is_indirect() {
+            match context {
+                PlaceContext::MutatingUse(MutatingUseContext::Borrow)
+                | PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::ShallowBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::UniqueBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf) => {
+                    self.0.insert(place.local);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Collects the locals which a statement or terminator writes to, or whose storage it changes.
+struct MutatedLocals(Vec<Local>);
+
+impl<'tcx> Visitor<'tcx> for MutatedLocals {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, _: Location) {
+        // A write through a pointer can't change a tracked local, as none is borrowed.
+        if context.is_mutating_use() && !place.is_indirect() {
+            self.0.push(place.local);
+        }
+    }
+
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, _: Location) {
+        match context {
+            PlaceContext::NonUse(NonUseContext::StorageLive)
+            | PlaceContext::NonUse(NonUseContext::StorageDead) => self.0.push(local),
+            _ => {}
+        }
+    }
+}
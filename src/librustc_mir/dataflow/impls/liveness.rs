@@ -0,0 +1,181 @@
+use rustc::mir::visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor};
+use rustc::mir::{self, Local, Location};
+use rustc_index::bit_set::BitSet;
+
+use crate::dataflow::generic::{self as dataflow, Backward, GenKill};
+use crate::dataflow::BottomValue;
+
+/// A [live-variable dataflow analysis][liveness].
+///
+/// A local is *live* at a location if its current value may be read later. Writing to a whole
+/// local kills it, while writing to a part of it, e.g. to one of its fields or to its
+/// discriminant, neither kills it nor makes it live. Borrowing a local, or writing through a
+/// pointer it holds, makes it live.
+///
+/// This is a backward analysis: the entry set of a block holds the locals which are live at the
+/// *end* of that block.
+///
+/// [liveness]: https://en.wikipedia.org/wiki/Live_variable_analysis
+pub struct MaybeLiveLocals;
+
+impl MaybeLiveLocals {
+    fn transfer_function<T>(&self, trans: &'a mut T) -> TransferFunction<'a, T> {
+        TransferFunction(trans)
+    }
+}
+
+impl BottomValue for MaybeLiveLocals {
+    // bottom = not live
+    const BOTTOM_VALUE: bool = false;
+}
+
+impl dataflow::AnalysisDomain<'tcx> for MaybeLiveLocals {
+    type Idx = Local;
+    type Direction = Backward;
+
+    const NAME: &'static str = "liveness";
+
+    fn bits_per_block(&self, body: &mir::Body<'tcx>) -> usize {
+        body.local_decls.len()
+    }
+
+    fn initialize_start_block(&self, _: &mir::Body<'tcx>, _: &mut BitSet<Self::Idx>) {
+        // No variables are live until we observe a use
+    }
+}
+
+impl dataflow::GenKillAnalysis<'tcx> for MaybeLiveLocals {
+    fn statement_effect(
+        &self,
+        trans: &mut impl GenKill<Self::Idx>,
+        statement: &mir::Statement<'tcx>,
+        location: Location,
+    ) {
+        self.transfer_function(trans).visit_statement(statement, location);
+    }
+
+    fn terminator_effect(
+        &self,
+        trans: &mut impl GenKill<Self::Idx>,
+        terminator: &mir::Terminator<'tcx>,
+        location: Location,
+    ) {
+        match &terminator.kind {
+            // The return place is read when returning, but the visitor does not see it.
+            mir::TerminatorKind::Return => trans.gen(mir::RETURN_PLACE),
+
+            // The resume argument is only written if the generator is resumed, and not when it is
+            // dropped, so it stays live across the `yield`.
+            mir::TerminatorKind::Yield { value, .. } => {
+                self.transfer_function(trans).visit_operand(value, location)
+            }
+
+            _ => self.transfer_function(trans).visit_terminator(terminator, location),
+        }
+    }
+
+    fn call_return_effect(
+        &self,
+        trans: &mut impl GenKill<Self::Idx>,
+        _block: mir::BasicBlock,
+        _func: &mir::Operand<'tcx>,
+        _args: &[mir::Operand<'tcx>],
+        dest_place: &mir::Place<'tcx>,
+    ) {
+        if let Some(local) = dest_place.as_local() {
+            trans.kill(local);
+        }
+    }
+}
+
+struct TransferFunction<'a, T>(&'a mut T);
+
+impl<'tcx, T> Visitor<'tcx> for TransferFunction<'_, T>
+where
+    T: GenKill<Local>,
+{
+    fn visit_statement(&mut self, statement: &mir::Statement<'tcx>, location: Location) {
+        // The visitor sees the place of a `SetDiscriminant` as stored to, but the payload of the
+        // variant is written by other statements, which it must not make dead.
+        if let mir::StatementKind::SetDiscriminant { place, .. } = &statement.kind {
+            if !place.is_indirect() {
+                return;
+            }
+        }
+
+        self.super_statement(statement, location);
+    }
+
+    fn visit_place(&mut self, place: &mir::Place<'tcx>, context: PlaceContext, location: Location) {
+        match DefUse::for_place(place, context) {
+            Some(DefUse::Def) => self.0.kill(place.local),
+            Some(DefUse::Use) => self.0.gen(place.local),
+            None => {}
+        }
+
+        // Visit the locals used as indices. We purposefully do not call `super_place`, which would
+        // call `visit_local` for the base local with a `Projection` context.
+        self.visit_projection(&place.local, &place.projection, context, location);
+    }
+
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, _: Location) {
+        match DefUse::for_place(&local.into(), context) {
+            Some(DefUse::Def) => self.0.kill(local),
+            Some(DefUse::Use) => self.0.gen(local),
+            None => {}
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone)]
+enum DefUse {
+    Def,
+    Use,
+}
+
+impl DefUse {
+    fn for_place(place: &mir::Place<'_>, context: PlaceContext) -> Option<DefUse> {
+        match context {
+            PlaceContext::NonUse(_) => None,
+
+            PlaceContext::MutatingUse(MutatingUseContext::Store)
+            | PlaceContext::MutatingUse(MutatingUseContext::AsmOutput) => {
+                if place.is_indirect() {
+                    // Treat derefs as a use of the base local. `*p = 4` is not a def of `p` but a
+                    // use.
+                    Some(DefUse::Use)
+                } else if place.projection.is_empty() {
+                    Some(DefUse::Def)
+                } else {
+                    None
+                }
+            }
+
+            // The destination of a call is only written if the call returns, which is handled by
+            // `call_return_effect`.
+            PlaceContext::MutatingUse(MutatingUseContext::Call) => {
+                if place.is_indirect() {
+                    Some(DefUse::Use)
+                } else {
+                    None
+                }
+            }
+
+            // All other contexts are uses: reads, borrows, drops, retags and the bases of
+            // projections.
+            PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+            | PlaceContext::MutatingUse(MutatingUseContext::Borrow)
+            | PlaceContext::MutatingUse(MutatingUseContext::Drop)
+            | PlaceContext::MutatingUse(MutatingUseContext::Retag)
+            | PlaceContext::MutatingUse(MutatingUseContext::Projection)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Copy)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Inspect)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Move)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::ShallowBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::UniqueBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::Projection) => Some(DefUse::Use),
+        }
+    }
+}
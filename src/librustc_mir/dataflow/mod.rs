@@ -23,8 +23,10 @@ pub(crate) use self::drop_flag_effects::*;
 pub use self::impls::borrows::Borrows;
 pub use self::impls::DefinitelyInitializedPlaces;
 pub use self::impls::EverInitializedPlaces;
+pub use self::impls::{IntervalState, Intervals};
+pub use self::impls::MaybeLiveLocals;
 pub use self::impls::{MaybeBorrowedLocals, MaybeMutBorrowedLocals};
-pub use self::impls::{MaybeInitializedPlaces, MaybeUninitializedPlaces};
+pub use self::impls::{MaybeInitializedLocals, MaybeInitializedPlaces, MaybeUninitializedPlaces};
 pub use self::impls::{MaybeRequiresStorage, MaybeStorageLive};
 
 use self::move_paths::MoveData;
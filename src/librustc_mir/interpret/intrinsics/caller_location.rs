@@ -12,9 +12,29 @@ impl<'mir, 'tcx, M: Machine<'mir, 'tcx>> InterpCx<'mir, 'tcx, M> {
     /// Walks up the callstack from the intrinsic's callsite, searching for the first callsite in a
     /// frame which is not `#[track_caller]`. If the first frame found lacks `#[track_caller]`, then
     /// `None` is returned and the callsite of the function invocation itself should be used.
+    ///
+    /// Functions which the MIR inliner inlined into a frame are treated like frames of their own,
+    /// by walking up the `SourceScope`s of the callsite in that frame.
     crate fn find_closest_untracked_caller_location(&self) -> Option<Span> {
         let mut caller_span = None;
         for next_caller in self.stack.iter().rev() {
+            if let Some(source_info) = next_caller.current_source_info() {
+                let mut scope = source_info.scope;
+                loop {
+                    let scope_data = &next_caller.body.source_scopes[scope];
+                    if let Some((callee, callsite_span)) = scope_data.inlined {
+                        if !callee.def.requires_caller_location(*self.tcx) {
+                            return caller_span;
+                        }
+                        caller_span = Some(callsite_span);
+                    }
+                    match scope_data.parent_scope {
+                        Some(parent) => scope = parent,
+                        None => break,
+                    }
+                }
+            }
+
             if !next_caller.instance.def.requires_caller_location(*self.tcx) {
                 return caller_span;
             }
@@ -5,6 +5,7 @@ Rust MIR: a lowered representation of Rust.
 */
 
 #![feature(nll)]
+#![feature(associated_type_defaults)]
 #![feature(in_band_lifetimes)]
 #![feature(bool_to_option)]
 #![feature(box_patterns)]
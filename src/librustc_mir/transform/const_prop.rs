@@ -1,5 +1,9 @@
 //! Propagates constants for early reporting of statically known
 //! assertion failures
+//!
+//! The values of temporaries are tracked through the fields of aggregates, shared borrows of
+//! locals which can't be mutated through them, and calls to `const fn`s with constant arguments,
+//! which are evaluated by the const evaluator.
 
 use std::borrow::Cow;
 use std::cell::Cell;
@@ -10,16 +14,16 @@ use rustc::mir::visit::{
     MutVisitor, MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor,
 };
 use rustc::mir::{
-    read_only, AggregateKind, AssertKind, BasicBlock, BinOp, Body, BodyAndCache, ClearCrossCrate,
-    Constant, Local, LocalDecl, LocalKind, Location, Operand, Place, ReadOnlyBodyAndCache, Rvalue,
-    SourceInfo, SourceScope, SourceScopeData, Statement, StatementKind, Terminator, TerminatorKind,
-    UnOp, RETURN_PLACE,
+    read_only, AggregateKind, AssertKind, BasicBlock, BinOp, Body, BodyAndCache, BorrowKind,
+    ClearCrossCrate, Constant, Local, LocalDecl, LocalKind, Location, Operand, Place,
+    ReadOnlyBodyAndCache, Rvalue, SourceInfo, SourceScope, SourceScopeData, Statement,
+    StatementKind, Terminator, TerminatorKind, UnOp, RETURN_PLACE,
 };
 use rustc::ty::layout::{
-    HasDataLayout, HasTyCtxt, LayoutError, LayoutOf, Size, TargetDataLayout, TyLayout,
+    Abi, HasDataLayout, HasTyCtxt, LayoutError, LayoutOf, Size, TargetDataLayout, TyLayout,
 };
 use rustc::ty::subst::{InternalSubsts, Subst};
-use rustc::ty::{self, ConstKind, Instance, ParamEnv, Ty, TyCtxt, TypeFoldable};
+use rustc::ty::{self, ConstKind, Instance, InstanceDef, ParamEnv, Ty, TyCtxt, TypeFoldable};
 use rustc_ast::ast::Mutability;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def::DefKind;
@@ -28,20 +32,28 @@ use rustc_index::vec::IndexVec;
 use rustc_infer::traits;
 use rustc_span::Span;
 
-use crate::const_eval::error_to_const_error;
+use crate::const_eval::{error_to_const_error, eval_const_fn_call};
 use crate::interpret::{
     self, intern_const_alloc_recursive, AllocId, Allocation, Frame, ImmTy, Immediate, InternKind,
     InterpCx, LocalState, LocalValue, Memory, MemoryKind, OpTy, Operand as InterpOperand, PlaceTy,
     Pointer, ScalarMaybeUndef, StackPopCleanup,
 };
-use crate::transform::{MirPass, MirSource};
+use crate::transform::inline::Inline;
+use crate::transform::{is_pass_enabled, MirPass, MirSource};
 
 /// The maximum number of bytes that we'll allocate space for a return value.
 const MAX_ALLOC_LIMIT: u64 = 1024;
 
+/// The maximum number of interpreter steps spent on evaluating a call to a `const fn`.
+const MAX_CALL_STEPS: usize = 10_000;
+
 pub struct ConstProp;
 
 impl<'tcx> MirPass<'tcx> for ConstProp {
+    fn is_required(&self) -> bool {
+        false
+    }
+
     fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
         // will be evaluated by miri and produce its errors there
         if source.promoted.is_some() {
@@ -285,7 +297,7 @@ struct ConstPropagator<'mir, 'tcx> {
     param_env: ParamEnv<'tcx>,
     // FIXME(eddyb) avoid cloning these two fields more than once,
     // by accessing them through `ecx` instead.
-    source_scopes: IndexVec<SourceScope, SourceScopeData>,
+    source_scopes: IndexVec<SourceScope, SourceScopeData<'tcx>>,
     local_decls: IndexVec<Local, LocalDecl<'tcx>>,
     ret: Option<OpTy<'tcx, ()>>,
     // Because we have `MutVisitor` we can't obtain the `SourceInfo` from a `Location`. So we store
@@ -336,7 +348,7 @@ impl<'mir, 'tcx> ConstPropagator<'mir, 'tcx> {
 
         let span = tcx.def_span(def_id);
         let mut ecx = InterpCx::new(tcx.at(span), param_env, ConstPropMachine, ());
-        let can_const_prop = CanConstProp::check(body);
+        let can_const_prop = CanConstProp::check(tcx, param_env, body);
 
         let ret = ecx
             .layout_of(body.return_ty().subst(tcx, substs))
@@ -622,7 +634,11 @@ impl<'mir, 'tcx> ConstPropagator<'mir, 'tcx> {
                 self.check_binary_op(*op, left, right, source_info)?;
             }
 
-            // Do not try creating references (#67862)
+            // Only create shared references to locals which `CanConstProp` checked can't be
+            // mutated through them. Do not try creating other references (#67862).
+            Rvalue::Ref(_, BorrowKind::Shared, place_ref)
+                if !place_ref.is_indirect()
+                    && self.can_const_prop[place_ref.local] == ConstPropMode::FullConstProp => {}
             Rvalue::Ref(_, _, place_ref) => {
                 trace!("skipping Ref({:?})", place_ref);
 
@@ -632,10 +648,137 @@ impl<'mir, 'tcx> ConstPropagator<'mir, 'tcx> {
             _ => {}
         }
 
-        self.use_ecx(|this| {
+        let evaluated = self.use_ecx(|this| {
             trace!("calling eval_rvalue_into_place(rvalue = {:?}, place = {:?})", rvalue, place);
             this.ecx.eval_rvalue_into_place(rvalue, place)?;
             Ok(())
+        });
+
+        // Keep the fields of an aggregate which are known, even if the others are not, so that
+        // they can be read later on.
+        if evaluated.is_none() {
+            if let Rvalue::Aggregate(kind, operands) = rvalue {
+                self.const_prop_aggregate_fields(kind, operands, place);
+            }
+        }
+
+        evaluated
+    }
+
+    /// Writes the value of each operand of an aggregate which can be evaluated into its field
+    /// of `place`, leaving the other fields uninitialized.
+    fn const_prop_aggregate_fields(
+        &mut self,
+        kind: &AggregateKind<'tcx>,
+        operands: &[Operand<'tcx>],
+        place: &Place<'tcx>,
+    ) -> Option<()> {
+        self.use_ecx(|this| {
+            let dest = this.ecx.eval_place(place)?;
+            let (dest, active_field_index) = match *kind {
+                AggregateKind::Adt(adt_def, variant_index, _, _, active_field_index) => {
+                    this.ecx.write_discriminant_index(variant_index, dest)?;
+                    if adt_def.is_enum() {
+                        (this.ecx.place_downcast(dest, variant_index)?, active_field_index)
+                    } else {
+                        (dest, active_field_index)
+                    }
+                }
+                _ => (dest, None),
+            };
+
+            for (i, operand) in operands.iter().enumerate() {
+                let op = match this.ecx.eval_operand(operand, None) {
+                    Ok(op) => op,
+                    Err(_) => continue,
+                };
+                // Ignore zero-sized fields.
+                if !op.layout.is_zst() {
+                    let field_index = active_field_index.unwrap_or(i);
+                    let field_dest = this.ecx.place_field(dest, field_index as u64)?;
+                    this.ecx.copy_op(op, field_dest)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Evaluates a call to a `const fn` whose arguments are all known, and writes its result to
+    /// `dest` so that it can be propagated further. The call itself is kept.
+    fn const_prop_call(
+        &mut self,
+        func: &Operand<'tcx>,
+        args: &[Operand<'tcx>],
+        dest: &Place<'tcx>,
+        source_info: SourceInfo,
+    ) -> Option<()> {
+        let local = dest.as_local().filter(|&local| local != RETURN_PLACE)?;
+        if self.can_const_prop[local] != ConstPropMode::FullConstProp {
+            return None;
+        }
+
+        let (def_id, substs) = match func.ty(&self.local_decls, self.tcx).kind {
+            ty::FnDef(def_id, substs) => (def_id, substs),
+            _ => return None,
+        };
+        // FIXME we need to revisit this for #67176
+        if !self.tcx.is_const_fn(def_id) || substs.needs_subst() {
+            return None;
+        }
+        let instance = Instance::resolve(self.tcx, self.param_env, def_id, substs)?;
+        match instance.def {
+            InstanceDef::Item(_) => {}
+            _ => return None,
+        }
+
+        // Evaluating the callee requires its optimized MIR, so make sure that it can't
+        // (transitively) call us, which would be a query cycle. This looks at the MIR before
+        // optimizations, which `optimized_mir` only keeps through `mir_inliner_callees` when
+        // the inliner is enabled, so local callees are skipped otherwise.
+        let caller = self.ecx.frame().instance.def_id();
+        if instance.def_id().is_local()
+            && (instance.def_id() == caller
+                || !is_pass_enabled(self.tcx.sess, &Inline)
+                || self.tcx.mir_callgraph_reachable((instance, caller)))
+        {
+            return None;
+        }
+
+        let mut arg_values = Vec::with_capacity(args.len());
+        for arg in args {
+            let value = self.use_ecx(|this| {
+                let op = this.ecx.eval_operand(arg, None)?;
+                this.ecx.try_read_immediate(op)
+            })?;
+            match value {
+                Ok(imm) if is_bits_only(*imm) => arg_values.push(*imm),
+                _ => return None,
+            }
+        }
+
+        let dest_ty = dest.ty(&self.local_decls, self.tcx).ty;
+        let ret_layout = self.layout_of(dest_ty).ok()?;
+        match ret_layout.abi {
+            Abi::Scalar(_) | Abi::ScalarPair(..) => {}
+            _ => return None,
+        }
+
+        trace!("evaluating call to {:?} with {:?}", instance, arg_values);
+        let result = eval_const_fn_call(
+            self.tcx,
+            source_info.span,
+            self.param_env,
+            instance,
+            &arg_values,
+            ret_layout,
+            MAX_CALL_STEPS,
+        )
+        .ok()
+        .filter(|&result| is_bits_only(result))?;
+
+        self.use_ecx(|this| {
+            let dest = this.ecx.eval_place(dest)?;
+            this.ecx.write_immediate(result, dest)
         })
     }
 
@@ -736,6 +879,10 @@ impl<'mir, 'tcx> ConstPropagator<'mir, 'tcx> {
             )) => l.is_bits() && r.is_bits(),
             interpret::Operand::Indirect(_) if mir_opt_level >= 2 => {
                 let mplace = op.assert_mem_place(&self.ecx);
+                // Values holding references to locals must not become constants.
+                if self.points_to_locals(mplace.ptr) {
+                    return false;
+                }
                 intern_const_alloc_recursive(&mut self.ecx, InternKind::ConstProp, mplace, false)
                     .expect("failed to intern alloc");
                 true
@@ -743,6 +890,33 @@ impl<'mir, 'tcx> ConstPropagator<'mir, 'tcx> {
             _ => false,
         }
     }
+
+    /// Returns `true` if the allocation `ptr` points to holds pointers to allocations which are
+    /// not global, i.e., to the locals of the function.
+    fn points_to_locals(&self, ptr: Scalar) -> bool {
+        let alloc_id = match ptr.to_ptr() {
+            Ok(ptr) => ptr.alloc_id,
+            Err(_) => return false,
+        };
+        match self.ecx.memory.get_raw(alloc_id) {
+            Ok(alloc) => alloc
+                .relocations()
+                .values()
+                .any(|&(_, id)| self.tcx.alloc_map.lock().get(id).is_none()),
+            Err(_) => true,
+        }
+    }
+}
+
+/// Returns `true` if `imm` is fully initialized and holds no pointers.
+fn is_bits_only(imm: Immediate) -> bool {
+    match imm {
+        Immediate::Scalar(ScalarMaybeUndef::Scalar(s)) => s.is_bits(),
+        Immediate::ScalarPair(ScalarMaybeUndef::Scalar(l), ScalarMaybeUndef::Scalar(r)) => {
+            l.is_bits() && r.is_bits()
+        }
+        _ => false,
+    }
 }
 
 /// The mode that `ConstProp` is allowed to run in for a given `Local`.
@@ -756,18 +930,28 @@ enum ConstPropMode {
     NoPropagation,
 }
 
-struct CanConstProp {
+struct CanConstProp<'a, 'tcx> {
     can_const_prop: IndexVec<Local, ConstPropMode>,
     // false at the beginning, once set, there are not allowed to be any more assignments
     found_assignment: IndexVec<Local, bool>,
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    local_decls: &'a IndexVec<Local, LocalDecl<'tcx>>,
 }
 
-impl CanConstProp {
+impl<'a, 'tcx> CanConstProp<'a, 'tcx> {
     /// returns true if `local` can be propagated
-    fn check(body: ReadOnlyBodyAndCache<'_, '_>) -> IndexVec<Local, ConstPropMode> {
+    fn check(
+        tcx: TyCtxt<'tcx>,
+        param_env: ParamEnv<'tcx>,
+        body: ReadOnlyBodyAndCache<'a, 'tcx>,
+    ) -> IndexVec<Local, ConstPropMode> {
         let mut cpv = CanConstProp {
             can_const_prop: IndexVec::from_elem(ConstPropMode::FullConstProp, &body.local_decls),
             found_assignment: IndexVec::from_elem(false, &body.local_decls),
+            tcx,
+            param_env,
+            local_decls: &body.local_decls,
         };
         for (local, val) in cpv.can_const_prop.iter_enumerated_mut() {
             // cannot use args at all
@@ -787,14 +971,35 @@ impl CanConstProp {
     }
 }
 
-impl<'tcx> Visitor<'tcx> for CanConstProp {
+impl<'tcx> Visitor<'tcx> for CanConstProp<'_, 'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        // The base local of a projection is visited with a `Projection` context, but borrowing a
+        // part of a local may let it be modified as much as borrowing all of it.
+        if !place.projection.is_empty() && !place.is_indirect() {
+            match context {
+                PlaceContext::MutatingUse(MutatingUseContext::Borrow)
+                | PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::ShallowBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::UniqueBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf) => {
+                    self.visit_local(&place.local, context, location);
+                }
+                _ => {}
+            }
+        }
+
+        self.super_place(place, context, location);
+    }
+
     fn visit_local(&mut self, &local: &Local, context: PlaceContext, _: Location) {
         use rustc::mir::visit::PlaceContext::*;
         match context {
             // Constants must have at most one write
             // FIXME(oli-obk): we could be more powerful here, if the multiple writes
             // only occur in independent execution paths
-            MutatingUse(MutatingUseContext::Store) => {
+            // The destination of a call is written when the call returns.
+            MutatingUse(MutatingUseContext::Store) | MutatingUse(MutatingUseContext::Call) => {
                 if self.found_assignment[local] {
                     trace!("local {:?} can't be propagated because of multiple assignments", local);
                     self.can_const_prop[local] = ConstPropMode::NoPropagation;
@@ -809,6 +1014,13 @@ impl<'tcx> Visitor<'tcx> for CanConstProp {
             | NonMutatingUse(NonMutatingUseContext::Projection)
             | MutatingUse(MutatingUseContext::Projection)
             | NonUse(_) => {}
+            // Shared borrows can only modify the local through interior mutability
+            NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+                if self.local_decls[local].ty.is_freeze(
+                    self.tcx,
+                    self.param_env,
+                    self.local_decls[local].source_info.span,
+                ) => {}
             _ => {
                 trace!("local {:?} can't be propagaged because it's used: {:?}", local, context);
                 self.can_const_prop[local] = ConstPropMode::NoPropagation;
@@ -859,6 +1071,16 @@ impl<'mir, 'tcx> MutVisitor<'tcx> for ConstPropagator<'mir, 'tcx> {
                             self.remove_const(local);
                         }
                     }
+                } else if !place.is_indirect() && place.local != RETURN_PLACE {
+                    // A write to a part of a local updates the known value of that part, or
+                    // makes the whole local unknown.
+                    let local = place.local;
+                    if self.can_const_prop[local] != ConstPropMode::FullConstProp
+                        || self.const_prop(rval, place_layout, source_info, place).is_none()
+                    {
+                        trace!("can't propagate into {:?}", place);
+                        self.remove_const(local);
+                    }
                 }
             }
         } else {
@@ -968,8 +1190,18 @@ impl<'mir, 'tcx> MutVisitor<'tcx> for ConstPropagator<'mir, 'tcx> {
             | TerminatorKind::GeneratorDrop
             | TerminatorKind::FalseEdges { .. }
             | TerminatorKind::FalseUnwind { .. } => {}
-            //FIXME(wesleywiser) Call does have Operands that could be const-propagated
-            TerminatorKind::Call { .. } => {}
+            TerminatorKind::Call { func, args, destination: Some((dest, _)), .. } => {
+                if self.const_prop_call(func, args, dest, source_info).is_none() {
+                    if let Some(local) = dest.as_local() {
+                        if local != RETURN_PLACE {
+                            self.remove_const(local);
+                        }
+                    } else if !dest.is_indirect() && dest.local != RETURN_PLACE {
+                        self.remove_const(dest.local);
+                    }
+                }
+            }
+            TerminatorKind::Call { destination: None, .. } => {}
         }
     }
 }
@@ -0,0 +1,149 @@
+//! Dead store elimination.
+//!
+//! This pass removes the assignments to locals, or to parts of them, which are never read
+//! afterwards, in this block or in any block reachable from it:
+//!
+//!     _2 = Add(_1, const 1i32);       nop;
+//!     _2 = Mul(_1, const 2i32);  =>   _2 = Mul(_1, const 2i32);
+//!     _0 = _2;                        _0 = _2;
+//!
+//! Whether a local may still be read comes from the `MaybeLiveLocals` backward dataflow
+//! analysis. A write to a field of a local doesn't make the local live, so a value which is
+//! built field by field is removed as a whole when it is dead. Locals which are borrowed at some
+//! point may be read through a pointer that liveness doesn't track, so stores to them are kept,
+//! as well as stores through pointers.
+//!
+//! The storage markers of the locals which are no longer used, along with their declarations,
+//! are removed by `SimplifyLocals`.
+
+use crate::dataflow::generic::{Analysis, ResultsVisitor};
+use crate::dataflow::MaybeLiveLocals;
+use crate::transform::{MirPass, MirSource};
+use rustc::mir::visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor};
+use rustc::mir::*;
+use rustc::session::Session;
+use rustc::ty::{self, TyCtxt};
+use rustc_index::bit_set::BitSet;
+
+pub struct DeadStoreElimination;
+
+impl<'tcx> MirPass<'tcx> for DeadStoreElimination {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.mir_opt_level >= 2
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        let dead_stores = {
+            let read_only = read_only!(body);
+            let mut borrowed = BorrowedLocals(BitSet::new_empty(read_only.local_decls.len()));
+            borrowed.visit_body(read_only);
+
+            let body_ref: &Body<'_> = &read_only;
+            let results =
+                MaybeLiveLocals.into_engine(tcx, body_ref, source.def_id()).iterate_to_fixpoint();
+            let mut finder = DeadStoreFinder { borrowed: borrowed.0, dead_stores: vec![] };
+            results.visit_with(body_ref, body_ref.basic_blocks().indices(), &mut finder);
+            finder.dead_stores
+        };
+
+        let basic_blocks = body.basic_blocks_mut();
+        for Location { block, statement_index } in dead_stores {
+            let statement = &mut basic_blocks[block].statements[statement_index];
+            debug!("DeadStoreElimination: removing {:?}", statement);
+            statement.make_nop();
+        }
+    }
+}
+
+/// Collects the locals which are borrowed, or whose address is taken, anywhere in the body.
+struct BorrowedLocals(BitSet<Local>);
+
+impl<'tcx> Visitor<'tcx> for BorrowedLocals {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        // Borrowing something behind a pointer doesn't borrow the local holding the pointer.
+        if !place.is_indirect() {
+            match context {
+                PlaceContext::MutatingUse(MutatingUseContext::Borrow)
+                | PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::ShallowBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::UniqueBorrow)
+                | PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf) => {
+                    self.0.insert(place.local);
+                }
+                _ => {}
+            }
+        }
+
+        self.super_place(place, context, location);
+    }
+}
+
+/// Finds the statements which write to a local which is dead right after them.
+struct DeadStoreFinder {
+    borrowed: BitSet<Local>,
+    dead_stores: Vec<Location>,
+}
+
+impl<'mir, 'tcx> ResultsVisitor<'mir, 'tcx> for DeadStoreFinder {
+    type FlowState = BitSet<Local>;
+
+    // Liveness is a backward analysis, so `state` holds the locals which are live right after the
+    // statement.
+    fn visit_statement(
+        &mut self,
+        state: &Self::FlowState,
+        statement: &'mir Statement<'tcx>,
+        location: Location,
+    ) {
+        let place = match &statement.kind {
+            StatementKind::Assign(box (place, rvalue)) => {
+                // Constants which are not evaluated yet may fail to evaluate, which must still
+                // be reported.
+                if has_unevaluated_constants(rvalue) {
+                    return;
+                }
+                place
+            }
+            StatementKind::SetDiscriminant { place, .. } => place,
+            _ => return,
+        };
+
+        if place.is_indirect() || self.borrowed.contains(place.local) || state.contains(place.local)
+        {
+            return;
+        }
+
+        self.dead_stores.push(location);
+    }
+}
+
+fn has_unevaluated_constants(rvalue: &Rvalue<'_>) -> bool {
+    let is_unevaluated = |operand: &Operand<'_>| match operand {
+        Operand::Constant(constant) => match constant.literal.val {
+            ty::ConstKind::Unevaluated(..) => true,
+            _ => false,
+        },
+        Operand::Copy(_) | Operand::Move(_) => false,
+    };
+
+    match rvalue {
+        Rvalue::Use(operand) | Rvalue::Repeat(operand, _) | Rvalue::UnaryOp(_, operand) => {
+            is_unevaluated(operand)
+        }
+        Rvalue::Cast(_, operand, _) => is_unevaluated(operand),
+        Rvalue::BinaryOp(_, lhs, rhs) | Rvalue::CheckedBinaryOp(_, lhs, rhs) => {
+            is_unevaluated(lhs) || is_unevaluated(rhs)
+        }
+        Rvalue::Aggregate(_, operands) => operands.iter().any(is_unevaluated),
+        Rvalue::Ref(..)
+        | Rvalue::AddressOf(..)
+        | Rvalue::Len(_)
+        | Rvalue::Discriminant(_)
+        | Rvalue::NullaryOp(..) => false,
+    }
+}
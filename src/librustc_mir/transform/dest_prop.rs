@@ -0,0 +1,390 @@
+//! Destination propagation.
+//!
+//! This pass looks for assignments of a local to another one:
+//!
+//!     DEST = move SRC
+//!
+//! and, when the two locals can share the same storage, replaces one of them by the other
+//! everywhere, which turns the assignment into a no-op that is then removed. This avoids the
+//! copies of large values that are moved through several locals before being returned or
+//! passed to a function, which LLVM often fails to remove:
+//!
+//!     _2 = Foo { .. };        _0 = Foo { .. };
+//!     _3 = move _2;      =>   nop;
+//!     _0 = move _3;           nop;
+//!
+//! Two locals can share their storage if they do not *interfere*, that is, if neither of them
+//! is written while the other one is live, except by the assignment of one to the other. The
+//! liveness of locals comes from `util::liveness`, and is refined inside each block. Since
+//! liveness doesn't account for pointers, locals which may be borrowed at some point, according
+//! to the `MaybeBorrowedLocals` dataflow analysis, are never unified. Once two locals are
+//! unified, the result interferes with everything either of them interfered with, and it may be
+//! unified again with a third one.
+//!
+//! The storage markers of the unified locals are removed, since their live ranges were merged.
+//! The return place and the arguments are never replaced, so at most one of them can be unified
+//! with other locals.
+
+use crate::dataflow::generic::{Analysis, ResultsVisitor};
+use crate::dataflow::MaybeBorrowedLocals;
+use crate::transform::{MirPass, MirSource};
+use crate::util::liveness::{self, categorize, DefUse, LiveVarSet};
+use rustc::mir::visit::{MutVisitor, PlaceContext, Visitor};
+use rustc::mir::*;
+use rustc::session::config::OptLevel;
+use rustc::session::Session;
+use rustc::ty::TyCtxt;
+use rustc_index::bit_set::{BitSet, SparseBitMatrix};
+use rustc_index::vec::IndexVec;
+
+/// Bodies with more locals than this are left alone, to bound the cost of computing the
+/// interference between locals.
+const MAX_LOCALS: usize = 1000;
+
+pub struct DestinationPropagation;
+
+impl<'tcx> MirPass<'tcx> for DestinationPropagation {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        // This pass only makes sense in optimized builds, where it doesn't get in the way of
+        // debugging. `-Z mir-opt-level=2` enables it regardless.
+        let mir_opt_level = sess.opts.debugging_opts.mir_opt_level;
+        mir_opt_level >= 2 || (mir_opt_level == 1 && sess.opts.optimize != OptLevel::No)
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        // Liveness doesn't know about the locals saved across suspension points.
+        if body.generator_kind.is_some() || body.local_decls.len() > MAX_LOCALS {
+            return;
+        }
+
+        let candidates = find_candidates(body);
+        if candidates.is_empty() {
+            return;
+        }
+
+        let mut relevant = BitSet::new_empty(body.local_decls.len());
+        for &(dest, src) in &candidates {
+            relevant.insert(dest);
+            relevant.insert(src);
+        }
+
+        // Liveness ignores pointers, so give up on anything which may be borrowed.
+        // Unlike in general, ignoring the borrows made by `Drop` terminators is sound here:
+        // the dropped value is dead afterwards, so nothing can observe it through a
+        // pointer leaked by the drop glue anyway.
+        let borrowed = {
+            let body_ref: &Body<'_> = body;
+            let results = MaybeBorrowedLocals::all_borrows()
+                .unsound_ignore_borrow_on_drop()
+                .into_engine(tcx, body_ref, source.def_id())
+                .iterate_to_fixpoint();
+            let mut collector = BorrowedLocals(BitSet::new_empty(body.local_decls.len()));
+            results.visit_with(body_ref, body.basic_blocks().indices(), &mut collector);
+            collector.0
+        };
+        relevant.subtract(&borrowed);
+
+        let liveness = liveness::liveness_of_locals_and_return_place(read_only!(body));
+        let Interference { mut conflicts, unknown_at_entry } =
+            compute_interference(body, &liveness, &relevant);
+
+        let mut replacements = Replacements::new(body.local_decls.len());
+        for (dest, src) in candidates {
+            let (dest, src) = (replacements.find(dest), replacements.find(src));
+            if dest == src
+                || !relevant.contains(dest)
+                || !relevant.contains(src)
+                || unknown_at_entry.contains(dest)
+                || unknown_at_entry.contains(src)
+                || conflicts.contains(dest, src)
+                || body.local_decls[dest].ty != body.local_decls[src].ty
+            {
+                continue;
+            }
+
+            // Keep the return place or the argument, if any.
+            let (keep, replace) = match (is_fixed(body, dest), is_fixed(body, src)) {
+                (true, true) => continue,
+                (false, true) => (src, dest),
+                _ => (dest, src),
+            };
+
+            debug!("DestinationPropagation: replacing {:?} by {:?}", replace, keep);
+            replacements.union(keep, replace);
+
+            let replaced_conflicts: Vec<_> = conflicts.iter(replace).collect();
+            for local in replaced_conflicts {
+                conflicts.insert(keep, local);
+                conflicts.insert(local, keep);
+            }
+        }
+
+        if replacements.merged.is_empty() {
+            return;
+        }
+
+        let map = body.local_decls.indices().map(|local| replacements.find(local)).collect();
+        Replacer { tcx, map, merged: replacements.merged }.visit_body(body);
+    }
+}
+
+/// The return place and the arguments hold values on entry or exit, so they can't be replaced.
+fn is_fixed(body: &Body<'_>, local: Local) -> bool {
+    local == RETURN_PLACE || body.local_kind(local) == LocalKind::Arg
+}
+
+/// Finds the assignments of a local to another one, `(dest, src)`.
+fn find_candidates(body: &Body<'_>) -> Vec<(Local, Local)> {
+    let mut candidates = vec![];
+    for data in body.basic_blocks() {
+        for statement in &data.statements {
+            if let StatementKind::Assign(box (dest, rvalue)) = &statement.kind {
+                if let Some((dest, src)) = local_copy(dest, rvalue) {
+                    if dest != src {
+                        candidates.push((dest, src));
+                    }
+                }
+            }
+        }
+    }
+    candidates
+}
+
+fn local_copy(dest: &Place<'_>, rvalue: &Rvalue<'_>) -> Option<(Local, Local)> {
+    match rvalue {
+        Rvalue::Use(Operand::Copy(src)) | Rvalue::Use(Operand::Move(src)) => {
+            Some((dest.as_local()?, src.as_local()?))
+        }
+        _ => None,
+    }
+}
+
+struct BorrowedLocals(BitSet<Local>);
+
+impl<'mir, 'tcx> ResultsVisitor<'mir, 'tcx> for BorrowedLocals {
+    type FlowState = BitSet<Local>;
+
+    fn visit_statement_exit(
+        &mut self,
+        state: &Self::FlowState,
+        _: &'mir Statement<'tcx>,
+        _: Location,
+    ) {
+        self.0.union(state);
+    }
+
+    fn visit_terminator_exit(
+        &mut self,
+        state: &Self::FlowState,
+        _: &'mir Terminator<'tcx>,
+        _: Location,
+    ) {
+        self.0.union(state);
+    }
+}
+
+struct Interference {
+    /// Pairs of relevant locals which can't share their storage, in both orders.
+    conflicts: SparseBitMatrix<Local, Local>,
+    /// Relevant locals, other than the return place and the arguments, which may be read
+    /// before being written.
+    unknown_at_entry: BitSet<Local>,
+}
+
+/// Computes which of the `relevant` locals interfere with each other, by walking each block
+/// backwards from the locals live at its end.
+fn compute_interference(
+    body: &Body<'_>,
+    liveness: &liveness::LivenessResult,
+    relevant: &BitSet<Local>,
+) -> Interference {
+    let mut conflicts = SparseBitMatrix::new(body.local_decls.len());
+    let mut unknown_at_entry = BitSet::new_empty(body.local_decls.len());
+    let mut live = LiveVarSet::new_empty(body.local_decls.len());
+    let mut accesses = Accesses::default();
+
+    for (block, data) in body.basic_blocks().iter_enumerated() {
+        live.overwrite(&liveness.outs[block]);
+
+        let location = body.terminator_loc(block);
+        accesses.clear();
+        accesses.visit_terminator(data.terminator(), location);
+        if let TerminatorKind::Return = data.terminator().kind {
+            accesses.uses.push(RETURN_PLACE);
+        }
+        accesses.apply(&mut live, &mut conflicts, relevant, None);
+
+        for (statement_index, statement) in data.statements.iter().enumerate().rev() {
+            let location = Location { block, statement_index };
+            accesses.clear();
+            accesses.visit_statement(statement, location);
+            let copy = match &statement.kind {
+                StatementKind::Assign(box (dest, rvalue)) => local_copy(dest, rvalue),
+                _ => None,
+            };
+            accesses.apply(&mut live, &mut conflicts, relevant, copy);
+        }
+
+        if block == START_BLOCK {
+            // The return place and the arguments are written on entry. Anything else that is
+            // live may be read before having been written.
+            for local in live.iter().filter(|&local| relevant.contains(local)) {
+                if is_fixed(body, local) {
+                    for other in live.iter().filter(|&other| other != local) {
+                        if relevant.contains(other) {
+                            conflicts.insert(local, other);
+                            conflicts.insert(other, local);
+                        }
+                    }
+                } else {
+                    unknown_at_entry.insert(local);
+                }
+            }
+        }
+    }
+
+    Interference { conflicts, unknown_at_entry }
+}
+
+/// The locals accessed by a statement or terminator.
+#[derive(Default)]
+struct Accesses {
+    /// Locals whose value is killed, as categorized by liveness.
+    defs: Vec<Local>,
+    /// Locals whose value is read, as categorized by liveness.
+    uses: Vec<Local>,
+    /// Locals which are written to, even partially.
+    mutated: Vec<Local>,
+}
+
+impl Accesses {
+    fn clear(&mut self) {
+        self.defs.clear();
+        self.uses.clear();
+        self.mutated.clear();
+    }
+
+    /// Records the interference caused by these accesses, given the locals that are `live`
+    /// after them, and updates `live` to the locals live before them. `copy` is the pair of
+    /// locals assigned one to the other by this statement, if any, which don't interfere here.
+    fn apply(
+        &self,
+        live: &mut LiveVarSet,
+        conflicts: &mut SparseBitMatrix<Local, Local>,
+        relevant: &BitSet<Local>,
+        copy: Option<(Local, Local)>,
+    ) {
+        for &written in self.mutated.iter().filter(|&&local| relevant.contains(local)) {
+            // Besides the locals live afterwards, a written local interferes with the ones
+            // read by the same statement, as an rvalue or a call may read its operands after
+            // starting to write its destination.
+            let others = live.iter().chain(self.uses.iter().copied());
+            for other in others.filter(|&other| other != written && relevant.contains(other)) {
+                if copy == Some((written, other)) {
+                    continue;
+                }
+                conflicts.insert(written, other);
+                conflicts.insert(other, written);
+            }
+        }
+
+        for &local in &self.defs {
+            live.remove(local);
+        }
+        for &local in &self.uses {
+            live.insert(local);
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Accesses {
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, _: Location) {
+        if context.is_mutating_use() {
+            self.mutated.push(local);
+        }
+        match categorize(context) {
+            Some(DefUse::Def) => self.defs.push(local),
+            Some(DefUse::Use) | Some(DefUse::Drop) => self.uses.push(local),
+            None => {}
+        }
+    }
+}
+
+/// A union-find of the locals which have been unified.
+struct Replacements {
+    parents: IndexVec<Local, Local>,
+    /// All the locals which have been unified with another one.
+    merged: BitSet<Local>,
+}
+
+impl Replacements {
+    fn new(locals: usize) -> Self {
+        Replacements {
+            parents: (0..locals).map(Local::new).collect(),
+            merged: BitSet::new_empty(locals),
+        }
+    }
+
+    fn find(&mut self, local: Local) -> Local {
+        let parent = self.parents[local];
+        if parent == local {
+            return local;
+        }
+        let root = self.find(parent);
+        self.parents[local] = root;
+        root
+    }
+
+    fn union(&mut self, keep: Local, replace: Local) {
+        self.parents[replace] = keep;
+        self.merged.insert(keep);
+        self.merged.insert(replace);
+    }
+}
+
+struct Replacer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    map: IndexVec<Local, Local>,
+    merged: BitSet<Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for Replacer<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_basic_block_data(&mut self, block: BasicBlock, data: &mut BasicBlockData<'tcx>) {
+        self.super_basic_block_data(block, data);
+
+        for statement in &mut data.statements {
+            let remove = match &statement.kind {
+                // The live ranges of the merged locals were merged too.
+                StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
+                    self.merged.contains(*local)
+                }
+                StatementKind::Assign(box (dest, rvalue)) => {
+                    local_copy(dest, rvalue).map_or(false, |(dest, src)| dest == src)
+                }
+                _ => false,
+            };
+            if remove {
+                statement.make_nop();
+            }
+        }
+    }
+
+    fn visit_local(&mut self, local: &mut Local, _: PlaceContext, _: Location) {
+        *local = self.map[*local];
+    }
+
+    fn process_projection_elem(&mut self, elem: &PlaceElem<'tcx>) -> Option<PlaceElem<'tcx>> {
+        match elem {
+            PlaceElem::Index(local) => Some(PlaceElem::Index(self.map[*local])),
+            _ => None,
+        }
+    }
+}
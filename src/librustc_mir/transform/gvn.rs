@@ -0,0 +1,303 @@
+//! Global value numbering.
+//!
+//! This pass finds computations whose result is already available in a local, and replaces
+//! them by a copy of that local:
+//!
+//!     _3 = Add(_1, _2);       _3 = Add(_1, _2);
+//!     ...                =>   ...
+//!     _5 = Add(_1, _2);       _5 = _3;
+//!
+//! Only pure rvalues are considered: arithmetic, `Len`, `Discriminant` and reads of fields.
+//! Their operands must be constants or *SSA locals*, that is, locals which are written once,
+//! as a whole, and are never borrowed, so that they hold the same value everywhere they are
+//! used. Such locals have the same value number as any SSA local they are copied from.
+//!
+//! A computation is replaced when an identical one, which stored its result in an SSA local,
+//! dominates it. Blocks are visited in reverse postorder, so dominating computations are
+//! always seen first. The storage markers of the reused locals are removed, as their values
+//! are now needed for longer, and moves out of them are turned into copies.
+
+use crate::transform::{MirPass, MirSource};
+use rustc::mir::traversal;
+use rustc::mir::visit::{
+    MutVisitor, MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor,
+};
+use rustc::mir::*;
+use rustc::session::Session;
+use rustc::ty::{self, ParamEnv, TyCtxt};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::graph::dominators::Dominators;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+use rustc_span::DUMMY_SP;
+
+pub struct GlobalValueNumbering;
+
+impl<'tcx> MirPass<'tcx> for GlobalValueNumbering {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.mir_opt_level >= 2
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        let param_env = tcx.param_env(source.def_id());
+        let replacements = {
+            let read_only = read_only!(body);
+            let dominators = read_only.dominators();
+            let numbering = ValueNumbering::new(tcx, param_env, &read_only);
+            numbering.find_replacements(&read_only, &dominators)
+        };
+        if replacements.is_empty() {
+            return;
+        }
+
+        let mut reused = BitSet::new_empty(body.local_decls.len());
+        for &(location, result) in &replacements {
+            debug!("GlobalValueNumbering: reusing {:?} at {:?}", result, location);
+            let statement =
+                &mut body.basic_blocks_mut()[location.block].statements[location.statement_index];
+            if let StatementKind::Assign(box (_, rvalue)) = &mut statement.kind {
+                *rvalue = Rvalue::Use(Operand::Copy(Place::from(result)));
+            }
+            reused.insert(result);
+        }
+
+        Reuser { tcx, reused }.visit_body(body);
+    }
+}
+
+/// An operand of a pure computation.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Value<'tcx> {
+    /// The value number of an SSA local: the SSA local it was (transitively) copied from.
+    Local(Local),
+    Constant(&'tcx ty::Const<'tcx>),
+}
+
+/// A pure computation, which always gives the same result for the same operands. The base
+/// locals of the places are value numbers.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+enum Expr<'tcx> {
+    BinaryOp(BinOp, Value<'tcx>, Value<'tcx>),
+    CheckedBinaryOp(BinOp, Value<'tcx>, Value<'tcx>),
+    UnaryOp(UnOp, Value<'tcx>),
+    Len(Place<'tcx>),
+    Discriminant(Place<'tcx>),
+    Field(Place<'tcx>),
+}
+
+struct ValueNumbering<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    local_decls: &'a LocalDecls<'tcx>,
+    ssa: BitSet<Local>,
+    /// The value number of each SSA local.
+    numbers: IndexVec<Local, Local>,
+}
+
+impl<'a, 'tcx> ValueNumbering<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, param_env: ParamEnv<'tcx>, body: &'a Body<'tcx>) -> Self {
+        let mut finder = SsaFinder {
+            defs: IndexVec::from_elem(0, &body.local_decls),
+            disqualified: BitSet::new_empty(body.local_decls.len()),
+        };
+        for (block, data) in body.basic_blocks().iter_enumerated() {
+            finder.visit_basic_block_data(block, data);
+        }
+
+        // The arguments are written on entry, and the return place is read on exit.
+        let mut ssa = BitSet::new_empty(body.local_decls.len());
+        for (local, &defs) in finder.defs.iter_enumerated() {
+            let expected_defs = match body.local_kind(local) {
+                LocalKind::ReturnPointer => continue,
+                LocalKind::Arg => 0,
+                LocalKind::Var | LocalKind::Temp => 1,
+            };
+            if defs == expected_defs && !finder.disqualified.contains(local) {
+                ssa.insert(local);
+            }
+        }
+
+        let numbers = body.local_decls.indices().collect();
+        ValueNumbering { tcx, param_env, local_decls: &body.local_decls, ssa, numbers }
+    }
+
+    /// Returns the computations to replace, along with the local holding their result.
+    fn find_replacements(
+        mut self,
+        body: &Body<'tcx>,
+        dominators: &Dominators<BasicBlock>,
+    ) -> Vec<(Location, Local)> {
+        let mut available: FxHashMap<Expr<'tcx>, Vec<(Location, Local)>> = FxHashMap::default();
+        let mut replacements = vec![];
+
+        for (block, data) in traversal::reverse_postorder(body) {
+            for (statement_index, statement) in data.statements.iter().enumerate() {
+                let (dest, rvalue) = match &statement.kind {
+                    StatementKind::Assign(box (dest, rvalue)) => (dest, rvalue),
+                    _ => continue,
+                };
+                let location = Location { block, statement_index };
+                let dest = dest.as_local().filter(|&dest| self.ssa.contains(dest));
+
+                if let Rvalue::Use(Operand::Copy(src)) | Rvalue::Use(Operand::Move(src)) = rvalue {
+                    if let (Some(dest), Some(Value::Local(src))) = (dest, self.value_of_local(src))
+                    {
+                        self.numbers[dest] = src;
+                        continue;
+                    }
+                }
+
+                let expr = match self.expr(rvalue) {
+                    Some(expr) => expr,
+                    None => continue,
+                };
+                let candidates = available.entry(expr).or_default();
+                let found = candidates
+                    .iter()
+                    .rev()
+                    .find(|&&(available_at, _)| available_at.dominates(location, dominators));
+                if let Some(&(_, result)) = found {
+                    replacements.push((location, result));
+                    if let Some(dest) = dest {
+                        self.numbers[dest] = result;
+                    }
+                } else if let Some(dest) = dest {
+                    candidates.push((location, dest));
+                }
+            }
+        }
+
+        replacements
+    }
+
+    fn expr(&self, rvalue: &Rvalue<'tcx>) -> Option<Expr<'tcx>> {
+        Some(match rvalue {
+            Rvalue::BinaryOp(op, lhs, rhs) => {
+                Expr::BinaryOp(*op, self.value(lhs)?, self.value(rhs)?)
+            }
+            Rvalue::CheckedBinaryOp(op, lhs, rhs) => {
+                Expr::CheckedBinaryOp(*op, self.value(lhs)?, self.value(rhs)?)
+            }
+            Rvalue::UnaryOp(op, operand) => Expr::UnaryOp(*op, self.value(operand)?),
+            // The length of a slice behind a pointer is part of the pointer, so it can't change.
+            Rvalue::Len(place) => Expr::Len(self.immutable_place(place, true)?),
+            Rvalue::Discriminant(place) => Expr::Discriminant(self.immutable_place(place, false)?),
+            Rvalue::Use(Operand::Copy(place)) | Rvalue::Use(Operand::Move(place))
+                if !place.projection.is_empty() =>
+            {
+                let ty = place.ty(self.local_decls, self.tcx).ty;
+                if !ty.is_copy_modulo_regions(self.tcx, self.param_env, DUMMY_SP) {
+                    return None;
+                }
+                Expr::Field(self.immutable_place(place, false)?)
+            }
+            _ => return None,
+        })
+    }
+
+    fn value(&self, operand: &Operand<'tcx>) -> Option<Value<'tcx>> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.value_of_local(place),
+            Operand::Constant(constant) => Some(Value::Constant(constant.literal)),
+        }
+    }
+
+    fn value_of_local(&self, place: &Place<'tcx>) -> Option<Value<'tcx>> {
+        let local = place.as_local().filter(|&local| self.ssa.contains(local))?;
+        Some(Value::Local(self.numbers[local]))
+    }
+
+    /// Returns `place` with its base replaced by its value number, if it is a part of an SSA
+    /// local, or if `through_deref`, the target of a shared reference stored in one. The
+    /// reference must be the last dereference, and its target must be `Freeze`, so that nothing
+    /// else can change the target while the reference is live.
+    fn immutable_place(&self, place: &Place<'tcx>, through_deref: bool) -> Option<Place<'tcx>> {
+        if !self.ssa.contains(place.local) {
+            return None;
+        }
+        for (i, elem) in place.projection.iter().enumerate() {
+            match elem {
+                ProjectionElem::Field(..) | ProjectionElem::Downcast(..) => {}
+                ProjectionElem::Deref if through_deref && i == place.projection.len() - 1 => {
+                    let base = &place.projection[..i];
+                    let pointer_ty = Place::ty_from(place.local, base, self.local_decls, self.tcx);
+                    match pointer_ty.ty.kind {
+                        ty::Ref(_, pointee, Mutability::Not)
+                            if pointee.is_freeze(self.tcx, self.param_env, DUMMY_SP) => {}
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            }
+        }
+        Some(Place { local: self.numbers[place.local], projection: place.projection })
+    }
+}
+
+/// Counts the writes of each local, and disqualifies the ones which are borrowed or partially
+/// written.
+struct SsaFinder {
+    defs: IndexVec<Local, usize>,
+    disqualified: BitSet<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for SsaFinder {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, _: Location) {
+        // Accesses behind a pointer only read the local.
+        if place.is_indirect() {
+            return;
+        }
+
+        match context {
+            PlaceContext::MutatingUse(MutatingUseContext::Store)
+            | PlaceContext::MutatingUse(MutatingUseContext::Call)
+                if place.projection.is_empty() =>
+            {
+                self.defs[place.local] += 1;
+            }
+            PlaceContext::MutatingUse(_)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::ShallowBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::UniqueBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf) => {
+                self.disqualified.insert(place.local);
+            }
+            PlaceContext::NonMutatingUse(_) | PlaceContext::NonUse(_) => {}
+        }
+    }
+}
+
+/// Makes the reused locals live for the whole body, and only copies them.
+struct Reuser<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    reused: BitSet<Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for Reuser<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement<'tcx>, location: Location) {
+        match statement.kind {
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local)
+                if self.reused.contains(local) =>
+            {
+                statement.make_nop();
+            }
+            _ => self.super_statement(statement, location),
+        }
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, _: Location) {
+        if let Operand::Move(place) = *operand {
+            if self.reused.contains(place.local) {
+                *operand = Operand::Copy(place);
+            }
+        }
+    }
+}
@@ -9,8 +9,11 @@ use rustc::middle::codegen_fn_attrs::CodegenFnAttrFlags;
 use rustc::mir::visit::*;
 use rustc::mir::*;
 use rustc::session::config::Sanitizer;
+use rustc::session::Session;
+use rustc::ty::query::Providers;
 use rustc::ty::subst::{InternalSubsts, Subst, SubstsRef};
 use rustc::ty::{self, Instance, InstanceDef, ParamEnv, Ty, TyCtxt, TypeFoldable};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 
 use super::simplify::{remove_dead_blocks, CfgSimplifier};
 use crate::transform::{MirPass, MirSource};
@@ -20,9 +23,6 @@ use std::iter;
 use rustc_attr as attr;
 use rustc_target::spec::abi::Abi;
 
-const DEFAULT_THRESHOLD: usize = 50;
-const HINT_THRESHOLD: usize = 100;
-
 const INSTR_COST: usize = 5;
 const CALL_PENALTY: usize = 25;
 
@@ -39,13 +39,28 @@ struct CallSite<'tcx> {
 }
 
 impl<'tcx> MirPass<'tcx> for Inline {
-    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
-        if tcx.sess.opts.debugging_opts.mir_opt_level >= 2 {
-            Inliner { tcx, source }.run_pass(body);
+    /// The MIR inliner only runs with `-Z inline-mir`, or with `-Z mir-opt-level=2` or above.
+    fn is_enabled(&self, sess: &Session) -> bool {
+        if let Some(enabled) = sess.opts.debugging_opts.inline_mir {
+            return enabled;
         }
+
+        sess.opts.debugging_opts.mir_opt_level >= 2
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        Inliner { tcx, source }.run_pass(body);
     }
 }
 
+pub(crate) fn provide(providers: &mut Providers<'_>) {
+    *providers = Providers { mir_callgraph_reachable, mir_inliner_callees, ..*providers };
+}
+
 struct Inliner<'tcx> {
     tcx: TyCtxt<'tcx>,
     source: MirSource<'tcx>,
@@ -53,12 +68,10 @@ struct Inliner<'tcx> {
 
 impl Inliner<'tcx> {
     fn run_pass(&self, caller_body: &mut BodyAndCache<'tcx>) {
-        // Keep a queue of callsites to try inlining on. We take
-        // advantage of the fact that queries detect cycles here to
-        // allow us to try and fetch the fully optimized MIR of a
-        // call; if it succeeds, we can inline it and we know that
-        // they do not call us.  Otherwise, we just don't try to
-        // inline.
+        // Keep a queue of callsites to try inlining on. Fetching the
+        // fully optimized MIR of a local callee could cause a query
+        // cycle if it (transitively) calls us, so we first check that
+        // it doesn't, see `is_cycle_free`.
         //
         // We use a queue so that we inline "broadly" before we inline
         // in depth. It is unclear if this is the best heuristic,
@@ -76,6 +89,13 @@ impl Inliner<'tcx> {
             param_env = param_env.with_reveal_all();
         }
 
+        // Every function inlined so far, along with the index (in this same
+        // vector) of the function whose inlined body it was called from, if any.
+        // Following these indices from a callsite gives all the functions that
+        // it was inlined through, which must not be inlined again at it: they
+        // are (possibly mutually) recursive, and would be inlined forever.
+        let mut history: Vec<(DefId, Option<usize>)> = Vec::new();
+
         // Only do inlining into fn bodies.
         let id = self.tcx.hir().as_local_hir_id(self.source.def_id()).unwrap();
         if self.tcx.hir().body_owner_kind(id).is_fn_or_closure() && self.source.promoted.is_none() {
@@ -83,7 +103,7 @@ impl Inliner<'tcx> {
                 if let Some(callsite) =
                     self.get_valid_function_call(bb, bb_data, caller_body, param_env)
                 {
-                    callsites.push_back(callsite);
+                    callsites.push_back((callsite, None));
                 }
             }
         } else {
@@ -95,33 +115,32 @@ impl Inliner<'tcx> {
 
         loop {
             local_change = false;
-            while let Some(callsite) = callsites.pop_front() {
+            while let Some((callsite, inlined_from)) = callsites.pop_front() {
                 debug!("checking whether to inline callsite {:?}", callsite);
                 if !self.tcx.is_mir_available(callsite.callee) {
                     debug!("checking whether to inline callsite {:?} - MIR unavailable", callsite);
                     continue;
                 }
 
-                let self_node_id = self.tcx.hir().as_local_node_id(self.source.def_id()).unwrap();
-                let callee_node_id = self.tcx.hir().as_local_node_id(callsite.callee);
+                let mut parent = inlined_from;
+                let mut recursive = false;
+                while let Some(index) = parent {
+                    let (callee, next) = history[index];
+                    recursive |= callee == callsite.callee;
+                    parent = next;
+                }
+                if recursive {
+                    debug!("checking whether to inline callsite {:?} - recursive", callsite);
+                    continue;
+                }
 
-                let callee_body = if let Some(callee_node_id) = callee_node_id {
-                    // Avoid a cycle here by only using `optimized_mir` only if we have
-                    // a lower node id than the callee. This ensures that the callee will
-                    // not inline us. This trick only works without incremental compilation.
-                    // So don't do it if that is enabled.
-                    if !self.tcx.dep_graph.is_fully_enabled()
-                        && self_node_id.as_u32() < callee_node_id.as_u32()
-                    {
-                        self.tcx.optimized_mir(callsite.callee)
-                    } else {
-                        continue;
-                    }
-                } else {
-                    // This cannot result in a cycle since the callee MIR is from another crate
-                    // and is already optimized.
-                    self.tcx.optimized_mir(callsite.callee)
-                };
+                // The MIR of a callee from another crate is already optimized,
+                // so fetching it can't result in a cycle.
+                if callsite.callee.is_local() && !self.is_cycle_free(callsite) {
+                    debug!("checking whether to inline callsite {:?} - cycle", callsite);
+                    continue;
+                }
+                let callee_body = self.tcx.optimized_mir(callsite.callee);
 
                 let callee_body = if self.consider_optimizing(callsite, callee_body) {
                     self.tcx.subst_and_normalize_erasing_regions(
@@ -142,14 +161,13 @@ impl Inliner<'tcx> {
                 debug!("attempting to inline callsite {:?} - success", callsite);
 
                 // Add callsites from inlined function
+                history.push((callsite.callee, inlined_from));
+                let inlined_from = Some(history.len() - 1);
                 for (bb, bb_data) in caller_body.basic_blocks().iter_enumerated().skip(start) {
                     if let Some(new_callsite) =
                         self.get_valid_function_call(bb, bb_data, caller_body, param_env)
                     {
-                        // Don't inline the same function multiple times.
-                        if callsite.callee != new_callsite.callee {
-                            callsites.push_back(new_callsite);
-                        }
+                        callsites.push_back((new_callsite, inlined_from));
                     }
                 }
 
@@ -204,6 +222,32 @@ impl Inliner<'tcx> {
         None
     }
 
+    /// Returns `true` if fetching the optimized MIR of the local function
+    /// `callsite.callee` can't cause a query cycle, i.e., if the callee will
+    /// never try to inline the function we are optimizing.
+    fn is_cycle_free(&self, callsite: CallSite<'tcx>) -> bool {
+        let caller = self.source.def_id();
+        if callsite.callee == caller {
+            return false;
+        }
+
+        // Avoid a cycle here by only using `optimized_mir` only if we have
+        // a lower node id than the callee. This ensures that the callee will
+        // not inline us. This trick only works without incremental compilation.
+        // So don't do it if that is enabled.
+        if !self.tcx.dep_graph.is_fully_enabled() {
+            let self_node_id = self.tcx.hir().as_local_node_id(caller).unwrap();
+            let callee_node_id = self.tcx.hir().as_local_node_id(callsite.callee).unwrap();
+            if self_node_id.as_u32() < callee_node_id.as_u32() {
+                return true;
+            }
+        }
+
+        // Otherwise, the callee may only inline us if it can (transitively) call us.
+        let callee = Instance::new(callsite.callee, callsite.substs);
+        !self.tcx.mir_callgraph_reachable((callee, caller))
+    }
+
     fn consider_optimizing(&self, callsite: CallSite<'tcx>, callee_body: &Body<'tcx>) -> bool {
         debug!("consider_optimizing({:?})", callsite);
         self.should_inline(callsite, callee_body)
@@ -222,13 +266,11 @@ impl Inliner<'tcx> {
             return false;
         }
 
+        // `#[track_caller]` functions are fine to inline: their source scopes
+        // remember the callsite, which codegen and const-eval then use for the
+        // caller location of `core::panic::Location::caller()`.
         let codegen_fn_attrs = tcx.codegen_fn_attrs(callsite.callee);
 
-        if codegen_fn_attrs.flags.contains(CodegenFnAttrFlags::TRACK_CALLER) {
-            debug!("`#[track_caller]` present - not inlining");
-            return false;
-        }
-
         // Avoid inlining functions marked as no_sanitize if sanitizer is enabled,
         // since instrumentation might be enabled and performed on the caller.
         match self.tcx.sess.opts.debugging_opts.sanitizer {
@@ -274,7 +316,11 @@ impl Inliner<'tcx> {
             }
         }
 
-        let mut threshold = if hinted { HINT_THRESHOLD } else { DEFAULT_THRESHOLD };
+        let mut threshold = if hinted {
+            tcx.sess.opts.debugging_opts.inline_mir_hint_threshold
+        } else {
+            tcx.sess.opts.debugging_opts.inline_mir_threshold
+        };
 
         // Significantly lower the threshold for inlining cold functions
         if codegen_fn_attrs.flags.contains(CodegenFnAttrFlags::COLD) {
@@ -414,18 +460,23 @@ impl Inliner<'tcx> {
                 let mut scope_map = IndexVec::with_capacity(callee_body.source_scopes.len());
 
                 for mut scope in callee_body.source_scopes.iter().cloned() {
-                    if scope.parent_scope.is_none() {
-                        scope.parent_scope = Some(callsite.location.scope);
-                        // FIXME(eddyb) is this really needed?
-                        // (also note that it's always overwritten below)
-                        scope.span = callee_body.span;
+                    // The callee scopes keep their own spans, for debuginfo. Parents
+                    // always come before their children, so they are already mapped.
+                    match scope.parent_scope {
+                        Some(parent) => scope.parent_scope = Some(scope_map[parent]),
+                        None => {
+                            // The outermost callee scope is a child of the callsite
+                            // scope, and is marked as inlined, so that codegen can
+                            // emit it as an inlined function body.
+                            scope.parent_scope = Some(callsite.location.scope);
+                            assert!(scope.inlined.is_none());
+                            scope.inlined = Some((
+                                Instance::new(callsite.callee, callsite.substs),
+                                callsite.location.span,
+                            ));
+                        }
                     }
 
-                    // FIXME(eddyb) this doesn't seem right at all.
-                    // The inlined source scopes should probably be annotated as
-                    // such, but also contain all of the original information.
-                    scope.span = callsite.location.span;
-
                     let idx = caller_body.source_scopes.push(scope);
                     scope_map.push(idx);
                 }
@@ -629,6 +680,95 @@ impl Inliner<'tcx> {
     }
 }
 
+/// Returns the functions directly called by `def_id`, with their generic arguments, as found
+/// in its MIR before optimizations, so that this can be computed without query cycles.
+///
+/// For local functions this reads `mir_validated`, so when the inliner is enabled,
+/// `optimized_mir` forces this query before stealing it. It must not be called otherwise.
+fn mir_inliner_callees<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> &'tcx [(DefId, SubstsRef<'tcx>)] {
+    if tcx.is_constructor(def_id) {
+        return &[];
+    }
+
+    let guard;
+    let body: &Body<'tcx> = if def_id.is_local() {
+        guard = tcx.mir_validated(def_id).0.borrow();
+        &guard
+    } else {
+        tcx.optimized_mir(def_id)
+    };
+
+    let mut calls = FxHashSet::default();
+    for bb_data in body.basic_blocks() {
+        let terminator = bb_data.terminator();
+        if let TerminatorKind::Call { func, .. } = &terminator.kind {
+            if let ty::FnDef(def_id, substs) = func.ty(body, tcx).kind {
+                calls.insert((def_id, substs));
+            }
+        }
+    }
+    tcx.arena.alloc_from_iter(calls.into_iter())
+}
+
+/// Returns `true` if `root` may (transitively) call `target`, looking through the
+/// callees of each function as returned by `mir_inliner_callees`. Used by the inliner
+/// to check that inlining `root` into `target` can't cause a query cycle.
+fn mir_callgraph_reachable<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    (root, target): (Instance<'tcx>, DefId),
+) -> bool {
+    trace!("mir_callgraph_reachable({:?}, {:?})", root, target);
+    let param_env = tcx.param_env(target);
+    let param_env = if root.substs.needs_subst() { param_env } else { param_env.with_reveal_all() };
+
+    fn process<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        param_env: ParamEnv<'tcx>,
+        caller: Instance<'tcx>,
+        target: DefId,
+        seen: &mut FxHashSet<Instance<'tcx>>,
+        recursion_limiter: &mut FxHashMap<DefId, usize>,
+    ) -> bool {
+        for &(callee, substs) in tcx.mir_inliner_callees(caller.def_id()) {
+            let substs = tcx.subst_and_normalize_erasing_regions(caller.substs, param_env, &substs);
+            let callee = match Instance::resolve(tcx, param_env, callee, substs) {
+                Some(callee) => callee,
+                None => continue,
+            };
+
+            // Only functions with MIR can call back into `target`.
+            match callee.def {
+                InstanceDef::Intrinsic(_) | InstanceDef::Virtual(..) => continue,
+                _ if !tcx.is_mir_available(callee.def_id()) => continue,
+                _ => {}
+            }
+
+            if callee.def_id() == target {
+                return true;
+            }
+            if tcx.is_constructor(callee.def_id()) || !seen.insert(callee) {
+                continue;
+            }
+
+            // Polymorphic recursion can produce infinitely many instances, so give up
+            // (and assume the target is reachable) once a function is seen too often.
+            let count = recursion_limiter.entry(callee.def_id()).or_default();
+            *count += 1;
+            if *count >= *tcx.sess.recursion_limit.get() {
+                return true;
+            }
+
+            if process(tcx, param_env, callee, target, seen, recursion_limiter) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    process(tcx, param_env, root, target, &mut FxHashSet::default(), &mut FxHashMap::default())
+}
+
 fn type_size_of<'tcx>(
     tcx: TyCtxt<'tcx>,
     param_env: ty::ParamEnv<'tcx>,
@@ -0,0 +1,291 @@
+//! Jump threading.
+//!
+//! This pass looks for blocks ending with a `SwitchInt` whose operand is known on some of the
+//! edges leading to them, and makes those edges jump directly to the right target. This is
+//! common when matching on an enum which was just built, or which was just matched on, as in
+//! chains of `match`es or of `?`s:
+//!
+//!     bb1: {                              bb1: {
+//!         discriminant(_2) = 1;               discriminant(_2) = 1;
+//!         goto -> bb3;                        goto -> bb4;
+//!     }                                   }
+//!     bb3: {                         =>   bb4: {
+//!         _3 = discriminant(_2);              _3 = discriminant(_2);
+//!         switchInt(move _3) -> [..];         goto -> bb5;
+//!     }                                   }
+//!
+//! The operand is evaluated by walking the statements of the predecessor, then the ones of the
+//! switching block, while tracking the constant values and discriminants that are assigned to
+//! locals. An edge coming from another `SwitchInt` also tells the value of its operand. Since
+//! the statements of the switching block still need to run on the threaded edge, they are
+//! copied to a new block, which jumps to the known target.
+
+use crate::transform::{MirPass, MirSource};
+use rustc::mir::*;
+use rustc::session::Session;
+use rustc::ty::{self, ParamEnv, TyCtxt};
+use rustc_data_structures::fx::FxHashMap;
+
+/// Switching blocks with more statements than this (besides storage markers) are not
+/// duplicated.
+const MAX_STATEMENTS: usize = 8;
+
+pub struct JumpThreading;
+
+impl<'tcx> MirPass<'tcx> for JumpThreading {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.mir_opt_level >= 2
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        let param_env = tcx.param_env(source.def_id());
+        let threads = find_threads(tcx, param_env, &read_only!(body));
+        if threads.is_empty() {
+            return;
+        }
+
+        let basic_blocks = body.basic_blocks_mut();
+        for ThreadedEdge { pred, edge, block, target } in threads {
+            debug!("JumpThreading: {:?} -> {:?} now goes to {:?}", pred, block, target);
+
+            // Keep running the statements of the switching block on the threaded edge.
+            let data = &basic_blocks[block];
+            let new_target = if data.statements.is_empty() {
+                target
+            } else {
+                let threaded = BasicBlockData {
+                    statements: data.statements.clone(),
+                    terminator: Some(Terminator {
+                        source_info: data.terminator().source_info,
+                        kind: TerminatorKind::Goto { target },
+                    }),
+                    is_cleanup: data.is_cleanup,
+                };
+                basic_blocks.push(threaded)
+            };
+
+            match &mut basic_blocks[pred].terminator_mut().kind {
+                TerminatorKind::Goto { target } => *target = new_target,
+                TerminatorKind::SwitchInt { targets, .. } => targets[edge] = new_target,
+                kind => bug!("unexpected terminator for a threaded edge: {:?}", kind),
+            }
+        }
+    }
+}
+
+/// The edge `pred -> block`, which is the `edge`th target of `pred`, and now goes to `target`.
+struct ThreadedEdge {
+    pred: BasicBlock,
+    edge: usize,
+    block: BasicBlock,
+    target: BasicBlock,
+}
+
+fn find_threads<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    body: &ReadOnlyBodyAndCache<'_, 'tcx>,
+) -> Vec<ThreadedEdge> {
+    let mut threads = vec![];
+    for (block, data) in body.basic_blocks().iter_enumerated() {
+        let (discr, values, targets) = match &data.terminator().kind {
+            TerminatorKind::SwitchInt { discr, values, targets, .. } => (discr, values, targets),
+            _ => continue,
+        };
+        let duplicated = data.statements.iter().filter(|statement| match statement.kind {
+            StatementKind::StorageLive(_) | StatementKind::StorageDead(_) | StatementKind::Nop => {
+                false
+            }
+            _ => true,
+        });
+        if duplicated.count() > MAX_STATEMENTS {
+            continue;
+        }
+
+        for &pred in body.predecessors_for(block) {
+            if pred == block {
+                continue;
+            }
+
+            let mut state = State::new(tcx, param_env, body);
+            for statement in &body[pred].statements {
+                state.apply(statement);
+            }
+
+            let edges: Vec<_> = match &body[pred].terminator().kind {
+                TerminatorKind::Goto { .. } => vec![(0, state)],
+                TerminatorKind::SwitchInt {
+                    discr: pred_discr,
+                    values: pred_values,
+                    targets,
+                    ..
+                } => {
+                    // Only the edges taken for a given value tell anything about the operand.
+                    targets
+                        .iter()
+                        .zip(pred_values.iter())
+                        .enumerate()
+                        .filter(|&(_, (&target, _))| target == block)
+                        .map(|(edge, (_, &value))| {
+                            let mut state = state.clone();
+                            state.assume(pred_discr, value);
+                            (edge, state)
+                        })
+                        .collect()
+                }
+                _ => continue,
+            };
+
+            for (edge, mut state) in edges {
+                for statement in &data.statements {
+                    state.apply(statement);
+                }
+                if let Some(value) = state.eval(discr) {
+                    let index = values.iter().position(|&v| v == value).unwrap_or(values.len());
+                    threads.push(ThreadedEdge { pred, edge, block, target: targets[index] });
+                }
+            }
+        }
+    }
+    threads
+}
+
+/// The values known at some point of the body.
+#[derive(Clone)]
+struct State<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    body: &'a Body<'tcx>,
+    /// The bits of the locals holding a known scalar.
+    values: FxHashMap<Local, u128>,
+    /// The discriminants of the locals holding an enum with a known variant.
+    discriminants: FxHashMap<Local, u128>,
+    /// The locals which hold the discriminant of another local.
+    discriminant_of: FxHashMap<Local, Local>,
+}
+
+impl<'a, 'tcx> State<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, param_env: ParamEnv<'tcx>, body: &'a Body<'tcx>) -> Self {
+        State {
+            tcx,
+            param_env,
+            body,
+            values: FxHashMap::default(),
+            discriminants: FxHashMap::default(),
+            discriminant_of: FxHashMap::default(),
+        }
+    }
+
+    fn eval(&self, operand: &Operand<'tcx>) -> Option<u128> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                self.values.get(&place.as_local()?).copied()
+            }
+            Operand::Constant(constant) => {
+                constant.literal.try_eval_bits(self.tcx, self.param_env, constant.literal.ty)
+            }
+        }
+    }
+
+    /// Records that `operand` holds `value`.
+    fn assume(&mut self, operand: &Operand<'tcx>, value: u128) {
+        if let Operand::Copy(place) | Operand::Move(place) = operand {
+            if let Some(local) = place.as_local() {
+                self.values.insert(local, value);
+                if let Some(&enum_local) = self.discriminant_of.get(&local) {
+                    self.discriminants.insert(enum_local, value);
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, statement: &Statement<'tcx>) {
+        match &statement.kind {
+            StatementKind::Assign(box (place, rvalue)) => {
+                let mut value = None;
+                let mut discriminant = None;
+                let mut discriminant_of = None;
+                match rvalue {
+                    Rvalue::Use(operand) => {
+                        value = self.eval(operand);
+                        if let Operand::Copy(src) | Operand::Move(src) = operand {
+                            if let Some(src) = src.as_local() {
+                                discriminant = self.discriminants.get(&src).copied();
+                            }
+                        }
+                    }
+                    Rvalue::Discriminant(enum_place) => {
+                        if let Some(enum_local) = enum_place.as_local() {
+                            value = self.discriminants.get(&enum_local).copied();
+                            discriminant_of = Some(enum_local);
+                        }
+                    }
+                    Rvalue::Aggregate(box AggregateKind::Adt(adt_def, variant_index, ..), _)
+                        if adt_def.is_enum() =>
+                    {
+                        discriminant =
+                            Some(adt_def.discriminant_for_variant(self.tcx, *variant_index).val);
+                    }
+                    _ => {}
+                }
+
+                self.kill(place);
+                if let Some(local) = place.as_local() {
+                    if let Some(value) = value {
+                        self.values.insert(local, value);
+                    }
+                    if let Some(discriminant) = discriminant {
+                        self.discriminants.insert(local, discriminant);
+                    }
+                    if let Some(enum_local) = discriminant_of {
+                        self.discriminant_of.insert(local, enum_local);
+                    }
+                }
+            }
+            StatementKind::SetDiscriminant { place, variant_index } => {
+                self.kill(place);
+                let ty = place.ty(self.body, self.tcx).ty;
+                if let (Some(local), ty::Adt(adt_def, _)) = (place.as_local(), &ty.kind) {
+                    if adt_def.is_enum() {
+                        let discriminant =
+                            adt_def.discriminant_for_variant(self.tcx, *variant_index);
+                        self.discriminants.insert(local, discriminant.val);
+                    }
+                }
+            }
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
+                self.kill(&Place::from(*local));
+            }
+            StatementKind::InlineAsm(..) => self.kill_all(),
+            StatementKind::FakeRead(..)
+            | StatementKind::Retag(..)
+            | StatementKind::AscribeUserType(..)
+            | StatementKind::Nop => {}
+        }
+    }
+
+    /// Forgets everything that a write to `place` may change.
+    fn kill(&mut self, place: &Place<'tcx>) {
+        // A write through a pointer may change any borrowed local.
+        if place.is_indirect() {
+            self.kill_all();
+            return;
+        }
+
+        let local = place.local;
+        self.values.remove(&local);
+        self.discriminants.remove(&local);
+        self.discriminant_of.remove(&local);
+        self.discriminant_of.retain(|_, &mut enum_local| enum_local != local);
+    }
+
+    fn kill_all(&mut self) {
+        self.values.clear();
+        self.discriminants.clear();
+        self.discriminant_of.clear();
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{shim, util};
 use rustc::hir::map::Map;
 use rustc::mir::{BodyAndCache, ConstQualifs, MirPhase, Promoted};
+use rustc::session::Session;
 use rustc::ty::query::Providers;
 use rustc::ty::steal::Steal;
 use rustc::ty::{InstanceDef, TyCtxt, TypeFoldable};
@@ -20,26 +21,34 @@ pub mod check_unsafety;
 pub mod cleanup_post_borrowck;
 pub mod const_prop;
 pub mod copy_prop;
+pub mod dead_store_elimination;
 pub mod deaggregator;
+pub mod dest_prop;
 pub mod dump_mir;
 pub mod elaborate_drops;
 pub mod erase_regions;
 pub mod generator;
+pub mod gvn;
 pub mod inline;
 pub mod instcombine;
+pub mod jump_threading;
 pub mod no_landing_pads;
 pub mod promote_consts;
 pub mod qualify_min_const_fn;
 pub mod remove_noop_landing_pads;
+pub mod remove_redundant_checks;
 pub mod rustc_peek;
 pub mod simplify;
 pub mod simplify_branches;
 pub mod simplify_try;
+pub mod sroa;
 pub mod uninhabited_enum_branching;
 pub mod unreachable_prop;
+pub mod validate;
 
 pub(crate) fn provide(providers: &mut Providers<'_>) {
     self::check_unsafety::provide(providers);
+    self::inline::provide(providers);
     *providers = Providers {
         mir_keys,
         mir_const,
@@ -133,9 +142,45 @@ pub trait MirPass<'tcx> {
         default_name::<Self>()
     }
 
+    /// Returns `true` if this pass should run with the current options, e.g. at the current
+    /// `-Z mir-opt-level`. `-Z mir-enable-passes` overrides this for optimizations.
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        true
+    }
+
+    /// Returns `false` if this pass is an optimization, which the MIR doesn't need to be valid
+    /// and to be codegened. Optimizations can be turned on and off with `-Z mir-enable-passes`,
+    /// and skipped by `-Z mir-opt-bisect-limit`, when optimizing MIR.
+    fn is_required(&self) -> bool {
+        true
+    }
+
     fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>);
 }
 
+/// Returns `true` if `pass` should run, taking `-Z mir-enable-passes` into account. A pass may be
+/// named by its full name, e.g. `SimplifyCfg-final`, or by the name of its type.
+pub fn is_pass_enabled(sess: &Session, pass: &dyn MirPass<'_>) -> bool {
+    enabled_by_flag(sess, pass).unwrap_or_else(|| pass.is_enabled(sess))
+}
+
+/// Returns whether `-Z mir-enable-passes` turns `pass` on or off, if it names it.
+fn enabled_by_flag(sess: &Session, pass: &dyn MirPass<'_>) -> Option<bool> {
+    if pass.is_required() {
+        return None;
+    }
+
+    let name = pass.name();
+    let type_name = name.split('-').next().unwrap();
+    sess.opts
+        .debugging_opts
+        .mir_enable_passes
+        .iter()
+        .rev()
+        .find(|(pass_name, _)| *pass_name == name || pass_name == type_name)
+        .map(|&(_, enabled)| enabled)
+}
+
 pub fn run_passes(
     tcx: TyCtxt<'tcx>,
     body: &mut BodyAndCache<'tcx>,
@@ -151,6 +196,13 @@ pub fn run_passes(
     }
 
     let source = MirSource { instance, promoted };
+    let validate = tcx.sess.opts.debugging_opts.validate_mir;
+
+    if validate {
+        validate::Validator { when: format!("input to phase {:?}", mir_phase) }
+            .run_pass(tcx, source, body);
+    }
+
     let mut index = 0;
     let mut run_pass = |pass: &dyn MirPass<'tcx>| {
         let run_hooks = |body: &_, index, is_after| {
@@ -163,9 +215,36 @@ pub fn run_passes(
                 is_after,
             );
         };
-        run_hooks(body, index, false);
-        pass.run_pass(tcx, source, body);
-        run_hooks(body, index, true);
+        // Only the optimizations of the MIR which is going to be codegened are subject to
+        // `-Z mir-enable-passes` and `-Z mir-opt-bisect-limit`. The passes these flags skip
+        // aren't dumped, so that skipping one shows in the dumps.
+        let (run, dump) = if mir_phase >= MirPhase::Optimized && !pass.is_required() {
+            if is_pass_enabled(tcx.sess, pass) {
+                let run = tcx.sess.mir_opt_bisect_allow(|| {
+                    format!("{} on {}", pass.name(), tcx.def_path_str(source.def_id()))
+                });
+                (run, run)
+            } else {
+                (false, enabled_by_flag(tcx.sess, pass).is_none())
+            }
+        } else {
+            (pass.is_enabled(tcx.sess), true)
+        };
+
+        if dump {
+            run_hooks(body, index, false);
+        }
+        if run {
+            pass.run_pass(tcx, source, body);
+        }
+        if dump {
+            run_hooks(body, index, true);
+        }
+
+        if validate {
+            validate::Validator { when: format!("after pass {}", pass.name()) }
+                .run_pass(tcx, source, body);
+        }
 
         index += 1;
     };
@@ -274,7 +353,7 @@ fn run_optimization_passes<'tcx>(
         body,
         InstanceDef::Item(def_id),
         promoted,
-        MirPhase::Optimized,
+        MirPhase::DropElaboration,
         &[
             // Remove all things only needed by analysis
             &no_landing_pads::NoLandingPads::new(tcx),
@@ -295,6 +374,16 @@ fn run_optimization_passes<'tcx>(
             &add_call_guards::AllCallEdges,
             &add_retag::AddRetag,
             &simplify::SimplifyCfg::new("elaborate-drops"),
+        ],
+    );
+
+    run_passes(
+        tcx,
+        body,
+        InstanceDef::Item(def_id),
+        promoted,
+        MirPhase::Optimized,
+        &[
             // No lifetime analysis based on borrowing can be done from here on out.
 
             // From here on out, regions are gone.
@@ -310,13 +399,19 @@ fn run_optimization_passes<'tcx>(
             &instcombine::InstCombine,
             &const_prop::ConstProp,
             &simplify_branches::SimplifyBranches::new("after-const-prop"),
+            &gvn::GlobalValueNumbering,
             &deaggregator::Deaggregator,
+            &sroa::ScalarReplacementOfAggregates,
             &copy_prop::CopyPropagation,
             &simplify_branches::SimplifyBranches::new("after-copy-prop"),
+            &dest_prop::DestinationPropagation,
             &remove_noop_landing_pads::RemoveNoopLandingPads,
             &simplify::SimplifyCfg::new("after-remove-noop-landing-pads"),
             &simplify_try::SimplifyArmIdentity,
             &simplify_try::SimplifyBranchSame,
+            &jump_threading::JumpThreading,
+            &remove_redundant_checks::RemoveRedundantChecks,
+            &dead_store_elimination::DeadStoreElimination,
             &simplify::SimplifyCfg::new("final"),
             &simplify::SimplifyLocals,
             &add_call_guards::CriticalCallEdges,
@@ -338,6 +433,13 @@ fn optimized_mir(tcx: TyCtxt<'_>, def_id: DefId) -> &BodyAndCache<'_> {
     // execute before we can steal.
     tcx.ensure().mir_borrowck(def_id);
 
+    // `mir_inliner_callees` reads `mir_validated`, and the inliner can ask for
+    // it after our optimizations ran, so it has to execute before we can steal.
+    // Its result isn't cached on disk, so `ensure()` wouldn't be enough.
+    if is_pass_enabled(tcx.sess, &inline::Inline) {
+        let _ = tcx.mir_inliner_callees(def_id);
+    }
+
     let (body, _) = tcx.mir_validated(def_id);
     let mut body = body.steal();
     run_optimization_passes(tcx, &mut body, def_id, None);
@@ -0,0 +1,72 @@
+//! Removal of redundant runtime checks.
+//!
+//! This pass replaces the `Assert` terminators whose condition always has the expected value,
+//! like bounds checks on an index which was already compared with the length of the slice, or
+//! overflow checks on an addition whose operands are small enough, with a `Goto`:
+//!
+//!     bb1: {                              bb1: {
+//!         _6 = Lt(_3, const 8usize);          _6 = Lt(_3, const 8usize);
+//!         switchInt(move _6) -> [..];         switchInt(move _6) -> [..];
+//!     }                                   }
+//!     bb2: {                         =>   bb2: {
+//!         _9 = Lt(_3, const 8usize);          _9 = Lt(_3, const 8usize);
+//!         assert(move _9, ...) -> bb3;        goto -> bb3;
+//!     }                                   }
+//!
+//! Whether a condition always holds comes from the `Intervals` analysis, whose results are
+//! dumped with `-Z dump-mir=intervals`. The comparisons which are no longer used are removed by
+//! `DeadStoreElimination`.
+//!
+//! Only the values of locals are known to the analysis, so the checks in a `for` loop over a
+//! range, as in `for i in 0..v.len() { v[i] }`, are kept: the index is returned by the `next`
+//! method of the range, whose bounds are fields of a borrowed local, and nothing relates it to
+//! the length of the slice.
+
+use crate::dataflow::Intervals;
+use crate::transform::{MirPass, MirSource};
+use rustc::mir::*;
+use rustc::session::Session;
+use rustc::ty::TyCtxt;
+
+pub struct RemoveRedundantChecks;
+
+impl<'tcx> MirPass<'tcx> for RemoveRedundantChecks {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.mir_opt_level >= 2
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        let param_env = tcx.param_env(source.def_id());
+        let redundant_checks: Vec<_> = {
+            let body_ref: &Body<'tcx> = body;
+            let intervals = Intervals::new(tcx, param_env, body_ref);
+            intervals.dump_mir(source);
+
+            body_ref
+                .basic_blocks()
+                .iter_enumerated()
+                .filter_map(|(bb, data)| match &data.terminator().kind {
+                    TerminatorKind::Assert { cond, expected, target, .. } => {
+                        let state = intervals.state_before_terminator(bb);
+                        if intervals.eval_condition(&state, cond) == Some(*expected) {
+                            Some((bb, *target))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        for (bb, target) in redundant_checks {
+            let terminator = body.basic_blocks_mut()[bb].terminator_mut();
+            debug!("RemoveRedundantChecks: removing {:?}", terminator.kind);
+            terminator.kind = TerminatorKind::Goto { target };
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Scalar replacement of aggregates.
+//!
+//! This pass splits the locals of tuple, struct or closure type which are only accessed field by
+//! field into one local per field:
+//!
+//!     StorageLive(_3);                    StorageLive(_8);
+//!     (_3.0: u32) = move _4;              StorageLive(_9);
+//!     (_3.1: u32) = move _5;         =>   _8 = move _4;
+//!     _6 = (_3.0: u32);                   _9 = move _5;
+//!     _7 = (_3.1: u32);                   _6 = _8;
+//!                                         _7 = _9;
+//!
+//! Codegen keeps a local in memory unless it is a single scalar or a pair of scalars, while the
+//! locals of each field can become SSA values. This applies once `Deaggregator` has turned the
+//! aggregate assignments into field assignments.
+//!
+//! A local can't be split if its address is taken, or if it is used as a whole, for example when
+//! moved into a call or dropped. The arguments and the return place are never split. Splitting a
+//! local makes its fields candidates in turn, so nested aggregates are split until no candidate
+//! remains.
+//!
+//! User variables are only split without debuginfo, as theirs would be lost.
+
+use crate::transform::{MirPass, MirSource};
+use rustc::mir::visit::{MutVisitor, MutatingUseContext, NonMutatingUseContext};
+use rustc::mir::visit::{NonUseContext, PlaceContext, Visitor};
+use rustc::mir::*;
+use rustc::session::config::DebugInfo;
+use rustc::session::Session;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_index::bit_set::BitSet;
+use rustc_index::vec::IndexVec;
+
+pub struct ScalarReplacementOfAggregates;
+
+impl<'tcx> MirPass<'tcx> for ScalarReplacementOfAggregates {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.mir_opt_level >= 2
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, _: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        let keep_debuginfo = tcx.sess.opts.debuginfo != DebugInfo::None;
+        loop {
+            let mut finder = CandidateFinder {
+                fields_used: BitSet::new_empty(body.local_decls.len()),
+                not_splittable: BitSet::new_empty(body.local_decls.len()),
+                field_tys: FxHashMap::default(),
+                keep_debuginfo,
+            };
+            for (bb, data) in body.basic_blocks().iter_enumerated() {
+                finder.visit_basic_block_data(bb, data);
+            }
+            for var_debug_info in &body.var_debug_info {
+                finder.visit_var_debug_info(var_debug_info);
+            }
+
+            let mut candidates = finder.fields_used;
+            candidates.subtract(&finder.not_splittable);
+            for local in body.args_iter().chain(Some(RETURN_PLACE)) {
+                candidates.remove(local);
+            }
+            for (local, decl) in body.local_decls.iter_enumerated() {
+                if !is_aggregate(decl.ty) {
+                    candidates.remove(local);
+                }
+            }
+            if candidates.is_empty() {
+                break;
+            }
+
+            let mut field_tys: Vec<_> = finder
+                .field_tys
+                .into_iter()
+                .filter(|((local, _), _)| candidates.contains(*local))
+                .collect();
+            field_tys.sort_by_key(|(key, _)| *key);
+            split_locals(tcx, body, &candidates, field_tys);
+        }
+    }
+}
+
+/// Returns whether `ty` is a type whose fields can be split into separate locals.
+fn is_aggregate(ty: Ty<'_>) -> bool {
+    match ty.kind {
+        ty::Tuple(..) | ty::Closure(..) => true,
+        ty::Adt(adt_def, _) => adt_def.is_struct() && !ty.is_box(),
+        _ => false,
+    }
+}
+
+/// Replaces each field of the `candidates` with a new local, given the type of its fields.
+fn split_locals<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &mut BodyAndCache<'tcx>,
+    candidates: &BitSet<Local>,
+    field_tys: Vec<((Local, Field), Ty<'tcx>)>,
+) {
+    let mut fields = FxHashMap::default();
+    let mut field_locals = IndexVec::from_elem(vec![], &body.local_decls);
+    for ((local, field), ty) in field_tys {
+        let decl = &body.local_decls[local];
+        let mut field_decl = LocalDecl::new_temp(ty, decl.source_info.span);
+        field_decl.internal = decl.internal;
+        let field_local = body.local_decls.push(field_decl);
+        debug!("ScalarReplacementOfAggregates: {:?}.{:?} => {:?}", local, field, field_local);
+        fields.insert((local, field), field_local);
+        field_locals[local].push(field_local);
+    }
+
+    FieldReplacer { tcx, fields }.visit_body(body);
+
+    // The debuginfo which used one of the fields now uses its local, but there is no place left
+    // for the whole aggregate.
+    body.var_debug_info.retain(|var_debug_info| !candidates.contains(var_debug_info.place.local));
+
+    for data in body.basic_blocks_mut() {
+        data.expand_statements(|statement| {
+            let (local, live) = match statement.kind {
+                StatementKind::StorageLive(local) if candidates.contains(local) => (local, true),
+                StatementKind::StorageDead(local) if candidates.contains(local) => (local, false),
+                _ => return None,
+            };
+            let source_info = statement.source_info;
+            let markers: Vec<_> = field_locals[local]
+                .iter()
+                .map(|&field_local| Statement {
+                    source_info,
+                    kind: if live {
+                        StatementKind::StorageLive(field_local)
+                    } else {
+                        StatementKind::StorageDead(field_local)
+                    },
+                })
+                .collect();
+            Some(markers.into_iter())
+        });
+    }
+}
+
+/// Collects the locals whose fields are used, and the ones used in a way that prevents splitting
+/// them.
+struct CandidateFinder<'tcx> {
+    fields_used: BitSet<Local>,
+    not_splittable: BitSet<Local>,
+    /// The type of each field of a local which is used.
+    field_tys: FxHashMap<(Local, Field), Ty<'tcx>>,
+    keep_debuginfo: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for CandidateFinder<'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, _: Location) {
+        // Whether the local must be kept whole, because its address is taken or because splitting
+        // it would lose its debuginfo.
+        let keep_whole = match context {
+            PlaceContext::NonUse(NonUseContext::VarDebugInfo) => self.keep_debuginfo,
+            PlaceContext::MutatingUse(MutatingUseContext::Borrow)
+            | PlaceContext::MutatingUse(MutatingUseContext::AddressOf)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::ShallowBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::UniqueBorrow)
+            | PlaceContext::NonMutatingUse(NonMutatingUseContext::AddressOf) => true,
+            _ => false,
+        };
+        match place.projection.as_ref() {
+            _ if keep_whole => {
+                self.not_splittable.insert(place.local);
+            }
+            // The debuginfo of the whole aggregate is dropped.
+            [] if context == PlaceContext::NonUse(NonUseContext::VarDebugInfo) => {}
+            [ProjectionElem::Field(field, ty), ..] => {
+                self.fields_used.insert(place.local);
+                self.field_tys.entry((place.local, *field)).or_insert(*ty);
+            }
+            _ => {
+                self.not_splittable.insert(place.local);
+            }
+        }
+    }
+}
+
+/// Replaces the uses of the fields of the split locals with their new locals.
+struct FieldReplacer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    fields: FxHashMap<(Local, Field), Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for FieldReplacer<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn visit_place(&mut self, place: &mut Place<'tcx>, context: PlaceContext, location: Location) {
+        let projection = place.projection;
+        if let [ProjectionElem::Field(field, _), rest @ ..] = projection.as_ref() {
+            if let Some(&local) = self.fields.get(&(place.local, *field)) {
+                *place = Place { local, projection: self.tcx.intern_place_elems(rest) };
+            }
+        }
+        self.super_place(place, context, location);
+    }
+}
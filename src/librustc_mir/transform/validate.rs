@@ -0,0 +1,514 @@
+//! Validates the MIR to ensure that invariants are upheld.
+//!
+//! The validator checks that assignments and calls are well-typed, that terminators only jump to
+//! existing blocks and respect the unwind invariants, and that locals are only used while their
+//! storage is live. Once drops are elaborated, it also checks that locals are not used after being
+//! moved out, and that the statements and terminators which only borrowck needs are gone.
+//!
+//! It is run after every pass with `-Z validate-mir`. Violations are reported as delayed bugs
+//! naming the pass which broke the invariant, since broken MIR may also come from code which
+//! already had errors.
+
+use super::{MirPass, MirSource};
+use crate::dataflow::generic::{Analysis, ResultsCursor};
+use crate::dataflow::{MaybeInitializedLocals, MaybeStorageLive};
+use rustc::mir::visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor};
+use rustc::mir::{
+    traversal, BasicBlock, Body, BodyAndCache, BorrowKind, Local, Location, MirPhase, Place,
+    Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+};
+use rustc::ty::relate::{Relate, RelateResult, TypeRelation};
+use rustc::ty::{self, ParamEnv, Ty, TyCtxt};
+use rustc_hir::def_id::DefId;
+use rustc_index::bit_set::BitSet;
+
+#[derive(Copy, Clone, Debug)]
+enum EdgeKind {
+    Unwind,
+    Normal,
+}
+
+pub struct Validator {
+    /// Describes at which point in the pipeline this validation is happening.
+    pub when: String,
+}
+
+impl<'tcx> MirPass<'tcx> for Validator {
+    fn run_pass(&self, tcx: TyCtxt<'tcx>, source: MirSource<'tcx>, body: &mut BodyAndCache<'tcx>) {
+        let def_id = source.def_id();
+        let param_env = tcx.param_env(def_id).with_reveal_all();
+        let body: &Body<'tcx> = body;
+
+        let mut reachable = BitSet::new_empty(body.basic_blocks().len());
+        for (block, _) in traversal::preorder(body) {
+            reachable.insert(block);
+        }
+
+        // `MaybeStorageLive` considers the locals without storage markers dead, except for the
+        // arguments, so only the locals with markers are checked.
+        let mut storage_markers = BitSet::new_empty(body.local_decls.len());
+        for data in body.basic_blocks() {
+            for statement in &data.statements {
+                match statement.kind {
+                    StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
+                        storage_markers.insert(local);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let storage_live = MaybeStorageLive
+            .into_engine(tcx, body, def_id)
+            .iterate_to_fixpoint()
+            .into_results_cursor(body);
+
+        // Before drop elaboration, a `Drop` may refer to a local which was moved out.
+        let initialized = if body.phase >= MirPhase::DropElaboration {
+            Some(
+                MaybeInitializedLocals
+                    .into_engine(tcx, body, def_id)
+                    .iterate_to_fixpoint()
+                    .into_results_cursor(body),
+            )
+        } else {
+            None
+        };
+
+        let mut checker = TypeChecker {
+            when: &self.when,
+            def_id,
+            body,
+            tcx,
+            param_env,
+            reachable,
+            storage_markers,
+            storage_live,
+            initialized,
+        };
+        for (block, data) in body.basic_blocks().iter_enumerated() {
+            checker.visit_basic_block_data(block, data);
+        }
+    }
+}
+
+/// Returns whether the two types are equal up to lifetimes.
+/// All lifetimes, including higher-ranked ones, get ignored for this comparison.
+/// (This is unlike the `erasing_regions` methods, which keep higher-ranked lifetimes for soundness
+/// reasons.)
+///
+/// The point of this function is to approximate "equal up to subtyping". However,
+/// the approximation is incorrect as variance is ignored.
+fn equal_up_to_regions(
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    src: Ty<'tcx>,
+    dest: Ty<'tcx>,
+) -> bool {
+    struct LifetimeIgnoreRelation<'tcx> {
+        tcx: TyCtxt<'tcx>,
+        param_env: ty::ParamEnv<'tcx>,
+    }
+
+    impl TypeRelation<'tcx> for LifetimeIgnoreRelation<'tcx> {
+        fn tcx(&self) -> TyCtxt<'tcx> {
+            self.tcx
+        }
+
+        fn param_env(&self) -> ty::ParamEnv<'tcx> {
+            self.param_env
+        }
+
+        fn tag(&self) -> &'static str {
+            "librustc_mir::transform::validate"
+        }
+
+        fn a_is_expected(&self) -> bool {
+            true
+        }
+
+        fn relate_with_variance<T: Relate<'tcx>>(
+            &mut self,
+            _: ty::Variance,
+            a: &T,
+            b: &T,
+        ) -> RelateResult<'tcx, T> {
+            // Ignore variance, require types to be exactly the same.
+            self.relate(a, b)
+        }
+
+        fn tys(&mut self, a: Ty<'tcx>, b: Ty<'tcx>) -> RelateResult<'tcx, Ty<'tcx>> {
+            if a == b {
+                // Short-circuit.
+                return Ok(a);
+            }
+            ty::relate::super_relate_tys(self, a, b)
+        }
+
+        fn regions(
+            &mut self,
+            a: ty::Region<'tcx>,
+            _b: ty::Region<'tcx>,
+        ) -> RelateResult<'tcx, ty::Region<'tcx>> {
+            // Ignore regions.
+            Ok(a)
+        }
+
+        fn consts(
+            &mut self,
+            a: &'tcx ty::Const<'tcx>,
+            b: &'tcx ty::Const<'tcx>,
+        ) -> RelateResult<'tcx, &'tcx ty::Const<'tcx>> {
+            ty::relate::super_relate_consts(self, a, b)
+        }
+
+        fn binders<T>(
+            &mut self,
+            a: &ty::Binder<T>,
+            b: &ty::Binder<T>,
+        ) -> RelateResult<'tcx, ty::Binder<T>>
+        where
+            T: Relate<'tcx>,
+        {
+            Ok(ty::Binder::bind(self.relate(a.skip_binder(), b.skip_binder())?))
+        }
+    }
+
+    // Instantiate and run relation.
+    let mut relator: LifetimeIgnoreRelation<'tcx> = LifetimeIgnoreRelation { tcx, param_env };
+    relator.relate(&src, &dest).is_ok()
+}
+
+struct TypeChecker<'a, 'tcx> {
+    when: &'a str,
+    def_id: DefId,
+    body: &'a Body<'tcx>,
+    tcx: TyCtxt<'tcx>,
+    param_env: ParamEnv<'tcx>,
+    reachable: BitSet<BasicBlock>,
+    storage_markers: BitSet<Local>,
+    storage_live: ResultsCursor<'a, 'tcx, MaybeStorageLive>,
+    /// Only computed once drops are elaborated.
+    initialized: Option<ResultsCursor<'a, 'tcx, MaybeInitializedLocals>>,
+}
+
+impl<'a, 'tcx> TypeChecker<'a, 'tcx> {
+    fn fail(&self, location: Location, msg: impl AsRef<str>) {
+        let span = self.body.source_info(location).span;
+        // We use `delay_span_bug` as we might see broken MIR when other errors have already
+        // occurred.
+        self.tcx.sess.delay_span_bug(
+            span,
+            &format!(
+                "broken MIR in {:?} ({}) at {:?}:\n{}",
+                self.def_id,
+                self.when,
+                location,
+                msg.as_ref()
+            ),
+        );
+    }
+
+    fn drops_elaborated(&self) -> bool {
+        self.body.phase >= MirPhase::DropElaboration
+    }
+
+    fn check_edge(&self, location: Location, bb: BasicBlock, edge_kind: EdgeKind) {
+        if let Some(target) = self.body.basic_blocks().get(bb) {
+            let src = &self.body.basic_blocks()[location.block];
+            match (src.is_cleanup, target.is_cleanup, edge_kind) {
+                // Non-cleanup blocks can jump to non-cleanup blocks along non-unwind edges
+                (false, false, EdgeKind::Normal)
+                // Non-cleanup blocks can jump to cleanup blocks along unwind edges
+                | (false, true, EdgeKind::Unwind)
+                // Cleanup blocks can jump to cleanup blocks along non-unwind edges
+                | (true, true, EdgeKind::Normal) => {}
+                // All other jumps are invalid
+                _ => self.fail(
+                    location,
+                    format!(
+                        "{:?} edge to {:?} violates unwind invariants (cleanup {:?} -> {:?})",
+                        edge_kind, bb, src.is_cleanup, target.is_cleanup,
+                    ),
+                ),
+            }
+        } else {
+            self.fail(location, format!("encountered jump to invalid basic block {:?}", bb))
+        }
+    }
+
+    /// Check if src can be assigned into dest.
+    /// This is not precise, it will accept some incorrect assignments.
+    fn mir_assign_valid_types(&self, src: Ty<'tcx>, dest: Ty<'tcx>) -> bool {
+        // Fast path before we normalize.
+        if src == dest {
+            // Equal types, all is good.
+            return true;
+        }
+        // Normalize projections and things like that.
+        let src = self.tcx.normalize_erasing_regions(self.param_env, src);
+        let dest = self.tcx.normalize_erasing_regions(self.param_env, dest);
+
+        // Type-changing assignments can happen when subtyping is used. While
+        // all normal lifetimes are erased, higher-ranked types with their
+        // late-bound lifetimes are still around and can lead to type
+        // differences. So we compare ignoring lifetimes.
+        equal_up_to_regions(self.tcx, self.param_env, src, dest)
+    }
+
+    fn check_storage(&mut self, local: Local, location: Location) {
+        if !self.storage_markers.contains(local) {
+            return;
+        }
+        self.storage_live.seek_before(location);
+        if !self.storage_live.contains(local) {
+            self.fail(location, format!("use of local {:?}, which has no storage here", local));
+        }
+    }
+
+    fn check_initialized(&mut self, local: Local, location: Location) {
+        let is_initialized = match &mut self.initialized {
+            Some(initialized) => {
+                initialized.seek_before(location);
+                initialized.contains(local)
+            }
+            None => return,
+        };
+        if !is_initialized {
+            self.fail(location, format!("use of moved-out or uninitialized local {:?}", local));
+        }
+    }
+}
+
+/// Returns whether `context` reads the value of the place, or drops it.
+fn is_read(context: PlaceContext) -> bool {
+    match context {
+        PlaceContext::NonMutatingUse(NonMutatingUseContext::Copy)
+        | PlaceContext::NonMutatingUse(NonMutatingUseContext::Move)
+        | PlaceContext::NonMutatingUse(NonMutatingUseContext::Inspect)
+        | PlaceContext::MutatingUse(MutatingUseContext::Drop) => true,
+        _ => false,
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for TypeChecker<'a, 'tcx> {
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext, location: Location) {
+        if !self.reachable.contains(location.block) || !context.is_use() {
+            return;
+        }
+
+        self.check_storage(local, location);
+        if is_read(context) {
+            self.check_initialized(local, location);
+        }
+    }
+
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        // The base local of a projection is visited with a `Projection` context, so reads of a
+        // part of a local are checked here.
+        if !place.projection.is_empty()
+            && is_read(context)
+            && self.reachable.contains(location.block)
+        {
+            self.check_initialized(place.local, location);
+        }
+
+        self.super_place(place, context, location);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement<'tcx>, location: Location) {
+        match &statement.kind {
+            StatementKind::Assign(box (dest, rvalue)) => {
+                // LHS and RHS of the assignment must have the same type.
+                let left_ty = dest.ty(self.body, self.tcx).ty;
+                let right_ty = rvalue.ty(self.body, self.tcx);
+                if !self.mir_assign_valid_types(right_ty, left_ty) {
+                    self.fail(
+                        location,
+                        format!(
+                            "encountered `Assign` statement with incompatible types:\n\
+                            left-hand side has type: {}\n\
+                            right-hand side has type: {}",
+                            left_ty, right_ty,
+                        ),
+                    );
+                }
+                if let Rvalue::Ref(_, BorrowKind::Shallow, _) = rvalue {
+                    if self.drops_elaborated() {
+                        self.fail(
+                            location,
+                            "shallow borrows are not allowed after drop elaboration",
+                        );
+                    }
+                }
+            }
+            StatementKind::FakeRead(..) | StatementKind::AscribeUserType(..)
+                if self.drops_elaborated() =>
+            {
+                self.fail(
+                    location,
+                    format!("`{:?}` statements are not allowed after drop elaboration", statement),
+                );
+            }
+            _ => {}
+        }
+
+        self.super_statement(statement, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location) {
+        match &terminator.kind {
+            TerminatorKind::Goto { target } => {
+                self.check_edge(location, *target, EdgeKind::Normal);
+            }
+            TerminatorKind::SwitchInt { discr, switch_ty, values, targets } => {
+                let ty = discr.ty(self.body, self.tcx);
+                if !self.mir_assign_valid_types(ty, *switch_ty) {
+                    self.fail(
+                        location,
+                        format!(
+                            "encountered `SwitchInt` terminator with type mismatch: {:?} != {:?}",
+                            ty, switch_ty,
+                        ),
+                    );
+                }
+                if targets.len() != values.len() + 1 {
+                    self.fail(
+                        location,
+                        format!(
+                            "encountered `SwitchInt` terminator with {} values, but {} targets \
+                            (should be values+1)",
+                            values.len(),
+                            targets.len(),
+                        ),
+                    );
+                }
+                for &target in targets {
+                    self.check_edge(location, target, EdgeKind::Normal);
+                }
+            }
+            TerminatorKind::Drop { target, unwind, .. } => {
+                self.check_edge(location, *target, EdgeKind::Normal);
+                if let Some(unwind) = unwind {
+                    self.check_edge(location, *unwind, EdgeKind::Unwind);
+                }
+            }
+            TerminatorKind::DropAndReplace { target, unwind, .. } => {
+                if self.drops_elaborated() {
+                    self.fail(
+                        location,
+                        "`DropAndReplace` terminators are not allowed after drop elaboration",
+                    );
+                }
+                self.check_edge(location, *target, EdgeKind::Normal);
+                if let Some(unwind) = unwind {
+                    self.check_edge(location, *unwind, EdgeKind::Unwind);
+                }
+            }
+            TerminatorKind::Call { func, args, destination, cleanup, .. } => {
+                let func_ty = func.ty(self.body, self.tcx);
+                match func_ty.kind {
+                    ty::FnPtr(..) | ty::FnDef(..) => {
+                        let sig = self.tcx.erase_late_bound_regions(&func_ty.fn_sig(self.tcx));
+                        let sig = self.tcx.normalize_erasing_regions(self.param_env, sig);
+
+                        let arg_count_matches = if sig.c_variadic {
+                            args.len() >= sig.inputs().len()
+                        } else {
+                            args.len() == sig.inputs().len()
+                        };
+                        if !arg_count_matches {
+                            self.fail(
+                                location,
+                                format!(
+                                    "encountered `Call` terminator with {} arguments, but the \
+                                    callee takes {}",
+                                    args.len(),
+                                    sig.inputs().len(),
+                                ),
+                            );
+                        }
+                        for (arg, &param_ty) in args.iter().zip(sig.inputs()) {
+                            let arg_ty = arg.ty(self.body, self.tcx);
+                            if !self.mir_assign_valid_types(arg_ty, param_ty) {
+                                self.fail(
+                                    location,
+                                    format!(
+                                        "encountered `Call` terminator with argument of type {}, \
+                                        but the parameter has type {}",
+                                        arg_ty, param_ty,
+                                    ),
+                                );
+                            }
+                        }
+                        if let Some((dest, _)) = destination {
+                            let dest_ty = dest.ty(self.body, self.tcx).ty;
+                            if !self.mir_assign_valid_types(sig.output(), dest_ty) {
+                                self.fail(
+                                    location,
+                                    format!(
+                                        "encountered `Call` terminator with destination of type \
+                                        {}, but the callee returns {}",
+                                        dest_ty,
+                                        sig.output(),
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                    _ => self.fail(
+                        location,
+                        format!("encountered non-callable type {} in `Call` terminator", func_ty),
+                    ),
+                }
+                if let Some((_, target)) = destination {
+                    self.check_edge(location, *target, EdgeKind::Normal);
+                }
+                if let Some(cleanup) = cleanup {
+                    self.check_edge(location, *cleanup, EdgeKind::Unwind);
+                }
+            }
+            TerminatorKind::Assert { target, cleanup, .. } => {
+                self.check_edge(location, *target, EdgeKind::Normal);
+                if let Some(cleanup) = cleanup {
+                    self.check_edge(location, *cleanup, EdgeKind::Unwind);
+                }
+            }
+            TerminatorKind::Yield { resume, drop, .. } => {
+                self.check_edge(location, *resume, EdgeKind::Normal);
+                if let Some(drop) = drop {
+                    self.check_edge(location, *drop, EdgeKind::Normal);
+                }
+            }
+            TerminatorKind::FalseEdges { real_target, imaginary_target } => {
+                if self.drops_elaborated() {
+                    self.fail(
+                        location,
+                        "`FalseEdges` terminators are not allowed after drop elaboration",
+                    );
+                }
+                self.check_edge(location, *real_target, EdgeKind::Normal);
+                self.check_edge(location, *imaginary_target, EdgeKind::Normal);
+            }
+            TerminatorKind::FalseUnwind { real_target, unwind } => {
+                if self.drops_elaborated() {
+                    self.fail(
+                        location,
+                        "`FalseUnwind` terminators are not allowed after drop elaboration",
+                    );
+                }
+                self.check_edge(location, *real_target, EdgeKind::Normal);
+                if let Some(unwind) = unwind {
+                    self.check_edge(location, *unwind, EdgeKind::Unwind);
+                }
+            }
+            TerminatorKind::Resume
+            | TerminatorKind::Abort
+            | TerminatorKind::Return
+            | TerminatorKind::Unreachable
+            | TerminatorKind::GeneratorDrop => {}
+        }
+
+        self.super_terminator(terminator, location);
+    }
+}
@@ -57,10 +57,20 @@ pub struct LivenessResult {
 /// Computes which local variables are live within the given function
 /// `mir`, including drops.
 pub fn liveness_of_locals(body: ReadOnlyBodyAndCache<'_, '_>) -> LivenessResult {
+    compute_liveness(body, false)
+}
+
+/// Like `liveness_of_locals`, but also considers the return place to be used
+/// by `Return` terminators, since the caller reads it then.
+pub fn liveness_of_locals_and_return_place(body: ReadOnlyBodyAndCache<'_, '_>) -> LivenessResult {
+    compute_liveness(body, true)
+}
+
+fn compute_liveness(body: ReadOnlyBodyAndCache<'_, '_>, use_return_place: bool) -> LivenessResult {
     let num_live_vars = body.local_decls.len();
 
     let def_use: IndexVec<_, DefsUses> =
-        body.basic_blocks().iter().map(|b| block(b, num_live_vars)).collect();
+        body.basic_blocks().iter().map(|b| block(b, num_live_vars, use_return_place)).collect();
 
     let mut outs: IndexVec<_, LiveVarSet> =
         body.basic_blocks().indices().map(|_| LiveVarSet::new_empty(num_live_vars)).collect();
@@ -238,7 +248,7 @@ impl<'tcx> Visitor<'tcx> for DefsUsesVisitor {
     }
 }
 
-fn block(b: &BasicBlockData<'_>, locals: usize) -> DefsUses {
+fn block(b: &BasicBlockData<'_>, locals: usize, use_return_place: bool) -> DefsUses {
     let mut visitor = DefsUsesVisitor {
         defs_uses: DefsUses {
             defs: LiveVarSet::new_empty(locals),
@@ -251,6 +261,11 @@ fn block(b: &BasicBlockData<'_>, locals: usize) -> DefsUses {
     // Visit the various parts of the basic block in reverse. If we go
     // forward, the logic in `add_def` and `add_use` would be wrong.
     visitor.visit_terminator(b.terminator(), dummy_location);
+    if let TerminatorKind::Return = b.terminator().kind {
+        if use_return_place {
+            visitor.defs_uses.add_use(RETURN_PLACE);
+        }
+    }
     for statement in b.statements.iter().rev() {
         visitor.visit_statement(statement, dummy_location);
     }
@@ -26,6 +26,7 @@ use std::collections::btree_map::{
     Iter as BTreeMapIter, Keys as BTreeMapKeysIter, Values as BTreeMapValuesIter,
 };
 use std::collections::{BTreeMap, BTreeSet};
+use std::env;
 use std::fmt;
 use std::iter::{self, FromIterator};
 use std::path::{Path, PathBuf};
@@ -167,6 +168,31 @@ pub enum SymbolManglingVersion {
 
 impl_stable_hash_via_hash!(SymbolManglingVersion);
 
+/// The format of the report printed by `-Z incremental-rebuild-report`.
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+pub enum RebuildReportFormat {
+    /// Every re-executed query and codegen unit, with the changed inputs below it.
+    Tree,
+    Json,
+}
+
+/// The format of the event data written by `-Z self-profile`.
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+pub enum SelfProfileFormat {
+    /// The binary files analyzed by the `measureme` tools.
+    Measureme,
+    /// A JSON file in the trace event format of Chrome's tracing UI.
+    Chrome,
+}
+
+/// The format of the summary printed by `-Z self-profile-summary`.
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+pub enum SelfProfileSummaryFormat {
+    /// A table of the queries and generic activities, sorted by self time.
+    Table,
+    Json,
+}
+
 #[derive(Clone, Copy, PartialEq, Hash)]
 pub enum DebugInfo {
     None,
@@ -589,11 +615,24 @@ impl Options {
     #[inline(always)]
     pub fn enable_dep_node_debug_strs(&self) -> bool {
         cfg!(debug_assertions)
-            && (self.debugging_opts.query_dep_graph || self.debugging_opts.incremental_info)
+            && (self.debugging_opts.query_dep_graph
+                || self.debugging_opts.incremental_info
+                || self.debugging_opts.incremental_rebuild_report.is_some())
     }
 
     pub fn file_path_mapping(&self) -> FilePathMapping {
-        FilePathMapping::new(self.remap_path_prefix.clone())
+        if !self.debugging_opts.incremental_relocatable {
+            return FilePathMapping::new(self.remap_path_prefix.clone());
+        }
+
+        // Paths under the working directory become relative to it, unless they are remapped
+        // explicitly: later mappings take precedence.
+        let mut mapping = vec![];
+        if let Ok(working_dir) = env::current_dir() {
+            mapping.push((working_dir, PathBuf::new()));
+        }
+        mapping.extend(self.remap_path_prefix.iter().cloned());
+        FilePathMapping::relocatable(mapping)
     }
 
     /// Returns `true` if there will be an output file generated.
@@ -1283,6 +1322,13 @@ fn check_thread_count(debugging_opts: &DebuggingOptions, error_format: ErrorOutp
     if debugging_opts.threads > 1 && debugging_opts.fuel.is_some() {
         early_error(error_format, "optimization fuel is incompatible with multiple threads");
     }
+
+    if debugging_opts.threads > 1 && debugging_opts.mir_opt_bisect_limit.is_some() {
+        early_error(
+            error_format,
+            "MIR optimization bisection is incompatible with multiple threads",
+        );
+    }
 }
 
 fn select_incremental_path(
@@ -2074,6 +2120,8 @@ crate mod dep_tracking {
     impl_dep_tracking_hash_via_hash!(LinkerPluginLto);
     impl_dep_tracking_hash_via_hash!(SwitchWithOptPath);
     impl_dep_tracking_hash_via_hash!(SymbolManglingVersion);
+    // The order of the entries matters, as later ones override earlier ones.
+    impl_dep_tracking_hash_via_hash!(Vec<(String, bool)>);
 
     impl_dep_tracking_hash_for_sortable_vec_of!(String);
     impl_dep_tracking_hash_for_sortable_vec_of!(PathBuf);
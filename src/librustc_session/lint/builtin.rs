@@ -386,6 +386,12 @@ declare_lint! {
     "failures in resolving intra-doc link targets"
 }
 
+declare_lint! {
+    pub BROKEN_DOC_LINKS,
+    Warn,
+    "detects links in the generated documentation which don't lead anywhere"
+}
+
 declare_lint! {
     pub MISSING_DOC_CODE_EXAMPLES,
     Allow,
@@ -547,6 +553,7 @@ declare_lint_pass! {
         UNSTABLE_NAME_COLLISIONS,
         IRREFUTABLE_LET_PATTERNS,
         INTRA_DOC_LINK_RESOLUTION_FAILURE,
+        BROKEN_DOC_LINKS,
         MISSING_DOC_CODE_EXAMPLES,
         PRIVATE_DOC_TESTS,
         WHERE_CLAUSES_OBJECT_SAFETY,
@@ -246,6 +246,8 @@ macro_rules! options {
         pub const parse_list: Option<&str> = Some("a space-separated list of strings");
         pub const parse_opt_list: Option<&str> = Some("a space-separated list of strings");
         pub const parse_opt_comma_list: Option<&str> = Some("a comma-separated list of strings");
+        pub const parse_list_with_polarity: Option<&str> =
+            Some("a comma-separated list of strings, each starting with `+` or `-`");
         pub const parse_threads: Option<&str> = Some("a number");
         pub const parse_uint: Option<&str> = Some("a number");
         pub const parse_passes: Option<&str> =
@@ -283,12 +285,19 @@ macro_rules! options {
             Some("one of: `disabled`, `trampolines`, or `aliases`");
         pub const parse_symbol_mangling_version: Option<&str> =
             Some("either `legacy` or `v0` (RFC 2603)");
+        pub const parse_rebuild_report_format: Option<&str> =
+            Some("either `tree` or `json`");
+        pub const parse_self_profile_summary_format: Option<&str> =
+            Some("either `table` or `json`, or omitted");
+        pub const parse_self_profile_format: Option<&str> =
+            Some("either `measureme` or `chrome`");
     }
 
     #[allow(dead_code)]
     mod $mod_set {
         use super::{$struct_name, Passes, Sanitizer, LtoCli, LinkerPluginLto, SwitchWithOptPath,
-            SymbolManglingVersion, CFGuard};
+            SymbolManglingVersion, CFGuard, RebuildReportFormat, SelfProfileSummaryFormat,
+            SelfProfileFormat};
         use rustc_target::spec::{LinkerFlavor, MergeFunctions, PanicStrategy, RelroLevel};
         use std::path::PathBuf;
         use std::str::FromStr;
@@ -395,6 +404,26 @@ macro_rules! options {
             }
         }
 
+        fn parse_list_with_polarity(slot: &mut Vec<(String, bool)>, v: Option<&str>) -> bool {
+            match v {
+                Some(s) => {
+                    for s in s.split(',') {
+                        let pass_name = match s.get(1..) {
+                            Some(name) if !name.is_empty() => name.to_string(),
+                            _ => return false,
+                        };
+                        match &s[..1] {
+                            "+" => slot.push((pass_name, true)),
+                            "-" => slot.push((pass_name, false)),
+                            _ => return false,
+                        }
+                    }
+                    true
+                },
+                None => false,
+            }
+        }
+
         fn parse_threads(slot: &mut usize, v: Option<&str>) -> bool {
             match v.and_then(|s| s.parse().ok()) {
                 Some(0) => { *slot = ::num_cpus::get(); true },
@@ -619,6 +648,39 @@ macro_rules! options {
             };
             true
         }
+
+        fn parse_rebuild_report_format(
+            slot: &mut Option<RebuildReportFormat>,
+            v: Option<&str>,
+        ) -> bool {
+            *slot = match v {
+                Some("tree") => Some(RebuildReportFormat::Tree),
+                Some("json") => Some(RebuildReportFormat::Json),
+                _ => return false,
+            };
+            true
+        }
+
+        fn parse_self_profile_summary_format(
+            slot: &mut Option<SelfProfileSummaryFormat>,
+            v: Option<&str>,
+        ) -> bool {
+            *slot = match v {
+                None | Some("table") => Some(SelfProfileSummaryFormat::Table),
+                Some("json") => Some(SelfProfileSummaryFormat::Json),
+                _ => return false,
+            };
+            true
+        }
+
+        fn parse_self_profile_format(slot: &mut SelfProfileFormat, v: Option<&str>) -> bool {
+            *slot = match v {
+                Some("measureme") => SelfProfileFormat::Measureme,
+                Some("chrome") => SelfProfileFormat::Chrome,
+                _ => return false,
+            };
+            true
+        }
     }
 ) }
 
@@ -792,10 +854,35 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "enable incremental compilation support for queries (experimental)"),
     incremental_info: bool = (false, parse_bool, [UNTRACKED],
         "print high-level information about incremental reuse (or the lack thereof)"),
+    incremental_max_size: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "keep the incremental compilation directory under this size, in MiB, by deleting the least \
+         recently used caches of all the crates sharing it"),
+    incremental_max_age: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "delete the caches in the incremental compilation directory which haven't been used for \
+         this many days"),
+    incremental_keep_sessions: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "keep this many finalized session directories of the crate, with a manifest of the \
+         source files they were built from, instead of only the most recent one"),
+    incremental_replay: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "start from the finalized session directory with this name, e.g. one kept with \
+         `-Z incremental-keep-sessions`, instead of the most recent one"),
+    incremental_relocatable: bool = (false, parse_bool, [TRACKED],
+        "store file paths in the incremental cache relative to the working directory \
+         (or to `--remap-path-prefix` roots), so that the cache can be used from another checkout"),
+    incremental_import: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "when the incremental cache of the crate is empty, start from the one in this incremental \
+         directory, e.g. one built with `-Z incremental-relocatable` on another machine"),
+    incremental_rebuild_report: Option<RebuildReportFormat> = (None,
+        parse_rebuild_report_format, [UNTRACKED],
+        "explain which changed inputs caused each query and codegen unit to be re-executed, \
+         as a `tree` or as `json`"),
     incremental_dump_hash: bool = (false, parse_bool, [UNTRACKED],
         "dump hash information in textual format to stdout"),
     incremental_verify_ich: bool = (false, parse_bool, [UNTRACKED],
         "verify incr. comp. hashes of green query instances"),
+    incremental_verify_cache: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+        "recompute this percentage of the query results loaded from the incr. comp. cache, \
+        and report those which differ from the cached ones"),
     incremental_ignore_spans: bool = (false, parse_bool, [UNTRACKED],
         "ignore spans during ICH computation -- used for testing"),
     instrument_mcount: bool = (false, parse_bool, [TRACKED],
@@ -804,6 +891,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "dump the dependency graph to $RUST_DEP_GRAPH (default: /tmp/dep_graph.gv)"),
     query_dep_graph: bool = (false, parse_bool, [UNTRACKED],
         "enable queries of the dependency graph for regression testing"),
+    query_saved_dep_graph: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "instead of compiling, answer a query (`dependents`, `dependencies`, `path`, `fan-in` \
+        or `dot`) about the dependency graph saved by the previous incremental session"),
     no_analysis: bool = (false, parse_bool, [UNTRACKED],
         "parse and expand the source, but run no analysis"),
     unstable_options: bool = (false, parse_bool, [UNTRACKED],
@@ -828,6 +918,19 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "print the result of the monomorphization collection pass"),
     mir_opt_level: usize = (1, parse_uint, [TRACKED],
         "set the MIR optimization level (0-3, default: 1)"),
+    inline_mir: Option<bool> = (None, parse_opt_bool, [TRACKED],
+        "enable MIR inlining (default: with `-Z mir-opt-level=2` and above)"),
+    inline_mir_threshold: usize = (50, parse_uint, [TRACKED],
+        "the cost threshold below which a function is inlined into MIR callers (default: 50)"),
+    inline_mir_hint_threshold: usize = (100, parse_uint, [TRACKED],
+        "the cost threshold for MIR inlining of `#[inline]` functions (default: 100)"),
+    mir_enable_passes: Vec<(String, bool)> = (Vec::new(), parse_list_with_polarity, [TRACKED],
+        "use like `-Zmir-enable-passes=+Inline,-ConstProp` to force MIR optimizations on or off, \
+        regardless of the MIR optimization level; the last occurrence of a pass wins"),
+    mir_opt_bisect_limit: Option<usize> = (None, parse_opt_uint, [TRACKED],
+        "only run the first N MIR optimizations, counting every pass on every body, and print \
+        each of them, to find the one which breaks a program; incompatible with multiple \
+        threads (default: no limit)"),
     mutable_noalias: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "emit noalias metadata for mutable references (default: no)"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
@@ -844,6 +947,8 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "in addition to `.mir` files, create graphviz `.dot` files"),
     dump_mir_exclude_pass_number: bool = (false, parse_bool, [UNTRACKED],
         "if set, exclude the pass number when dumping MIR (used in tests)"),
+    validate_mir: bool = (false, parse_bool, [UNTRACKED],
+        "validate MIR after each transformation, and report which pass broke it"),
     mir_emit_retag: bool = (false, parse_bool, [TRACKED],
         "emit Retagging MIR statements, interpreted e.g., by miri; implies -Zmir-opt-level=0"),
     perf_stats: bool = (false, parse_bool, [UNTRACKED],
@@ -933,6 +1038,14 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
     self_profile: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
         parse_switch_with_opt_path, [UNTRACKED],
         "run the self profiler and output the raw event data"),
+    self_profile_format: SelfProfileFormat = (SelfProfileFormat::Measureme,
+        parse_self_profile_format, [UNTRACKED],
+        "the format of the event data output by `-Z self-profile`: the `measureme` binary \
+        files (the default), or a `chrome` trace event JSON file"),
+    self_profile_summary: Option<SelfProfileSummaryFormat> = (None,
+        parse_self_profile_summary_format, [UNTRACKED],
+        "run the self profiler and print the time spent in each query and generic activity \
+        at the end of the compilation, as a `table` (the default) or as `json`"),
     // keep this in sync with the event filter names in librustc_data_structures/profiling.rs
     self_profile_events: Option<Vec<String>> = (None, parse_opt_comma_list, [UNTRACKED],
         "specifies which kinds of events get recorded by the self profiler;
@@ -119,6 +119,9 @@ pub struct Session {
     /// Always set to zero and incremented so that we can print fuel expended by a crate.
     pub print_fuel: AtomicU64,
 
+    /// The number of MIR optimizations considered so far, for `-Zmir-opt-bisect-limit`.
+    mir_opt_bisect_count: AtomicUsize,
+
     /// Loaded up early on in the initialization of this `Session` to avoid
     /// false positives about a job server in our environment.
     pub jobserver: Client,
@@ -729,12 +732,40 @@ impl Session {
         ret
     }
 
+    /// Whether the next MIR optimization may run. With `-Zmir-opt-bisect-limit=n`, only the first
+    /// `n` optimizations run, and each one is printed along with its number, so that the one
+    /// which breaks a program can be found by bisecting on `n`. The optimizations are numbered in
+    /// the order in which they are considered, which is only deterministic with a single thread.
+    pub fn mir_opt_bisect_allow<T: FnOnce() -> String>(&self, msg: T) -> bool {
+        let limit = match self.opts.debugging_opts.mir_opt_bisect_limit {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let count = self.mir_opt_bisect_count.fetch_add(1, SeqCst) + 1;
+        let allowed = count <= limit;
+        let running = if allowed { "running" } else { "NOT running" };
+        eprintln!("BISECT: {} MIR optimization ({}) {}", running, count, msg());
+        allowed
+    }
+
     /// Returns the number of query threads that should be used for this
     /// compilation
     pub fn threads(&self) -> usize {
         self.opts.debugging_opts.threads
     }
 
+    /// Runs `f`, which may check many items in parallel, so that the
+    /// diagnostics it reports come out in the same order whatever the number
+    /// of threads. With a single thread they are emitted as usual, in the order
+    /// in which the items are checked.
+    pub fn with_deterministic_diagnostics<R>(&self, f: impl FnOnce() -> R) -> R {
+        if cfg!(parallel_compiler) && self.threads() > 1 {
+            self.diagnostic().buffer_diagnostics(f)
+        } else {
+            f()
+        }
+    }
+
     /// Returns the number of codegen units that should be used for this
     /// compilation
     pub fn codegen_units(&self) -> usize {
@@ -965,15 +996,26 @@ fn build_session_(
     source_map: Lrc<source_map::SourceMap>,
     driver_lint_caps: FxHashMap<lint::LintId, lint::Level>,
 ) -> Session {
-    let self_profiler = if let SwitchWithOptPath::Enabled(ref d) = sopts.debugging_opts.self_profile
-    {
-        let directory =
-            if let Some(ref directory) = d { directory } else { std::path::Path::new(".") };
+    let summary = sopts.debugging_opts.self_profile_summary.is_some();
+    let self_profiler = if sopts.debugging_opts.self_profile.enabled() || summary {
+        // With only `-Z self-profile-summary`, the raw event data isn't kept.
+        let directory = match sopts.debugging_opts.self_profile {
+            SwitchWithOptPath::Enabled(Some(ref directory)) => Some(directory.as_path()),
+            SwitchWithOptPath::Enabled(None) => Some(std::path::Path::new(".")),
+            SwitchWithOptPath::Disabled => None,
+        };
+        let (measureme_directory, chrome_trace_directory) =
+            match sopts.debugging_opts.self_profile_format {
+                config::SelfProfileFormat::Measureme => (directory, None),
+                config::SelfProfileFormat::Chrome => (None, directory),
+            };
 
         let profiler = SelfProfiler::new(
-            directory,
+            measureme_directory,
             sopts.crate_name.as_ref().map(|s| &s[..]),
             &sopts.debugging_opts.self_profile_events,
+            summary,
+            chrome_trace_directory,
         );
         match profiler {
             Ok(profiler) => Some(Arc::new(profiler)),
@@ -1069,6 +1111,7 @@ fn build_session_(
         optimization_fuel,
         print_fuel_crate,
         print_fuel,
+        mir_opt_bisect_count: AtomicUsize::new(0),
         jobserver: jobserver::client(),
         driver_lint_caps,
         trait_methods_not_found: Lock::new(Default::default()),
@@ -209,6 +209,29 @@ impl SourceMap {
         self.files.borrow().stable_id_to_source_file.get(&stable_id).cloned()
     }
 
+    /// Returns the `StableSourceFileId` of a file of this `SourceMap`.
+    ///
+    /// This is `StableSourceFileId::new`, except that the paths of remapped files aren't taken
+    /// into account if the mapping is relocatable.
+    pub fn stable_id(&self, source_file: &SourceFile) -> StableSourceFileId {
+        self.stable_id_from_pieces(
+            &source_file.name,
+            source_file.name_was_remapped,
+            source_file.unmapped_path.as_ref(),
+        )
+    }
+
+    fn stable_id_from_pieces(
+        &self,
+        name: &FileName,
+        name_was_remapped: bool,
+        unmapped_path: Option<&FileName>,
+    ) -> StableSourceFileId {
+        let unmapped_path =
+            if name_was_remapped && self.path_mapping.relocatable { None } else { unmapped_path };
+        StableSourceFileId::new_from_pieces(name, name_was_remapped, unmapped_path)
+    }
+
     fn allocate_address_space(&self, size: usize) -> Result<usize, OffsetOverflowError> {
         let size = u32::try_from(size).map_err(|_| OffsetOverflowError)?;
 
@@ -261,8 +284,7 @@ impl SourceMap {
             other => (other, false),
         };
 
-        let file_id =
-            StableSourceFileId::new_from_pieces(&filename, was_remapped, Some(&unmapped_path));
+        let file_id = self.stable_id_from_pieces(&filename, was_remapped, Some(&unmapped_path));
 
         let lrc_sf = match self.source_file_by_stable_id(file_id) {
             Some(lrc_sf) => lrc_sf,
@@ -350,7 +372,7 @@ impl SourceMap {
         files.source_files.push(source_file.clone());
         files
             .stable_id_to_source_file
-            .insert(StableSourceFileId::new(&source_file), source_file.clone());
+            .insert(self.stable_id(&source_file), source_file.clone());
 
         source_file
     }
@@ -980,15 +1002,27 @@ impl SourceMap {
 #[derive(Clone)]
 pub struct FilePathMapping {
     mapping: Vec<(PathBuf, PathBuf)>,
+    /// Whether remapped files should be identified by their remapped name only, so that they
+    /// keep the same `StableSourceFileId` wherever the sources are (for relocatable incremental
+    /// caches).
+    relocatable: bool,
 }
 
 impl FilePathMapping {
     pub fn empty() -> FilePathMapping {
-        FilePathMapping { mapping: vec![] }
+        FilePathMapping { mapping: vec![], relocatable: false }
     }
 
     pub fn new(mapping: Vec<(PathBuf, PathBuf)>) -> FilePathMapping {
-        FilePathMapping { mapping }
+        FilePathMapping { mapping, relocatable: false }
+    }
+
+    pub fn relocatable(mapping: Vec<(PathBuf, PathBuf)>) -> FilePathMapping {
+        FilePathMapping { mapping, relocatable: true }
+    }
+
+    pub fn is_relocatable(&self) -> bool {
+        self.relocatable
     }
 
     /// Applies any path prefix substitution as defined by the mapping.
@@ -340,7 +340,9 @@ pub fn check_crate(tcx: TyCtxt<'_>) -> Result<(), ErrorReported> {
         }
     });
 
-    tcx.sess.time("item_bodies_checking", || tcx.typeck_item_bodies(LOCAL_CRATE));
+    tcx.sess.time("item_bodies_checking", || {
+        tcx.sess.with_deterministic_diagnostics(|| tcx.typeck_item_bodies(LOCAL_CRATE))
+    });
 
     check_unused::check_crate(tcx);
     check_for_entry_fn(tcx);
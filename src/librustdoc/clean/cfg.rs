@@ -3,6 +3,7 @@
 // FIXME: Once the portability lint RFC is implemented (see tracking issue #41619),
 // switch to use those structures instead.
 
+use std::cmp;
 use std::fmt::{self, Write};
 use std::mem;
 use std::ops;
@@ -117,6 +118,75 @@ impl Cfg {
         }
     }
 
+    /// Describes the configurations an item documented under several sets of cfgs is available
+    /// in, given the sets it was found in (`included[i]` tells whether it was in `sets[i]`).
+    ///
+    /// Whenever possible this picks a few of the cfgs which tell those sets apart from the
+    /// others, e.g. `feature = "serde"` or `not(windows)`, instead of listing whole sets.
+    pub fn from_cfg_sets(sets: &[Vec<Cfg>], included: &[bool]) -> Cfg {
+        let (inside, outside): (Vec<_>, Vec<_>) =
+            sets.iter().zip(included).partition(|(_, included)| **included);
+        let inside: Vec<&[Cfg]> = inside.into_iter().map(|(set, _)| &set[..]).collect();
+        let outside: Vec<&[Cfg]> = outside.into_iter().map(|(set, _)| &set[..]).collect();
+        if outside.is_empty() {
+            return Cfg::True;
+        }
+        if inside.is_empty() {
+            return Cfg::False;
+        }
+
+        // Picks cfgs until every set we're not in is ruled out.
+        fn rule_out<'a>(
+            candidates: impl Iterator<Item = &'a Cfg>,
+            outside: &[&[Cfg]],
+            rules_out: impl Fn(&Cfg, &[Cfg]) -> bool,
+        ) -> Option<Vec<Cfg>> {
+            // Try the cfgs ruling out the most sets first, so that we pick as few as possible.
+            let mut candidates: Vec<_> = candidates.collect();
+            candidates.sort_by_key(|cfg| {
+                cmp::Reverse(outside.iter().filter(|set| rules_out(cfg, set)).count())
+            });
+
+            let mut picked = vec![];
+            let mut remaining = outside.to_vec();
+            for cfg in candidates {
+                if remaining.iter().any(|set| rules_out(cfg, set)) {
+                    remaining.retain(|set| !rules_out(cfg, set));
+                    picked.push(cfg.clone());
+                }
+            }
+            if remaining.is_empty() {
+                Some(picked)
+            } else {
+                None
+            }
+        }
+
+        // Cfgs enabled in every set we're in, e.g. a feature gating the item.
+        let enabled = inside[0].iter().filter(|cfg| inside.iter().all(|set| set.contains(cfg)));
+        if let Some(picked) = rule_out(enabled, &outside, |cfg, set| !set.contains(cfg)) {
+            return picked.into_iter().fold(Cfg::True, |all, cfg| all & cfg);
+        }
+
+        // Cfgs disabled in every set we're in, e.g. `#[cfg(not(feature = "std"))]`.
+        let mut disabled = vec![];
+        for cfg in outside.iter().flat_map(|set| set.iter()) {
+            if !disabled.contains(&cfg) && !inside.iter().any(|set| set.contains(cfg)) {
+                disabled.push(cfg);
+            }
+        }
+        if let Some(picked) = rule_out(disabled.into_iter(), &outside, |cfg, set| set.contains(cfg))
+        {
+            return picked.into_iter().fold(Cfg::True, |all, cfg| all & !cfg);
+        }
+
+        // Otherwise, list the sets, leaving out what they all have in common.
+        inside.iter().fold(Cfg::False, |any, set| {
+            let specific = set.iter().filter(|cfg| !sets.iter().all(|other| other.contains(cfg)));
+            any | specific.cloned().fold(Cfg::True, |all, cfg| all & cfg)
+        })
+    }
+
     /// Whether the configuration consists of just `Cfg` or `Not`.
     fn is_simple(&self) -> bool {
         match *self {
@@ -134,7 +204,7 @@ impl Cfg {
     }
 
     /// Renders the configuration for human display, as a short HTML description.
-    pub(crate) fn render_short_html(&self) -> String {
+    crate fn render_short_html(&self) -> String {
         let mut msg = Html(self, true).to_string();
         if self.should_capitalize_first_letter() {
             if let Some(i) = msg.find(|c: char| c.is_ascii_alphanumeric()) {
@@ -145,7 +215,7 @@ impl Cfg {
     }
 
     /// Renders the configuration for long display, as a long HTML description.
-    pub(crate) fn render_long_html(&self) -> String {
+    crate fn render_long_html(&self) -> String {
         let on = if self.should_use_with_in_description() { "with" } else { "on" };
 
         let mut msg = format!("This is supported {} <strong>{}</strong>", on, Html(self, false));
@@ -434,3 +434,30 @@ fn test_render_long_html() {
         );
     })
 }
+
+#[test]
+fn test_from_cfg_sets() {
+    with_default_globals(|| {
+        let sets = vec![
+            vec![word_cfg("unix")],
+            vec![word_cfg("windows")],
+            vec![word_cfg("unix"), name_value_cfg("feature", "serde")],
+        ];
+        assert_eq!(Cfg::from_cfg_sets(&sets, &[true, true, true]), Cfg::True);
+        assert_eq!(Cfg::from_cfg_sets(&sets, &[true, false, true]), word_cfg("unix"));
+        assert_eq!(
+            Cfg::from_cfg_sets(&sets, &[false, false, true]),
+            name_value_cfg("feature", "serde")
+        );
+        assert_eq!(
+            Cfg::from_cfg_sets(&sets, &[true, true, false]),
+            !name_value_cfg("feature", "serde")
+        );
+
+        let sets = vec![vec![word_cfg("unix")], vec![word_cfg("windows")], vec![]];
+        assert_eq!(
+            Cfg::from_cfg_sets(&sets, &[true, true, false]),
+            word_cfg("unix") | word_cfg("windows")
+        );
+    })
+}
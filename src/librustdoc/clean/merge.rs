@@ -0,0 +1,519 @@
+//! Merging of the documentation of a crate built under several sets of cfgs.
+//!
+//! With `--cfg-set`, the crate is compiled and cleaned once per set, giving us one `Crate` for
+//! each of them. They are all merged into the first one: the items only found in some of the sets
+//! are added to it, and every item which isn't available in all the sets gets a `doc(cfg)` badge
+//! describing where it is (see `Cfg::from_cfg_sets`).
+//!
+//! Since every compilation numbers its `DefId`s on its own, the `DefId`s found in the added items
+//! have to be translated: local ones are matched by path with the items of the first crate (or
+//! given new indices), external ones by crate name.
+
+use std::sync::Arc;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def::Res;
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LOCAL_CRATE};
+use rustc_span::symbol::Symbol;
+
+use crate::clean::cfg::Cfg;
+use crate::clean::*;
+use crate::html::render::RenderInfo;
+
+/// The documentation of a crate compiled with one set of cfgs.
+pub struct CfgSetCrate {
+    pub krate: Crate,
+    pub renderinfo: RenderInfo,
+    /// The cfgs of the set, on top of the ones always passed to the compiler.
+    pub cfgs: Vec<Cfg>,
+}
+
+/// Parses a cfg as passed to `--cfg`, like `unix` or `feature="serde"`.
+pub fn cfg_from_spec(spec: &str) -> Cfg {
+    match spec.find('=') {
+        Some(eq) => Cfg::Cfg(
+            Symbol::intern(spec[..eq].trim()),
+            Some(Symbol::intern(spec[eq + 1..].trim().trim_matches('"'))),
+        ),
+        None => Cfg::Cfg(Symbol::intern(spec.trim()), None),
+    }
+}
+
+/// Merges the documentation of the crate under each set of cfgs into a single crate.
+pub fn merge(documented: Vec<CfgSetCrate>) -> (Crate, RenderInfo) {
+    let sets: Vec<Vec<Cfg>> = documented.iter().map(|doc| doc.cfgs.clone()).collect();
+    let mut documented = documented.into_iter();
+    let CfgSetCrate { mut krate, mut renderinfo, .. } =
+        documented.next().expect("no cfg set to document");
+
+    let mut presence = ItemPaths::default();
+    presence.collect(&krate, 0);
+    for (set, other) in documented.enumerate() {
+        presence.collect(&other.krate, set + 1);
+        merge_crate(&mut krate, &mut renderinfo, other);
+    }
+
+    if let Some(ref mut module) = krate.module {
+        add_cfgs(module, &mut vec![], &presence, &sets);
+    }
+    (krate, renderinfo)
+}
+
+/// Returns what identifies `item` among its siblings, across all the sets.
+fn item_key(item: &Item) -> String {
+    match item.inner {
+        ImplItem(ref i) | StrippedItem(box ImplItem(ref i)) => {
+            let negative = if i.polarity == Some(ImplPolarity::Negative) { "!" } else { "" };
+            match i.trait_ {
+                Some(ref trait_) => {
+                    format!("impl {}{:#} for {:#}", negative, trait_.print(), i.for_.print())
+                }
+                None => format!("impl {:#}", i.for_.print()),
+            }
+        }
+        ImportItem(Import::Simple(ref name, ref source)) => {
+            format!("use {} as {}", path_key(&source.path), name)
+        }
+        ImportItem(Import::Glob(ref source)) => format!("use {}::*", path_key(&source.path)),
+        _ => format!("{}.{}", item.type_(), item.name.as_deref().unwrap_or("")),
+    }
+}
+
+fn path_key(path: &Path) -> String {
+    path.segments.iter().map(|segment| &*segment.name).collect::<Vec<_>>().join("::")
+}
+
+/// The items merged into the first crate, with their children.
+fn children_mut(inner: &mut ItemEnum) -> Option<&mut Vec<Item>> {
+    match *inner {
+        StrippedItem(ref mut inner) => children_mut(inner),
+        ModuleItem(ref mut m) => Some(&mut m.items),
+        StructItem(ref mut s) => Some(&mut s.fields),
+        UnionItem(ref mut u) => Some(&mut u.fields),
+        EnumItem(ref mut e) => Some(&mut e.variants.raw),
+        TraitItem(ref mut t) => Some(&mut t.items),
+        ImplItem(ref mut i) => Some(&mut i.items),
+        VariantItem(Variant { kind: VariantKind::Struct(ref mut s) }) => Some(&mut s.fields),
+        _ => None,
+    }
+}
+
+fn children(inner: &ItemEnum) -> &[Item] {
+    match *inner {
+        StrippedItem(ref inner) => children(inner),
+        ModuleItem(ref m) => &m.items,
+        StructItem(ref s) => &s.fields,
+        UnionItem(ref u) => &u.fields,
+        EnumItem(ref e) => &e.variants.raw,
+        TraitItem(ref t) => &t.items,
+        ImplItem(ref i) => &i.items,
+        VariantItem(Variant { kind: VariantKind::Struct(ref s) }) => &s.fields,
+        _ => &[],
+    }
+}
+
+/// Records in which sets every item (identified by the keys leading to it) was found.
+#[derive(Default)]
+struct ItemPaths {
+    sets: FxHashMap<Vec<String>, FxHashSet<usize>>,
+    /// The `DefId`s of the items of the crate being collected.
+    def_ids: FxHashMap<Vec<String>, DefId>,
+}
+
+impl ItemPaths {
+    fn collect(&mut self, krate: &Crate, set: usize) {
+        self.def_ids.clear();
+        if let Some(ref module) = krate.module {
+            self.collect_children(&module.inner, &mut vec![], set);
+        }
+    }
+
+    fn collect_children(&mut self, inner: &ItemEnum, path: &mut Vec<String>, set: usize) {
+        for item in children(inner) {
+            path.push(item_key(item));
+            self.sets.entry(path.clone()).or_default().insert(set);
+            self.def_ids.insert(path.clone(), item.def_id);
+            self.collect_children(&item.inner, path, set);
+            path.pop();
+        }
+    }
+}
+
+fn merge_crate(krate: &mut Crate, renderinfo: &mut RenderInfo, other: CfgSetCrate) {
+    let CfgSetCrate { krate: other_krate, renderinfo: other_renderinfo, .. } = other;
+
+    // Match the local items by path.
+    let mut base_paths = ItemPaths::default();
+    base_paths.collect(krate, 0);
+    let mut other_paths = ItemPaths::default();
+    other_paths.collect(&other_krate, 0);
+    let mut local = FxHashMap::default();
+    for (path, &did) in &other_paths.def_ids {
+        if let Some(&known) = base_paths.def_ids.get(path) {
+            local.insert(did, known);
+        }
+    }
+
+    // Match the external crates by name.
+    let mut crates = FxHashMap::default();
+    let mut next_crate = krate.externs.iter().map(|(cnum, _)| cnum.as_usize()).max().unwrap_or(0);
+    for (cnum, ext) in other_krate.externs {
+        match krate.externs.iter().find(|(_, known)| known.name == ext.name) {
+            Some(&(known, _)) => {
+                crates.insert(cnum, known);
+            }
+            None => {
+                next_crate += 1;
+                crates.insert(cnum, CrateNum::new(next_crate));
+                krate.externs.push((CrateNum::new(next_crate), ext));
+            }
+        }
+    }
+
+    // New local `DefId`s go after every one the first crate uses.
+    let mut max_index = 0;
+    let mut find_max = DefIdFolder(|did: DefId| {
+        if did.is_local() {
+            max_index = max_index.max(did.index.as_usize());
+        }
+        did
+    });
+    if let Some(ref mut module) = krate.module {
+        find_max.item(module);
+    }
+    for did in renderinfo.access_levels.map.keys().chain(&renderinfo.inlined) {
+        if did.is_local() {
+            max_index = max_index.max(did.index.as_usize());
+        }
+    }
+    let mut fresh = FxHashMap::default();
+    let mut remap = DefIdFolder(|did: DefId| {
+        if !did.is_local() {
+            let krate = crates.get(&did.krate).copied().unwrap_or(did.krate);
+            return DefId { krate, index: did.index };
+        }
+        if let Some(&known) = local.get(&did) {
+            return known;
+        }
+        *fresh.entry(did).or_insert_with(|| {
+            max_index += 1;
+            DefId { krate: LOCAL_CRATE, index: DefIndex::from_usize(max_index) }
+        })
+    });
+
+    if let (Some(base), Some(other)) = (krate.module.as_mut(), other_krate.module) {
+        merge_children(&mut base.inner, other.inner, &mut remap);
+    }
+
+    {
+        let mut external_traits = krate.external_traits.borrow_mut();
+        for (did, mut trait_) in other_krate.external_traits.replace(Default::default()) {
+            let did = remap.def_id(did);
+            if !external_traits.contains_key(&did) {
+                remap.trait_(&mut trait_);
+                external_traits.insert(did, trait_);
+            }
+        }
+    }
+    krate.masked_crates.extend(
+        other_krate
+            .masked_crates
+            .into_iter()
+            .map(|cnum| crates.get(&cnum).copied().unwrap_or(cnum)),
+    );
+
+    let RenderInfo { inlined, external_paths, exact_paths, access_levels, .. } = other_renderinfo;
+    renderinfo.inlined.extend(inlined.into_iter().map(|did| remap.def_id(did)));
+    for (did, path) in external_paths {
+        renderinfo.external_paths.entry(remap.def_id(did)).or_insert(path);
+    }
+    for (did, path) in exact_paths {
+        renderinfo.exact_paths.entry(remap.def_id(did)).or_insert(path);
+    }
+    for (did, level) in access_levels.map {
+        renderinfo.access_levels.map.entry(remap.def_id(did)).or_insert(level);
+    }
+}
+
+/// Adds the children of `other` missing from `base`, recursively.
+fn merge_children<F: FnMut(DefId) -> DefId>(
+    base: &mut ItemEnum,
+    other: ItemEnum,
+    remap: &mut DefIdFolder<F>,
+) {
+    let other = match other {
+        StrippedItem(box other) => other,
+        other => other,
+    };
+    let other_children = match other {
+        ModuleItem(m) => m.items,
+        StructItem(s) => s.fields,
+        UnionItem(u) => u.fields,
+        EnumItem(e) => e.variants.raw,
+        TraitItem(t) => t.items,
+        ImplItem(i) => i.items,
+        VariantItem(Variant { kind: VariantKind::Struct(s) }) => s.fields,
+        _ => return,
+    };
+    let base_children = match children_mut(base) {
+        Some(children) => children,
+        None => return,
+    };
+
+    let mut known: FxHashMap<String, usize> = FxHashMap::default();
+    for (i, item) in base_children.iter().enumerate() {
+        known.entry(item_key(item)).or_insert(i);
+    }
+    for mut item in other_children {
+        match known.get(&item_key(&item)) {
+            Some(&i) => merge_children(&mut base_children[i].inner, item.inner, remap),
+            None => {
+                remap.item(&mut item);
+                base_children.push(item);
+            }
+        }
+    }
+}
+
+/// Badges the items which aren't available under every set of cfgs.
+fn add_cfgs(item: &mut Item, path: &mut Vec<String>, presence: &ItemPaths, sets: &[Vec<Cfg>]) {
+    if let Some(children) = children_mut(&mut item.inner) {
+        for child in children {
+            path.push(item_key(child));
+            if let Some(found) = presence.sets.get(&*path) {
+                let included: Vec<bool> = (0..sets.len()).map(|set| found.contains(&set)).collect();
+                let cfg = Cfg::from_cfg_sets(sets, &included);
+                if cfg != Cfg::True {
+                    child.attrs.cfg = Some(Arc::new(match child.attrs.cfg.take() {
+                        Some(own) => Cfg::clone(&own) & cfg,
+                        None => cfg,
+                    }));
+                }
+            }
+            add_cfgs(child, path, presence, sets);
+            path.pop();
+        }
+    }
+}
+
+/// Rewrites every `DefId` found in items and types.
+struct DefIdFolder<F>(F);
+
+impl<F: FnMut(DefId) -> DefId> DefIdFolder<F> {
+    fn def_id(&mut self, did: DefId) -> DefId {
+        (self.0)(did)
+    }
+
+    fn item(&mut self, item: &mut Item) {
+        item.def_id = self.def_id(item.def_id);
+        for (_, did, _) in &mut item.attrs.links {
+            if let Some(did) = did {
+                *did = self.def_id(*did);
+            }
+        }
+        if let Visibility::Restricted(ref mut did, ref mut path) = item.visibility {
+            *did = self.def_id(*did);
+            self.path(path);
+        }
+        self.inner(&mut item.inner);
+    }
+
+    fn items<'a>(&mut self, items: impl IntoIterator<Item = &'a mut Item>) {
+        for item in items {
+            self.item(item);
+        }
+    }
+
+    fn inner(&mut self, inner: &mut ItemEnum) {
+        match *inner {
+            StrippedItem(ref mut inner) => self.inner(inner),
+            ImportItem(Import::Simple(_, ref mut source))
+            | ImportItem(Import::Glob(ref mut source)) => {
+                self.path(&mut source.path);
+                if let Some(ref mut did) = source.did {
+                    *did = self.def_id(*did);
+                }
+            }
+            StructItem(ref mut s) => {
+                self.generics(&mut s.generics);
+                self.items(&mut s.fields);
+            }
+            UnionItem(ref mut u) => {
+                self.generics(&mut u.generics);
+                self.items(&mut u.fields);
+            }
+            EnumItem(ref mut e) => {
+                self.generics(&mut e.generics);
+                self.items(e.variants.iter_mut());
+            }
+            FunctionItem(ref mut f) | ForeignFunctionItem(ref mut f) => {
+                self.generics(&mut f.generics);
+                self.decl(&mut f.decl);
+                self.types(f.all_types.iter_mut().chain(&mut f.ret_types));
+            }
+            ModuleItem(ref mut m) => self.items(&mut m.items),
+            TypedefItem(ref mut t, _) => {
+                self.ty(&mut t.type_);
+                self.generics(&mut t.generics);
+                self.types(&mut t.item_type);
+            }
+            OpaqueTyItem(ref mut o, _) => {
+                self.bounds(&mut o.bounds);
+                self.generics(&mut o.generics);
+            }
+            StaticItem(ref mut s) | ForeignStaticItem(ref mut s) => self.ty(&mut s.type_),
+            ConstantItem(ref mut c) => self.ty(&mut c.type_),
+            TraitItem(ref mut t) => self.trait_(t),
+            TraitAliasItem(ref mut t) => {
+                self.generics(&mut t.generics);
+                self.bounds(&mut t.bounds);
+            }
+            ImplItem(ref mut i) => {
+                self.generics(&mut i.generics);
+                self.types(&mut i.trait_);
+                self.ty(&mut i.for_);
+                self.items(&mut i.items);
+                self.types(&mut i.blanket_impl);
+            }
+            TyMethodItem(ref mut m) => {
+                self.generics(&mut m.generics);
+                self.decl(&mut m.decl);
+                self.types(m.all_types.iter_mut().chain(&mut m.ret_types));
+            }
+            MethodItem(ref mut m) => {
+                self.generics(&mut m.generics);
+                self.decl(&mut m.decl);
+                self.types(m.all_types.iter_mut().chain(&mut m.ret_types));
+            }
+            StructFieldItem(ref mut ty) | AssocConstItem(ref mut ty, _) => self.ty(ty),
+            VariantItem(ref mut v) => match v.kind {
+                VariantKind::CLike => {}
+                VariantKind::Tuple(ref mut types) => self.types(types),
+                VariantKind::Struct(ref mut s) => self.items(&mut s.fields),
+            },
+            AssocTypeItem(ref mut bounds, ref mut ty) => {
+                self.bounds(bounds);
+                self.types(ty);
+            }
+            ExternCrateItem(..) | ForeignTypeItem | MacroItem(..) | ProcMacroItem(..)
+            | PrimitiveItem(..) | KeywordItem(..) => {}
+        }
+    }
+
+    fn trait_(&mut self, t: &mut Trait) {
+        self.items(&mut t.items);
+        self.generics(&mut t.generics);
+        self.bounds(&mut t.bounds);
+    }
+
+    fn types<'a>(&mut self, types: impl IntoIterator<Item = &'a mut Type>) {
+        for ty in types {
+            self.ty(ty);
+        }
+    }
+
+    fn ty(&mut self, ty: &mut Type) {
+        match *ty {
+            ResolvedPath { ref mut path, ref mut param_names, ref mut did, .. } => {
+                self.path(path);
+                if let Some(ref mut bounds) = *param_names {
+                    self.bounds(bounds);
+                }
+                *did = self.def_id(*did);
+            }
+            BareFunction(ref mut f) => {
+                self.params(&mut f.generic_params);
+                self.decl(&mut f.decl);
+            }
+            Tuple(ref mut types) => self.types(types),
+            Slice(ref mut ty) | Array(ref mut ty, _) | RawPointer(_, ref mut ty) => self.ty(ty),
+            BorrowedRef { ref mut type_, .. } => self.ty(type_),
+            QPath { ref mut self_type, ref mut trait_, .. } => {
+                self.ty(self_type);
+                self.ty(trait_);
+            }
+            ImplTrait(ref mut bounds) => self.bounds(bounds),
+            Generic(..) | Primitive(..) | Never | Infer => {}
+        }
+    }
+
+    fn path(&mut self, path: &mut Path) {
+        if let Res::Def(kind, did) = path.res {
+            path.res = Res::Def(kind, self.def_id(did));
+        }
+        for segment in &mut path.segments {
+            match segment.args {
+                GenericArgs::AngleBracketed { ref mut args, ref mut bindings } => {
+                    for arg in args {
+                        match *arg {
+                            GenericArg::Lifetime(_) => {}
+                            GenericArg::Type(ref mut ty) => self.ty(ty),
+                            GenericArg::Const(ref mut c) => self.ty(&mut c.type_),
+                        }
+                    }
+                    for binding in bindings {
+                        match binding.kind {
+                            TypeBindingKind::Equality { ref mut ty } => self.ty(ty),
+                            TypeBindingKind::Constraint { ref mut bounds } => self.bounds(bounds),
+                        }
+                    }
+                }
+                GenericArgs::Parenthesized { ref mut inputs, ref mut output } => {
+                    self.types(inputs.iter_mut().chain(output));
+                }
+            }
+        }
+    }
+
+    fn bounds(&mut self, bounds: &mut [GenericBound]) {
+        for bound in bounds {
+            if let GenericBound::TraitBound(ref mut poly, _) = *bound {
+                self.ty(&mut poly.trait_);
+                self.params(&mut poly.generic_params);
+            }
+        }
+    }
+
+    fn params(&mut self, params: &mut [GenericParamDef]) {
+        for param in params {
+            match param.kind {
+                GenericParamDefKind::Lifetime => {}
+                GenericParamDefKind::Type {
+                    ref mut did, ref mut bounds, ref mut default, ..
+                } => {
+                    *did = self.def_id(*did);
+                    self.bounds(bounds);
+                    self.types(default);
+                }
+                GenericParamDefKind::Const { ref mut did, ref mut ty } => {
+                    *did = self.def_id(*did);
+                    self.ty(ty);
+                }
+            }
+        }
+    }
+
+    fn generics(&mut self, generics: &mut Generics) {
+        self.params(&mut generics.params);
+        for predicate in &mut generics.where_predicates {
+            match *predicate {
+                WherePredicate::BoundPredicate { ref mut ty, ref mut bounds } => {
+                    self.ty(ty);
+                    self.bounds(bounds);
+                }
+                WherePredicate::RegionPredicate { ref mut bounds, .. } => self.bounds(bounds),
+                WherePredicate::EqPredicate { ref mut lhs, ref mut rhs } => {
+                    self.ty(lhs);
+                    self.ty(rhs);
+                }
+            }
+        }
+    }
+
+    fn decl(&mut self, decl: &mut FnDecl) {
+        self.types(decl.inputs.values.iter_mut().map(|arg| &mut arg.type_));
+        if let Return(ref mut ty) = decl.output {
+            self.ty(ty);
+        }
+    }
+}
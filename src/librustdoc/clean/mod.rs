@@ -5,6 +5,7 @@ mod auto_trait;
 mod blanket_impl;
 pub mod cfg;
 pub mod inline;
+pub mod merge;
 mod simplify;
 pub mod types;
 pub mod utils;
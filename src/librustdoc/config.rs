@@ -46,6 +46,9 @@ pub struct Options {
     pub extern_strs: Vec<String>,
     /// List of `cfg` flags to hand to the compiler. Always includes `rustdoc`.
     pub cfgs: Vec<String>,
+    /// Sets of `cfg` flags to document the crate under, one compilation each, on top of `cfgs`.
+    /// Items which aren't available under all of them are marked with the sets they appear in.
+    pub cfg_sets: Vec<Vec<String>>,
     /// Codegen options to hand to the compiler.
     pub codegen_options: CodegenOptions,
     /// Codegen options strings to hand to the compiler.
@@ -109,6 +112,9 @@ pub struct Options {
     /// Whether to run the `calculate-doc-coverage` pass, which counts the number of public items
     /// with and without documentation.
     pub show_coverage: bool,
+    /// If present, the path to write a JSON report of all intra-doc links that could not be
+    /// resolved or were ambiguous.
+    pub intra_doc_link_report: Option<PathBuf>,
 
     // Options that alter generated documentation pages
     /// Crate version to note on the sidebar of generated docs.
@@ -135,6 +141,7 @@ impl fmt::Debug for Options {
             .field("libs", &self.libs)
             .field("externs", &FmtExterns(&self.externs))
             .field("cfgs", &self.cfgs)
+            .field("cfg_sets", &self.cfg_sets)
             .field("codegen_options", &"...")
             .field("debugging_options", &"...")
             .field("target", &self.target)
@@ -152,6 +159,7 @@ impl fmt::Debug for Options {
             .field("manual_passes", &self.manual_passes)
             .field("display_warnings", &self.display_warnings)
             .field("show_coverage", &self.show_coverage)
+            .field("intra_doc_link_report", &self.intra_doc_link_report)
             .field("crate_version", &self.crate_version)
             .field("render_options", &self.render_options)
             .field("runtool", &self.runtool)
@@ -220,6 +228,12 @@ pub struct RenderOptions {
     pub generate_search_filter: bool,
     /// Option (disabled by default) to generate files used by RLS and some other tools.
     pub generate_redirect_pages: bool,
+    /// Option (disabled by default) to generate a full-text index of the documentation, searched
+    /// by the "In Documentation" tab of the search results.
+    pub generate_fulltext_index: bool,
+    /// Option (disabled by default) to check that every link of the generated documentation
+    /// leads somewhere, reporting broken ones through the `broken_doc_links` lint.
+    pub check_links: bool,
 }
 
 impl Options {
@@ -339,6 +353,17 @@ impl Options {
         let output =
             matches.opt_str("o").map(|s| PathBuf::from(&s)).unwrap_or_else(|| PathBuf::from("doc"));
         let cfgs = matches.opt_strs("cfg");
+        let mut cfg_sets = vec![];
+        for set in matches.opt_strs("cfg-set") {
+            let set: Vec<String> = set.split(',').map(|cfg| cfg.trim().to_string()).collect();
+            if set.iter().any(|cfg| cfg.is_empty() || cfg.starts_with('=')) {
+                diag.struct_err("`--cfg-set` expects a comma-separated list of cfgs")
+                    .note("for example: `--cfg-set unix,feature=\"serde\"`")
+                    .emit();
+                return Err(1);
+            }
+            cfg_sets.push(set);
+        }
 
         let extension_css = matches.opt_str("e").map(|s| PathBuf::from(&s));
 
@@ -486,6 +511,12 @@ impl Options {
         let generate_search_filter = !matches.opt_present("disable-per-crate-search");
         let persist_doctests = matches.opt_str("persist-doctests").map(PathBuf::from);
         let generate_redirect_pages = matches.opt_present("generate-redirect-pages");
+        let generate_fulltext_index = matches.opt_present("generate-fulltext-index");
+        let check_links = matches.opt_present("check-links");
+        if check_links && !cfg_sets.is_empty() {
+            diag.struct_err("`--check-links` cannot be used with `--cfg-set`").emit();
+            return Err(1);
+        }
         let test_builder = matches.opt_str("test-builder").map(PathBuf::from);
         let codegen_options_strs = matches.opt_strs("C");
         let debugging_options_strs = matches.opt_strs("Z");
@@ -496,6 +527,7 @@ impl Options {
         let enable_per_target_ignores = matches.opt_present("enable-per-target-ignores");
         let document_private = matches.opt_present("document-private-items");
         let document_hidden = matches.opt_present("document-hidden-items");
+        let intra_doc_link_report = matches.opt_str("intra-doc-link-report").map(PathBuf::from);
 
         let (lint_opts, describe_lints, lint_cap) = get_cmd_lint_options(matches, error_format);
 
@@ -509,6 +541,7 @@ impl Options {
             externs,
             extern_strs,
             cfgs,
+            cfg_sets,
             codegen_options,
             codegen_options_strs,
             debugging_options,
@@ -527,6 +560,7 @@ impl Options {
             manual_passes,
             display_warnings,
             show_coverage,
+            intra_doc_link_report,
             crate_version,
             persist_doctests,
             runtool,
@@ -552,6 +586,8 @@ impl Options {
                 markdown_playground_url,
                 generate_search_filter,
                 generate_redirect_pages,
+                generate_fulltext_index,
+                check_links,
             },
         })
     }
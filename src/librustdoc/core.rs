@@ -1,3 +1,4 @@
+use rustc::lint::LevelSource;
 use rustc::middle::cstore::CrateStore;
 use rustc::middle::privacy::AccessLevels;
 use rustc::session::config::ErrorOutputType;
@@ -9,7 +10,7 @@ use rustc_driver::abort_on_err;
 use rustc_feature::UnstableFeatures;
 use rustc_hir::def::Namespace::TypeNS;
 use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LOCAL_CRATE};
-use rustc_hir::HirId;
+use rustc_hir::{HirId, CRATE_HIR_ID};
 use rustc_interface::interface;
 use rustc_resolve as resolve;
 use rustc_session::lint;
@@ -25,12 +26,14 @@ use rustc_span::DUMMY_SP;
 use rustc_data_structures::sync::{self, Lrc};
 use std::cell::RefCell;
 use std::mem;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use crate::clean;
 use crate::clean::{AttributesExt, MAX_DEF_ID};
 use crate::config::{Options as RustdocOptions, RenderOptions};
-use crate::html::render::RenderInfo;
+use crate::fold::DocFolder;
+use crate::html::render::{DocLinkLint, RenderInfo};
 
 use crate::passes::{self, Condition::*, ConditionalPass};
 
@@ -65,6 +68,8 @@ pub struct DocContext<'tcx> {
     // FIXME(eddyb) make this a `ty::TraitRef<'tcx>` set.
     pub generated_synthetics: RefCell<FxHashSet<(Ty<'tcx>, DefId)>>,
     pub auto_traits: Vec<DefId>,
+    /// Where to write the report of broken intra-doc links, if requested.
+    pub intra_doc_link_report: Option<PathBuf>,
 }
 
 impl<'tcx> DocContext<'tcx> {
@@ -227,6 +232,7 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
         document_hidden,
         mut manual_passes,
         display_warnings,
+        intra_doc_link_report,
         render_options,
         ..
     } = options;
@@ -245,6 +251,7 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
     let input = Input::File(input);
 
     let intra_link_resolution_failure_name = lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE.name;
+    let broken_doc_links_name = lint::builtin::BROKEN_DOC_LINKS.name;
     let warnings_lint_name = lint::builtin::WARNINGS.name;
     let missing_docs = rustc_lint::builtin::MISSING_DOCS.name;
     let missing_doc_example = rustc_lint::builtin::MISSING_DOC_CODE_EXAMPLES.name;
@@ -255,6 +262,7 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
     let mut whitelisted_lints = vec![
         warnings_lint_name.to_owned(),
         intra_link_resolution_failure_name.to_owned(),
+        broken_doc_links_name.to_owned(),
         missing_docs.to_owned(),
         missing_doc_example.to_owned(),
         private_doc_tests.to_owned(),
@@ -270,7 +278,10 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
 
     let lint_opts = lints()
         .filter_map(|lint| {
-            if lint.name == warnings_lint_name || lint.name == intra_link_resolution_failure_name {
+            if lint.name == warnings_lint_name
+                || lint.name == intra_link_resolution_failure_name
+                || lint.name == broken_doc_links_name
+            {
                 None
             } else {
                 Some((lint.name_lower(), lint::Allow))
@@ -405,6 +416,7 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
                         .cloned()
                         .filter(|trait_def_id| tcx.trait_is_auto(*trait_def_id))
                         .collect(),
+                    intra_doc_link_report,
                 };
                 debug!("crate: {:?}", tcx.hir().krate());
 
@@ -495,12 +507,41 @@ pub fn run_core(options: RustdocOptions) -> (clean::Crate, RenderInfo, RenderOpt
 
                 ctxt.sess().abort_if_errors();
 
+                if render_options.check_links {
+                    let mut collector = DocLinkLevels { cx: &ctxt, levels: Default::default() };
+                    krate = collector.fold_crate(krate);
+                    let crate_level =
+                        tcx.lint_level_at_node(lint::builtin::BROKEN_DOC_LINKS, CRATE_HIR_ID);
+                    ctxt.renderinfo.borrow_mut().doc_link_lint = Some(DocLinkLint {
+                        source_map: compiler.source_map().clone(),
+                        levels: collector.levels,
+                        crate_level,
+                    });
+                }
+
                 (krate, ctxt.renderinfo.into_inner(), render_options)
             })
         })
     })
 }
 
+/// Collects the level of `broken_doc_links` for every local item, for `--check-links` to use
+/// once the compiler session has ended.
+struct DocLinkLevels<'a, 'tcx> {
+    cx: &'a DocContext<'tcx>,
+    levels: FxHashMap<DefId, LevelSource>,
+}
+
+impl DocFolder for DocLinkLevels<'_, '_> {
+    fn fold_item(&mut self, item: clean::Item) -> Option<clean::Item> {
+        if let Some(hir_id) = self.cx.as_local_hir_id(item.def_id) {
+            let level = self.cx.tcx.lint_level_at_node(lint::builtin::BROKEN_DOC_LINKS, hir_id);
+            self.levels.insert(item.def_id, level);
+        }
+        self.fold_item_recur(item)
+    }
+}
+
 /// `DefId` or parameter index (`ty::ParamTy.index`) of a synthetic type parameter
 /// for `impl Trait` in argument position.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
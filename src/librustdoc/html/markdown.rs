@@ -878,6 +878,27 @@ pub fn plain_summary_line(md: &str) -> String {
     s
 }
 
+/// Returns the prose of a whole markdown document as plain text, leaving out code blocks.
+///
+/// This is what the full-text search index is built from.
+pub fn plain_text(md: &str) -> String {
+    let mut s = String::with_capacity(md.len());
+    let mut in_code_block = false;
+    for event in Parser::new(md) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Text(ref text) | Event::Code(ref text) if !in_code_block => {
+                s.push_str(text);
+                s.push(' ');
+            }
+            Event::SoftBreak | Event::HardBreak => s.push(' '),
+            _ => {}
+        }
+    }
+    s
+}
+
 pub fn markdown_links(md: &str) -> Vec<(String, Option<Range<usize>>)> {
     if md.is_empty() {
         return vec![];
@@ -77,9 +77,13 @@ use crate::html::{highlight, layout, static_files};
 mod tests;
 
 mod cache;
+mod check_links;
+mod fulltext;
 
 use cache::Cache;
 crate use cache::ExternalLocation::{self, *};
+use check_links::DocLinks;
+crate use check_links::DocLinkLint;
 
 /// A pair of name and its optional document.
 pub type NameDoc = (String, Option<String>);
@@ -157,6 +161,8 @@ struct Context {
     pub render_redirect_pages: bool,
     /// The map used to ensure all generated 'id=' attributes are unique.
     id_map: Rc<RefCell<IdMap>>,
+    /// The links found in doc comments, recorded when `--check-links` is passed.
+    doc_links: Option<Rc<RefCell<DocLinks>>>,
     pub shared: Arc<SharedContext>,
     pub cache: Arc<Cache>,
 }
@@ -270,6 +276,8 @@ pub struct RenderInfo {
     pub deref_trait_did: Option<DefId>,
     pub deref_mut_trait_did: Option<DefId>,
     pub owned_box_did: Option<DefId>,
+    /// Set when `--check-links` is passed.
+    pub doc_link_lint: Option<DocLinkLint>,
 }
 
 // Helper structs for rendering items/sidebars and carrying along contextual
@@ -286,6 +294,7 @@ struct IndexItem {
     parent: Option<DefId>,
     parent_idx: Option<usize>,
     search_type: Option<IndexItemFunctionType>,
+    signature: Option<IndexItemSignature>,
 }
 
 impl Serialize for IndexItem {
@@ -295,8 +304,22 @@ impl Serialize for IndexItem {
     {
         assert_eq!(self.parent.is_some(), self.parent_idx.is_some());
 
-        (self.ty, &self.name, &self.path, &self.desc, self.parent_idx, &self.search_type)
-            .serialize(serializer)
+        // Most items aren't functions, so don't make them pay for a trailing `null`.
+        if let Some(ref signature) = self.signature {
+            (
+                self.ty,
+                &self.name,
+                &self.path,
+                &self.desc,
+                self.parent_idx,
+                &self.search_type,
+                signature,
+            )
+                .serialize(serializer)
+        } else {
+            (self.ty, &self.name, &self.path, &self.desc, self.parent_idx, &self.search_type)
+                .serialize(serializer)
+        }
     }
 }
 
@@ -359,6 +382,50 @@ impl Serialize for IndexItemFunctionType {
     }
 }
 
+/// A type in a function signature, keeping its full generic structure. Used by the type-based
+/// search, e.g. to tell `Vec<T> -> Option<T>` apart from `Vec<T> -> Option<usize>`.
+#[derive(Debug)]
+enum SignatureType {
+    /// A type or trait with its generic arguments, e.g. `Vec<T>` or `Iterator<Item = T>`.
+    Named(String, Vec<SignatureType>),
+    /// A generic parameter of the function, indexing into `IndexItemSignature::params`.
+    Param(usize),
+}
+
+impl Serialize for SignatureType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            SignatureType::Named(ref name, ref args) if args.is_empty() => {
+                name.serialize(serializer)
+            }
+            SignatureType::Named(ref name, ref args) => (name, args).serialize(serializer),
+            SignatureType::Param(idx) => idx.serialize(serializer),
+        }
+    }
+}
+
+/// Signature of functions/methods used by the type-based search.
+#[derive(Debug)]
+struct IndexItemSignature {
+    inputs: Vec<SignatureType>,
+    output: Option<SignatureType>,
+    /// The trait bounds of each generic parameter of the function, including the anonymous ones
+    /// introduced by `impl Trait` arguments.
+    params: Vec<Vec<SignatureType>>,
+}
+
+impl Serialize for IndexItemSignature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        (&self.inputs, &self.output, &self.params).serialize(serializer)
+    }
+}
+
 thread_local!(static CACHE_KEY: RefCell<Arc<Cache>> = Default::default());
 thread_local!(pub static CURRENT_DEPTH: Cell<usize> = Cell::new(0));
 
@@ -390,7 +457,7 @@ pub fn initial_ids() -> Vec<String> {
 pub fn run(
     mut krate: clean::Crate,
     options: RenderOptions,
-    renderinfo: RenderInfo,
+    mut renderinfo: RenderInfo,
     diag: &rustc_errors::Handler,
     edition: Edition,
 ) -> Result<(), Error> {
@@ -409,6 +476,7 @@ pub fn run(
         static_root_path,
         generate_search_filter,
         generate_redirect_pages,
+        generate_fulltext_index,
         ..
     } = options;
 
@@ -482,18 +550,27 @@ pub fn run(
         playground,
     };
 
+    let doc_links =
+        renderinfo.doc_link_lint.take().map(|lint| Rc::new(RefCell::new(DocLinks::new(lint))));
+
     let dst = output;
     scx.ensure_dir(&dst)?;
     krate = sources::render(&dst, &mut scx, krate)?;
-    let (new_crate, index, cache) =
-        Cache::from_krate(renderinfo, &extern_html_root_urls, &dst, krate);
+    let (new_crate, index, fulltext_index, cache) = Cache::from_krate(
+        renderinfo,
+        &extern_html_root_urls,
+        &dst,
+        generate_fulltext_index,
+        krate,
+    );
     krate = new_crate;
     let cache = Arc::new(cache);
     let mut cx = Context {
         current: Vec::new(),
-        dst,
+        dst: dst.clone(),
         render_redirect_pages: false,
         id_map: Rc::new(RefCell::new(id_map)),
+        doc_links: doc_links.clone(),
         shared: Arc::new(scx),
         cache: cache.clone(),
     };
@@ -505,10 +582,11 @@ pub fn run(
 
     // Write shared runs within a flock; disable thread dispatching of IO temporarily.
     Arc::get_mut(&mut cx.shared).unwrap().fs.set_sync_only(true);
-    write_shared(&cx, &krate, index, &md_opts, diag)?;
+    write_shared(&cx, &krate, index, fulltext_index, &md_opts, diag)?;
     Arc::get_mut(&mut cx.shared).unwrap().fs.set_sync_only(false);
 
     // And finally render the whole crate's documentation
+    let crate_name = krate.name.clone();
     let ret = cx.krate(krate);
     let nb_errors = Arc::get_mut(&mut errors).map_or_else(|| 0, |errors| errors.write_errors(diag));
     if ret.is_err() {
@@ -516,6 +594,9 @@ pub fn run(
     } else if nb_errors > 0 {
         Err(Error::new(io::Error::new(io::ErrorKind::Other, "I/O error"), ""))
     } else {
+        if let Some(doc_links) = doc_links {
+            check_links::check(&dst, &crate_name, &cache, &doc_links.borrow(), diag);
+        }
         Ok(())
     }
 }
@@ -524,6 +605,7 @@ fn write_shared(
     cx: &Context,
     krate: &clean::Crate,
     search_index: String,
+    fulltext_index: Option<String>,
     options: &RenderOptions,
     diag: &rustc_errors::Handler,
 ) -> Result<(), Error> {
@@ -871,6 +953,15 @@ themePicker.onblur = handleThemeButtonsBlur;
         v.push_str("\naddSearchOptions(searchIndex);initSearch(searchIndex);");
         cx.shared.fs.write(&dst, &v)?;
     }
+
+    // The full-text index is kept in its own file per crate so the search page only has to
+    // download it when the user actually searches something.
+    if let Some(fulltext_index) = fulltext_index {
+        let dst = cx.dst.join("search-fulltext");
+        cx.shared.ensure_dir(&dst)?;
+        let dst = dst.join(&format!("{}{}.js", krate.name, cx.shared.resource_suffix));
+        cx.shared.fs.write(&dst, &fulltext_index)?;
+    }
     if options.enable_index_page {
         if let Some(index_page) = options.index_page.clone() {
             let mut md_opts = options.clone();
@@ -1457,6 +1548,7 @@ impl Context {
 
             info!("Recursing into {}", self.dst.display());
 
+            self.set_current_page(self.dst.join("index.html"));
             let buf = self.render_item(&item, false);
             // buf will be empty if the module is stripped and there is no redirect for it
             if !buf.is_empty() {
@@ -1488,6 +1580,8 @@ impl Context {
             self.dst = prev;
             self.current.pop().unwrap();
         } else if item.name.is_some() {
+            let file_name = item_path(item.type_(), item.name.as_ref().unwrap());
+            self.set_current_page(self.dst.join(file_name));
             let buf = self.render_item(&item, true);
             // buf will be empty if the item is stripped and there is no redirect for it
             if !buf.is_empty() {
@@ -1522,6 +1616,20 @@ impl Context {
         Ok(())
     }
 
+    /// Notes the page about to be rendered, for `--check-links`.
+    fn set_current_page(&self, page: PathBuf) {
+        if let Some(ref doc_links) = self.doc_links {
+            doc_links.borrow_mut().set_current_page(page);
+        }
+    }
+
+    /// Remembers where the links in the documentation of `item` come from, for `--check-links`.
+    fn record_doc_links(&self, item: &clean::Item) {
+        if let Some(ref doc_links) = self.doc_links {
+            doc_links.borrow_mut().record(item);
+        }
+    }
+
     fn build_sidebar_items(&self, m: &clean::Module) -> BTreeMap<String, Vec<NameDoc>> {
         // BTreeMap instead of HashMap to get a sorted output
         let mut map: BTreeMap<_, Vec<_>> = BTreeMap::new();
@@ -1835,6 +1943,7 @@ fn document_short(
         } else {
             plain_summary_line(Some(s))
         };
+        cx.record_doc_links(item);
         render_markdown(w, cx, &markdown, item.links(), prefix, is_hidden);
     } else if !prefix.is_empty() {
         write!(
@@ -1849,6 +1958,7 @@ fn document_short(
 fn document_full(w: &mut Buffer, item: &clean::Item, cx: &Context, prefix: &str, is_hidden: bool) {
     if let Some(s) = cx.shared.maybe_collapsed_doc_value(item) {
         debug!("Doc block: =====\n{}\n=====", s);
+        cx.record_doc_links(item);
         render_markdown(w, cx, &*s, item.links(), prefix, is_hidden);
     } else if !prefix.is_empty() {
         write!(
@@ -2093,6 +2203,7 @@ fn item_module(w: &mut Buffer, cx: &Context, item: &clean::Item, items: &[clean:
                 let add = if stab.is_some() { " " } else { "" };
 
                 let doc_value = myitem.doc_value().unwrap_or("");
+                cx.record_doc_links(myitem);
                 write!(
                     w,
                     "\
@@ -3651,6 +3762,7 @@ fn render_impl(
         }
         write!(w, "</h3>");
         if let Some(ref dox) = cx.shared.maybe_collapsed_doc_value(&i.impl_item) {
+            cx.record_doc_links(&i.impl_item);
             let mut ids = cx.id_map.borrow_mut();
             write!(
                 w,
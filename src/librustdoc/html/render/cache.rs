@@ -2,6 +2,7 @@ use crate::clean::{self, AttributesExt, GetDefId};
 use crate::fold::DocFolder;
 use rustc::middle::privacy::AccessLevels;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir as hir;
 use rustc_hir::def_id::{CrateNum, DefId, CRATE_DEF_INDEX};
 use rustc_span::source_map::FileName;
 use rustc_span::symbol::sym;
@@ -11,8 +12,10 @@ use std::path::{Path, PathBuf};
 
 use serde::Serialize;
 
+use super::fulltext::FullTextIndex;
 use super::{plain_summary_line, shorten, Impl, IndexItem, IndexItemFunctionType, ItemType};
-use super::{RenderInfo, Type};
+use super::{IndexItemSignature, RenderInfo, SignatureType, Type};
+use crate::html::markdown::plain_text;
 
 /// Indicates where an external crate can be found.
 pub enum ExternalLocation {
@@ -95,6 +98,8 @@ crate struct Cache {
     stack: Vec<String>,
     parent_stack: Vec<DefId>,
     parent_is_trait_impl: bool,
+    /// The type `Self` refers to in the methods being crawled, if they're in an impl.
+    impl_self_type: Option<clean::Type>,
     search_index: Vec<IndexItem>,
     stripped_mod: bool,
     pub deref_trait_did: Option<DefId>,
@@ -121,6 +126,10 @@ crate struct Cache {
     /// Aliases added through `#[doc(alias = "...")]`. Since a few items can have the same alias,
     /// we need the alias element to have an array of items.
     pub(super) aliases: FxHashMap<String, Vec<IndexItem>>,
+
+    /// The full-text index of the documentation of every item in `search_index`, if
+    /// `--generate-fulltext-index` was passed.
+    fulltext_index: Option<FullTextIndex>,
 }
 
 impl Cache {
@@ -128,8 +137,9 @@ impl Cache {
         renderinfo: RenderInfo,
         extern_html_root_urls: &BTreeMap<String, String>,
         dst: &Path,
+        generate_fulltext_index: bool,
         mut krate: clean::Crate,
-    ) -> (clean::Crate, String, Cache) {
+    ) -> (clean::Crate, String, Option<String>, Cache) {
         // Crawl the crate to build various caches used for the output
         let RenderInfo {
             inlined: _,
@@ -139,6 +149,7 @@ impl Cache {
             deref_trait_did,
             deref_mut_trait_did,
             owned_box_did,
+            doc_link_lint: _,
         } = renderinfo;
 
         let external_paths =
@@ -154,6 +165,7 @@ impl Cache {
             parent_stack: Vec::new(),
             search_index: Vec::new(),
             parent_is_trait_impl: false,
+            impl_self_type: None,
             extern_locations: Default::default(),
             primitive_locations: Default::default(),
             stripped_mod: false,
@@ -167,6 +179,7 @@ impl Cache {
             owned_box_did,
             masked_crates: mem::take(&mut krate.masked_crates),
             aliases: Default::default(),
+            fulltext_index: if generate_fulltext_index { Some(Default::default()) } else { None },
         };
 
         // Cache where all our extern crates are located
@@ -212,9 +225,9 @@ impl Cache {
         }
 
         // Build our search index
-        let index = build_index(&krate, &mut cache);
+        let (index, fulltext_index) = build_index(&krate, &mut cache);
 
-        (krate, index, cache)
+        (krate, index, fulltext_index, cache)
     }
 }
 
@@ -314,6 +327,10 @@ impl DocFolder for Cache {
                     // which should not be indexed. The crate-item itself is
                     // inserted later on when serializing the search-index.
                     if item.def_id.index != CRATE_DEF_INDEX {
+                        if let Some(ref mut fulltext_index) = self.fulltext_index {
+                            let doc = plain_text(item.doc_value().unwrap_or(""));
+                            fulltext_index.add_item(self.search_index.len(), &doc);
+                        }
                         self.search_index.push(IndexItem {
                             ty: item.type_(),
                             name: s.to_string(),
@@ -322,6 +339,7 @@ impl DocFolder for Cache {
                             parent,
                             parent_idx: None,
                             search_type: get_index_search_type(&item),
+                            signature: get_index_signature(&item, self.impl_self_type.as_ref()),
                         });
                     }
                 }
@@ -385,6 +403,7 @@ impl DocFolder for Cache {
 
         // Maintain the parent stack
         let orig_parent_is_trait_impl = self.parent_is_trait_impl;
+        let orig_impl_self_type = self.impl_self_type.take();
         let parent_pushed = match item.inner {
             clean::TraitItem(..)
             | clean::EnumItem(..)
@@ -398,6 +417,7 @@ impl DocFolder for Cache {
             }
             clean::ImplItem(ref i) => {
                 self.parent_is_trait_impl = i.trait_.is_some();
+                self.impl_self_type = Some(i.for_.clone());
                 match i.for_ {
                     clean::ResolvedPath { did, .. } => {
                         self.parent_stack.push(did);
@@ -480,6 +500,7 @@ impl DocFolder for Cache {
         }
         self.stripped_mod = orig_stripped_mod;
         self.parent_is_trait_impl = orig_parent_is_trait_impl;
+        self.impl_self_type = orig_impl_self_type;
         ret
     }
 }
@@ -512,6 +533,7 @@ impl Cache {
                     parent: None,
                     parent_idx: None,
                     search_type: get_index_search_type(&item),
+                    signature: None,
                 });
             }
         }
@@ -557,18 +579,29 @@ fn extern_location(
         .unwrap_or(Unknown) // Well, at least we tried.
 }
 
-/// Builds the search index from the collected metadata
-fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
+/// Builds the search index from the collected metadata, along with the full-text index if one
+/// was requested.
+fn build_index(krate: &clean::Crate, cache: &mut Cache) -> (String, Option<String>) {
     let mut defid_to_pathid = FxHashMap::default();
     let mut crate_items = Vec::with_capacity(cache.search_index.len());
     let mut crate_paths = vec![];
 
-    let Cache { ref mut search_index, ref orphan_impl_items, ref paths, .. } = *cache;
+    let Cache {
+        ref mut search_index,
+        ref orphan_impl_items,
+        ref paths,
+        ref mut fulltext_index,
+        ..
+    } = *cache;
 
     // Attach all orphan items to the type's definition if the type
     // has since been learned.
     for &(did, ref item) in orphan_impl_items {
         if let Some(&(ref fqp, _)) = paths.get(&did) {
+            if let Some(ref mut fulltext_index) = fulltext_index {
+                let doc = plain_text(item.doc_value().unwrap_or(""));
+                fulltext_index.add_item(search_index.len(), &doc);
+            }
             search_index.push(IndexItem {
                 ty: item.type_(),
                 name: item.name.clone().unwrap(),
@@ -577,6 +610,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
                 parent: Some(did),
                 parent_idx: None,
                 search_type: get_index_search_type(&item),
+                signature: get_index_signature(&item, None),
             });
         }
     }
@@ -616,6 +650,9 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
         .map(|module| shorten(plain_summary_line(module.doc_value())))
         .unwrap_or(String::new());
 
+    // Only advertise the full-text index to the search page if there's something in it.
+    let fulltext_index = fulltext_index.take().filter(|index| !index.is_empty());
+
     #[derive(Serialize)]
     struct CrateData<'a> {
         doc: String,
@@ -623,19 +660,23 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
         items: Vec<&'a IndexItem>,
         #[serde(rename = "p")]
         paths: Vec<(ItemType, String)>,
+        #[serde(rename = "f", skip_serializing_if = "Option::is_none")]
+        has_fulltext_index: Option<u8>,
     }
 
     // Collect the index into a string
-    format!(
+    let index = format!(
         r#"searchIndex["{}"] = {};"#,
         krate.name,
         serde_json::to_string(&CrateData {
             doc: crate_doc,
             items: crate_items,
             paths: crate_paths,
+            has_fulltext_index: fulltext_index.as_ref().map(|_| 1),
         })
         .expect("failed serde conversion")
-    )
+    );
+    (index, fulltext_index.map(|index| index.to_js(&krate.name)))
 }
 
 fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
@@ -694,3 +735,149 @@ fn get_generics(clean_type: &clean::Type) -> Option<Vec<String>> {
         if r.is_empty() { None } else { Some(r) }
     })
 }
+
+/// Builds the signature used by the type-based search. Unlike `get_index_search_type`, this
+/// keeps the whole generic structure of the types and the bounds of the generic parameters.
+fn get_index_signature(
+    item: &clean::Item,
+    self_type: Option<&clean::Type>,
+) -> Option<IndexItemSignature> {
+    let (decl, generics) = match item.inner {
+        clean::FunctionItem(ref f) => (&f.decl, &f.generics),
+        clean::MethodItem(ref m) => (&m.decl, &m.generics),
+        clean::TyMethodItem(ref m) => (&m.decl, &m.generics),
+        _ => return None,
+    };
+
+    let mut builder =
+        SignatureBuilder { generics, self_type, param_ids: FxHashMap::default(), params: vec![] };
+    let inputs = decl.inputs.values.iter().filter_map(|arg| builder.convert(&arg.type_)).collect();
+    let output = match decl.output {
+        clean::FnRetTy::Return(ref ty) => builder.convert(ty),
+        clean::FnRetTy::DefaultReturn => None,
+    };
+
+    Some(IndexItemSignature { inputs, output, params: builder.params })
+}
+
+struct SignatureBuilder<'a> {
+    generics: &'a clean::Generics,
+    self_type: Option<&'a clean::Type>,
+    /// Maps the names of the generic parameters met so far to their index in `params`.
+    param_ids: FxHashMap<String, usize>,
+    params: Vec<Vec<SignatureType>>,
+}
+
+impl SignatureBuilder<'_> {
+    /// Converts a type of the signature. References and raw pointers are transparent, and types
+    /// which can't be searched for (like the unit type or function pointers) are dropped.
+    fn convert(&mut self, ty: &clean::Type) -> Option<SignatureType> {
+        match *ty {
+            clean::ResolvedPath { ref path, .. } => {
+                let segment = path.segments.last()?;
+                let args = self.convert_args(&segment.args);
+                Some(SignatureType::Named(segment.name.to_ascii_lowercase(), args))
+            }
+            clean::Generic(ref name) if name == "Self" && self.self_type.is_some() => {
+                // Methods of an impl are searched as if `Self` was spelled out.
+                let self_type = self.self_type.take();
+                let ret = self.convert(self_type.unwrap());
+                self.self_type = self_type;
+                ret
+            }
+            clean::Generic(ref name) => Some(SignatureType::Param(self.param(name))),
+            clean::Primitive(p) => Some(SignatureType::Named(p.as_str().to_string(), vec![])),
+            clean::BorrowedRef { ref type_, .. } | clean::RawPointer(_, ref type_) => {
+                self.convert(type_)
+            }
+            clean::Slice(ref type_) | clean::Array(ref type_, _) => {
+                let name = if let clean::Slice(_) = *ty { "slice" } else { "array" };
+                let args = self.convert(type_).into_iter().collect();
+                Some(SignatureType::Named(name.to_string(), args))
+            }
+            clean::Tuple(ref types) if !types.is_empty() => {
+                let types = types.iter().filter_map(|ty| self.convert(ty)).collect();
+                Some(SignatureType::Named("tuple".to_string(), types))
+            }
+            clean::ImplTrait(ref bounds) => {
+                // Arguments of type `impl Trait` are anonymous generic parameters.
+                let idx = self.params.len();
+                self.params.push(vec![]);
+                self.params[idx] = self.convert_bounds(bounds);
+                Some(SignatureType::Param(idx))
+            }
+            clean::QPath { ref name, ref self_type, .. } => {
+                // Associated types like `T::Item` can be anything: treat them as generic
+                // parameters of their own.
+                let name = match **self_type {
+                    clean::Generic(ref param) => format!("{}::{}", param, name),
+                    _ => format!("_::{}", name),
+                };
+                Some(SignatureType::Param(self.param(&name)))
+            }
+            clean::Tuple(_) | clean::BareFunction(_) | clean::Never | clean::Infer => None,
+        }
+    }
+
+    fn convert_args(&mut self, args: &clean::GenericArgs) -> Vec<SignatureType> {
+        match *args {
+            clean::GenericArgs::AngleBracketed { ref args, ref bindings } => {
+                let types = args.iter().filter_map(|arg| match *arg {
+                    clean::GenericArg::Type(ref ty) => Some(ty),
+                    clean::GenericArg::Lifetime(_) | clean::GenericArg::Const(_) => None,
+                });
+                // `Iterator<Item = T>` is recorded as `Iterator<T>`.
+                let bindings = bindings.iter().filter_map(|binding| match binding.kind {
+                    clean::TypeBindingKind::Equality { ref ty } => Some(ty),
+                    clean::TypeBindingKind::Constraint { .. } => None,
+                });
+                types.chain(bindings).filter_map(|ty| self.convert(ty)).collect()
+            }
+            clean::GenericArgs::Parenthesized { ref inputs, ref output } => {
+                inputs.iter().chain(output).filter_map(|ty| self.convert(ty)).collect()
+            }
+        }
+    }
+
+    fn convert_bounds(&mut self, bounds: &[clean::GenericBound]) -> Vec<SignatureType> {
+        bounds
+            .iter()
+            .filter_map(|bound| match *bound {
+                clean::GenericBound::TraitBound(ref poly_trait, hir::TraitBoundModifier::None) => {
+                    self.convert(&poly_trait.trait_)
+                }
+                // `?Sized` and lifetimes don't matter when searching.
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the index of the generic parameter `name`, recording its bounds the first time
+    /// it's met.
+    fn param(&mut self, name: &str) -> usize {
+        if let Some(&idx) = self.param_ids.get(name) {
+            return idx;
+        }
+        let idx = self.params.len();
+        self.param_ids.insert(name.to_string(), idx);
+        self.params.push(vec![]);
+
+        let generics = self.generics;
+        let declared = generics
+            .params
+            .iter()
+            .filter(|param| param.name == name)
+            .filter_map(|param| param.get_bounds());
+        let predicates = generics.where_predicates.iter().filter_map(|pred| match *pred {
+            clean::WherePredicate::BoundPredicate { ty: clean::Generic(ref ty), ref bounds }
+                if ty == name =>
+            {
+                Some(&bounds[..])
+            }
+            _ => None,
+        });
+        let bounds = declared.chain(predicates).flat_map(|bounds| self.convert_bounds(bounds));
+        self.params[idx] = bounds.collect();
+        idx
+    }
+}
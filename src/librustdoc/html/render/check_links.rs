@@ -0,0 +1,492 @@
+//! The `--check-links` link checker.
+//!
+//! Once the documentation of a crate has been written out, we go over every page generated for it
+//! and make sure that each `href` leads somewhere:
+//!
+//! * relative links must point to a file in the output directory and, if they have a fragment,
+//!   to an element of that file with a matching `id` (this covers the anchors of methods, fields,
+//!   variants, ...);
+//! * links into crates documented elsewhere (through `--extern-html-root-url` or
+//!   `#![doc(html_root_url)]`) must point to a page rustdoc knows that crate to have.
+//!
+//! Broken links coming from a doc comment are reported through the `broken_doc_links` lint, with
+//! a span pointing into that doc comment. Since the compiler session is gone by the time the
+//! documentation is rendered, the lint levels are computed upfront in `core::run_core` and
+//! handed over as a `DocLinkLint`.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::str;
+
+use rustc::lint::{LevelSource, LintSource};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::sync::Lrc;
+use rustc_errors::Handler;
+use rustc_hir::def_id::{CrateNum, DefId};
+use rustc_session::lint::{self, Level};
+use rustc_span::source_map::SourceMap;
+use rustc_span::Span;
+
+use crate::clean;
+use crate::html::item_type::ItemType;
+use crate::html::markdown::markdown_links;
+use crate::passes::{source_map_span_for_markdown_range, span_of_attrs};
+
+use super::{Cache, ExternalLocation};
+
+/// What is needed to report `broken_doc_links` after the compiler session has ended.
+pub struct DocLinkLint {
+    pub source_map: Lrc<SourceMap>,
+    /// The level of the lint for every local item, since attributes can change it.
+    pub levels: FxHashMap<DefId, LevelSource>,
+    /// The level of the lint at the crate root, used for the links rustdoc generated itself.
+    pub crate_level: LevelSource,
+}
+
+/// The doc comment a link of the generated documentation comes from.
+#[derive(PartialEq)]
+struct LinkOrigin {
+    item: DefId,
+    span: Span,
+    /// Whether the link was written out as-is (as opposed to resolved as an intra-doc link).
+    verbatim: bool,
+}
+
+/// Links coming from doc comments, recorded while rendering the pages they appear on.
+crate struct DocLinks {
+    lint: DocLinkLint,
+    /// The page currently being rendered.
+    current_page: PathBuf,
+    /// Maps a page and an `href` appearing on it to the doc comments it comes from.
+    origins: FxHashMap<(PathBuf, String), Vec<LinkOrigin>>,
+}
+
+impl DocLinks {
+    crate fn new(lint: DocLinkLint) -> DocLinks {
+        DocLinks { lint, current_page: PathBuf::new(), origins: Default::default() }
+    }
+
+    crate fn set_current_page(&mut self, page: PathBuf) {
+        self.current_page = page;
+    }
+
+    /// Remembers that the links of `item`'s documentation may appear on the current page.
+    crate fn record(&mut self, item: &clean::Item) {
+        let docs = match item.attrs.collapsed_doc_value() {
+            Some(docs) => docs,
+            None => return,
+        };
+        let resolved = item.links();
+        for (link, range) in markdown_links(&docs) {
+            let (href, verbatim) = match resolved.iter().find(|(original, _)| *original == link) {
+                Some((_, href)) => (href.clone(), false),
+                None => (link, true),
+            };
+            let span = range
+                .and_then(|range| {
+                    source_map_span_for_markdown_range(
+                        &self.lint.source_map,
+                        &docs,
+                        &range,
+                        &item.attrs,
+                    )
+                })
+                .or_else(|| span_of_attrs(&item.attrs))
+                .unwrap_or(item.source.span());
+            let origin = LinkOrigin { item: item.def_id, span, verbatim };
+            let origins = self.origins.entry((self.current_page.clone(), href)).or_default();
+            if !origins.contains(&origin) {
+                origins.push(origin);
+            }
+        }
+    }
+}
+
+/// Why a link is broken.
+enum Breakage {
+    /// The file it points to doesn't exist.
+    MissingFile(PathBuf),
+    /// The file exists, but has no element with the given `id`.
+    MissingAnchor(PathBuf, String),
+    /// The link points into the documentation of an external crate, at a page rustdoc doesn't
+    /// know about.
+    UnknownExternalPage(String),
+}
+
+/// Checks the links of every page generated for the crate `krate` in `dst`.
+crate fn check(dst: &Path, krate: &str, cache: &Cache, doc_links: &DocLinks, diag: &Handler) {
+    let mut pages = vec![];
+    collect_pages(&dst.join(krate), &mut pages);
+    pages.sort();
+
+    let mut checker = LinkChecker::new(dst, cache);
+    let mut reported = FxHashSet::default();
+    let mut explained = vec![];
+    for page in &pages {
+        let html = match fs::read_to_string(page) {
+            Ok(html) => html,
+            Err(_) => continue,
+        };
+        let mut seen = FxHashSet::default();
+        for href in attribute_values(&html, "href") {
+            if !seen.insert(href.clone()) {
+                continue;
+            }
+            let origins = doc_links.origins.get(&(page.clone(), href.clone()));
+            let verbatim = origins.map_or(false, |o| o.iter().all(|origin| origin.verbatim));
+            let breakage = match checker.check(page, &href, verbatim) {
+                Some(breakage) => breakage,
+                None => continue,
+            };
+            let relative_page = page.strip_prefix(dst).unwrap_or(page);
+            match origins {
+                Some(origins) => {
+                    for origin in origins {
+                        if !reported.insert((origin.item, origin.span, href.clone())) {
+                            continue;
+                        }
+                        let level = doc_links
+                            .lint
+                            .levels
+                            .get(&origin.item)
+                            .unwrap_or(&doc_links.lint.crate_level);
+                        report(
+                            diag,
+                            *level,
+                            &mut explained,
+                            Some(origin.span),
+                            &href,
+                            &breakage,
+                            relative_page,
+                        );
+                    }
+                }
+                None => {
+                    let level = doc_links.lint.crate_level;
+                    report(diag, level, &mut explained, None, &href, &breakage, relative_page)
+                }
+            }
+        }
+    }
+}
+
+fn report(
+    diag: &Handler,
+    (level, source): LevelSource,
+    explained: &mut Vec<LintSource>,
+    span: Option<Span>,
+    href: &str,
+    breakage: &Breakage,
+    page: &Path,
+) {
+    let msg = format!("broken link to `{}`", href);
+    let mut err = match (level, span) {
+        (Level::Allow, _) => return,
+        (Level::Warn, Some(span)) => diag.struct_span_warn(span, &msg),
+        (Level::Warn, None) => diag.struct_warn(&msg),
+        (Level::Deny, Some(span)) | (Level::Forbid, Some(span)) => diag.struct_span_err(span, &msg),
+        (Level::Deny, None) | (Level::Forbid, None) => diag.struct_err(&msg),
+    };
+
+    // Like for regular lints, only explain where the level comes from once.
+    let name = lint::builtin::BROKEN_DOC_LINKS.name_lower();
+    match source {
+        _ if explained.contains(&source) => {}
+        LintSource::Default => {
+            err.note(&format!("`#[{}({})]` on by default", level.as_str(), name));
+        }
+        LintSource::CommandLine(flag_val) => {
+            let flag = match level {
+                Level::Warn => "-W",
+                Level::Deny => "-D",
+                Level::Forbid => "-F",
+                Level::Allow => unreachable!(),
+            };
+            let hyphen_case_name = name.replace("_", "-");
+            if flag_val.as_str() == name {
+                err.note(&format!(
+                    "requested on the command line with `{} {}`",
+                    flag, hyphen_case_name
+                ));
+            } else {
+                err.note(&format!(
+                    "`{} {}` implied by `{} {}`",
+                    flag,
+                    hyphen_case_name,
+                    flag,
+                    flag_val.as_str().replace("_", "-")
+                ));
+            }
+        }
+        LintSource::Node(attr_name, src, reason) => {
+            if let Some(rationale) = reason {
+                err.note(&rationale.as_str());
+            }
+            err.span_note(src, "the lint level is defined here");
+            if attr_name.as_str() != name {
+                err.note(&format!(
+                    "`#[{}({})]` implied by `#[{}({})]`",
+                    level.as_str(),
+                    name,
+                    level.as_str(),
+                    attr_name
+                ));
+            }
+        }
+    }
+
+    if !explained.contains(&source) {
+        explained.push(source);
+    }
+    match breakage {
+        Breakage::MissingFile(file) => {
+            err.note(&format!("`{}` does not exist", file.display()));
+        }
+        Breakage::MissingAnchor(file, id) => {
+            err.note(&format!("`{}` has no element with id `{}`", file.display(), id));
+        }
+        Breakage::UnknownExternalPage(krate) => {
+            err.note(&format!(
+                "no such page is known to exist in the documentation of `{}`",
+                krate
+            ));
+        }
+    }
+    if span.is_some() {
+        err.note(&format!("the link appears in `{}`", page.display()));
+    } else {
+        err.note(&format!("the link was generated in `{}`", page.display()));
+    }
+    err.emit();
+}
+
+struct LinkChecker<'a> {
+    dst: &'a Path,
+    /// The root URL of every crate documented elsewhere, along with its name.
+    remote_roots: Vec<(&'a str, &'a str)>,
+    /// The pages known to exist in the documentation of the external crates, relative to the root
+    /// URL of their crate.
+    remote_pages: FxHashMap<&'a str, FxHashSet<String>>,
+    /// The `id`s of the files linked to so far, or `None` for files which don't exist.
+    ids: FxHashMap<PathBuf, Option<FxHashSet<String>>>,
+}
+
+impl<'a> LinkChecker<'a> {
+    fn new(dst: &'a Path, cache: &'a Cache) -> LinkChecker<'a> {
+        let mut remote_roots = vec![];
+        let mut remote_crates: FxHashMap<CrateNum, &'a str> = FxHashMap::default();
+        for (&cnum, (name, _, location)) in &cache.extern_locations {
+            if let ExternalLocation::Remote(ref url) = *location {
+                remote_roots.push((&url[..], &name[..]));
+                remote_crates.insert(cnum, &url[..]);
+            }
+        }
+        // Check the longest roots first, in case one of them is nested in another.
+        remote_roots.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+
+        let mut remote_pages: FxHashMap<&'a str, FxHashSet<String>> = FxHashMap::default();
+        for (did, (fqp, ty)) in &cache.external_paths {
+            if let Some(&root) = remote_crates.get(&did.krate) {
+                let mut page = fqp[..fqp.len() - 1].join("/");
+                if !page.is_empty() {
+                    page.push('/');
+                }
+                match ty {
+                    ItemType::Module => {
+                        page.push_str(fqp.last().unwrap());
+                        page.push_str("/index.html");
+                    }
+                    _ => page.push_str(&format!("{}.{}.html", ty, fqp.last().unwrap())),
+                }
+                remote_pages.entry(root).or_default().insert(page);
+            }
+        }
+
+        LinkChecker { dst, remote_roots, remote_pages, ids: Default::default() }
+    }
+
+    /// Checks the link `href` found on `page`, returning why it is broken if it is.
+    ///
+    /// `verbatim` links into external crates are trusted: they were written by hand and
+    /// may well lead to a page rustdoc never heard of.
+    fn check(&mut self, page: &Path, href: &str, verbatim: bool) -> Option<Breakage> {
+        let dst = self.dst;
+        if let Some(&(root, krate)) =
+            self.remote_roots.iter().find(|(root, _)| href.starts_with(root))
+        {
+            let path = &href[root.len()..];
+            let path = &path[..path.find(|c| c == '#' || c == '?').unwrap_or(path.len())];
+            if verbatim || self.is_known_remote_page(root, krate, path) {
+                return None;
+            }
+            return Some(Breakage::UnknownExternalPage(krate.to_string()));
+        }
+        if is_external(href) {
+            return None;
+        }
+
+        let (path, fragment) = match href.find('#') {
+            Some(pos) => (&href[..pos], Some(&href[pos + 1..])),
+            None => (href, None),
+        };
+        let path = &path[..path.find('?').unwrap_or(path.len())];
+        let target = if path.is_empty() {
+            page.to_path_buf()
+        } else {
+            let target = normalize(&page.parent().unwrap().join(percent_decode(path)))?;
+            // Links leaving the documentation are none of our business.
+            if !target.starts_with(dst) {
+                return None;
+            }
+            if target.is_dir() {
+                target.join("index.html")
+            } else {
+                target
+            }
+        };
+
+        let ids = self.ids_of(&target);
+        let ids = match ids {
+            Some(ids) => ids,
+            None => {
+                let relative = target.strip_prefix(dst).unwrap_or(&target);
+                return Some(Breakage::MissingFile(relative.to_path_buf()));
+            }
+        };
+        match fragment {
+            // Line numbers of source pages are handled by JavaScript.
+            Some(id) if !id.is_empty() && !id.chars().all(|c| c.is_ascii_digit() || c == '-') => {
+                let id = percent_decode(id);
+                if ids.contains(&id) {
+                    None
+                } else {
+                    let relative = target.strip_prefix(dst).unwrap_or(&target);
+                    Some(Breakage::MissingAnchor(relative.to_path_buf(), id))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn is_known_remote_page(&self, root: &str, krate: &str, path: &str) -> bool {
+        if path.is_empty()
+            || path == format!("{}/", krate)
+            || path == format!("{}/index.html", krate)
+        {
+            return true;
+        }
+        // Primitive types and keywords are documented without being part of any crate's paths.
+        let file = path.rsplit('/').next().unwrap();
+        if file.starts_with("primitive.") || file.starts_with("keyword.") {
+            return true;
+        }
+        self.remote_pages.get(root).map_or(false, |pages| pages.contains(path))
+    }
+
+    /// Returns the `id`s defined in `file`, or `None` if it doesn't exist.
+    fn ids_of(&mut self, file: &Path) -> Option<&FxHashSet<String>> {
+        self.ids
+            .entry(file.to_path_buf())
+            .or_insert_with(|| {
+                if !file.is_file() {
+                    return None;
+                }
+                if file.extension().map_or(true, |ext| ext != "html") {
+                    return Some(FxHashSet::default());
+                }
+                let html = fs::read_to_string(file).ok()?;
+                Some(attribute_values(&html, "id").collect())
+            })
+            .as_ref()
+    }
+}
+
+fn collect_pages(dir: &Path, pages: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_pages(&path, pages);
+        } else if path.extension().map_or(false, |ext| ext == "html") {
+            pages.push(path);
+        }
+    }
+}
+
+/// Returns `true` for links which can't be checked by looking at the output directory.
+fn is_external(href: &str) -> bool {
+    href.starts_with('/')
+        || href.find(':').map_or(false, |colon| {
+            // A scheme, like `https:` or `mailto:`, comes before any path or fragment.
+            !href[..colon].contains(|c| c == '/' || c == '#' || c == '?')
+        })
+}
+
+/// Resolves the `.` and `..` components of `path` without touching the file system.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    Some(normalized)
+}
+
+/// Returns the (unescaped) values of every `name` attribute in `html`.
+///
+/// This only understands the subset of HTML generated by rustdoc: the attribute must be
+/// preceded by whitespace and its value must be quoted.
+fn attribute_values<'a>(html: &'a str, name: &'a str) -> impl Iterator<Item = String> + 'a {
+    html.match_indices(name).filter_map(move |(pos, _)| {
+        if !html[..pos].ends_with(|c: char| c.is_ascii_whitespace()) {
+            return None;
+        }
+        let rest = &html[pos + name.len()..];
+        if !rest.starts_with('=') {
+            return None;
+        }
+        let rest = &rest[1..];
+        let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let rest = &rest[1..];
+        let end = rest.find(quote)?;
+        Some(unescape(&rest[..end]))
+    })
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
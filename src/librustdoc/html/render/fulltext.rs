@@ -0,0 +1,96 @@
+//! The optional full-text search index.
+//!
+//! The regular search index only knows about item names, paths and the first line of their
+//! documentation. When `--generate-fulltext-index` is passed, we additionally build an inverted
+//! index mapping every word of the documentation to the items it appears in. It is written to
+//! a separate `search-fulltext/<crate>.js` file per crate, which the search page only loads once
+//! the user starts searching, so that sites with many crates still load quickly.
+//!
+//! The format is deliberately compact: terms are sorted (allowing prefix lookup with a binary
+//! search) and every posting list is delta-encoded against the position of the items inside the
+//! crate's entry of the regular search index.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Words which appear in nearly every doc comment and are therefore useless for searching.
+///
+/// This list must be kept in sync with `FULLTEXT_STOP_WORDS` in `main.js`.
+const STOP_WORDS: &[&str] = &[
+    "an", "and", "are", "as", "at", "be", "by", "for", "from", "if", "in", "is", "it", "of", "on",
+    "or", "that", "the", "this", "to", "was", "with",
+];
+
+/// Terms longer than this are most likely hashes or generated identifiers.
+const MAX_TERM_LEN: usize = 40;
+
+/// Splits documentation text into the lowercase terms stored in the index.
+///
+/// `main.js` splits user queries the same way, so both sides agree on what a "word" is.
+crate fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+        .filter(|word| {
+            let len = word.chars().count();
+            len > 1 && len <= MAX_TERM_LEN
+        })
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&&**word))
+}
+
+#[derive(Default)]
+crate struct FullTextIndex {
+    /// Maps every term to the (sorted, deduplicated) positions of the items containing it.
+    terms: BTreeMap<String, Vec<usize>>,
+}
+
+impl FullTextIndex {
+    /// Indexes the documentation of the item at position `pos` of the crate's search index.
+    ///
+    /// Items must be added in increasing position order.
+    crate fn add_item(&mut self, pos: usize, text: &str) {
+        for term in tokenize(text) {
+            let postings = self.terms.entry(term).or_default();
+            if postings.last() != Some(&pos) {
+                postings.push(pos);
+            }
+        }
+    }
+
+    crate fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Serializes the index as the script loaded by the search page.
+    crate fn to_js(&self, krate: &str) -> String {
+        #[derive(Serialize)]
+        struct Data<'a> {
+            #[serde(rename = "t")]
+            terms: Vec<&'a str>,
+            #[serde(rename = "p")]
+            postings: Vec<Vec<usize>>,
+        }
+
+        let data = Data {
+            terms: self.terms.keys().map(|term| &**term).collect(),
+            postings: self.terms.values().map(|postings| delta_encode(postings)).collect(),
+        };
+        format!(
+            r#"addFullTextIndex("{}", {});"#,
+            krate,
+            serde_json::to_string(&data).expect("failed serde conversion")
+        )
+    }
+}
+
+fn delta_encode(postings: &[usize]) -> Vec<usize> {
+    let mut last = 0;
+    postings
+        .iter()
+        .map(|&pos| {
+            let delta = pos - last;
+            last = pos;
+            delta
+        })
+        .collect()
+}
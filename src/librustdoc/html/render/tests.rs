@@ -22,3 +22,23 @@ fn test_name_sorting() {
     sorted.sort_by_key(|&s| name_key(s));
     assert_eq!(names, sorted);
 }
+
+#[test]
+fn test_fulltext_tokenize() {
+    let terms: Vec<_> =
+        fulltext::tokenize("Returns the `Vec<u8>` of a (mutable) Élan, or x!").collect();
+    assert_eq!(terms, ["returns", "vec", "u8", "mutable", "élan"]);
+}
+
+#[test]
+fn test_fulltext_index() {
+    let mut index = fulltext::FullTextIndex::default();
+    assert!(index.is_empty());
+    index.add_item(0, "Frobnicates the widget.");
+    index.add_item(3, "A widget, or two widget.");
+    index.add_item(7, "Another widget");
+    assert_eq!(
+        index.to_js("foo"),
+        r#"addFullTextIndex("foo", {"t":["another","frobnicates","two","widget"],"p":[[7],[0],[3],[0,3,4]]});"#
+    );
+}
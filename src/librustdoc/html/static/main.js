@@ -1,5 +1,5 @@
 // From rust:
-/* global ALIASES, currentCrate, rootPath */
+/* global ALIASES, currentCrate, resourceSuffix, rootPath */
 
 // Local js definitions:
 /* global addClass, getCurrentValue, hasClass */
@@ -533,7 +533,18 @@ function getSearchElement() {
         var NAME = 0;
         var INPUTS_DATA = 0;
         var OUTPUT_DATA = 1;
+        // Must be kept in sync with `STOP_WORDS` in `html/render/fulltext.rs`.
+        var FULLTEXT_STOP_WORDS = ["an", "and", "are", "as", "at", "be", "by", "for", "from",
+                                   "if", "in", "is", "it", "of", "on", "or", "that", "the",
+                                   "this", "to", "was", "with"];
         var params = getQueryStringParams();
+        // Position of the first item of each crate in `searchIndex`.
+        var crateOffsets = {};
+        // Whether any crate has been documented with `--generate-fulltext-index`.
+        var hasFullText = false;
+        // The full-text indexes are only loaded once the user starts searching.
+        var fullTextIndexes = {};
+        var fullTextRequested = false;
 
         // Populate search bar with query string search term when provided,
         // but only if the input bar is empty. This avoid the obnoxious issue
@@ -881,6 +892,197 @@ function getSearchElement() {
                 return literalSearch === true ? false : lev_distance;
             }
 
+            // Types which are looked through when matching signatures, so that searching for
+            // `T` also finds the functions taking a `Box<T>` or an `Rc<T>`.
+            var SIG_WRAPPERS = ["box", "rc", "arc", "cow", "pin"];
+
+            /**
+             * Parses one type of a type-based search query, like `&Vec<T>` or `impl Read`.
+             * Single uppercase letters are generic parameters. Returns `null` (and doesn't
+             * consume anything) if there's no type at the current position.
+             */
+            function parseSigType(state) {
+                var tokens = state.tokens;
+                // References are transparent, and `dyn Trait` is searched like the trait.
+                while (tokens[state.pos] === "&" || tokens[state.pos] === "mut" ||
+                       tokens[state.pos] === "dyn") {
+                    state.pos += 1;
+                }
+                var token = tokens[state.pos];
+                if (token === undefined) {
+                    return null;
+                }
+                state.pos += 1;
+                if (token === "[") {
+                    var inner = parseSigType(state);
+                    // Skip the length of arrays.
+                    while (state.pos < tokens.length && tokens[state.pos] !== "]") {
+                        state.pos += 1;
+                    }
+                    state.pos += 1;
+                    return {name: "slice", generics: inner !== null ? [inner] : []};
+                } else if (token === "(") {
+                    var types = parseSigList(state, ")");
+                    if (types.length < 2) {
+                        return types.length === 1 ? types[0] : null;
+                    }
+                    return {name: "tuple", generics: types};
+                } else if (token === "impl") {
+                    var bounds = [];
+                    for (;;) {
+                        var bound = parseSigType(state);
+                        if (bound !== null) {
+                            bounds.push(bound);
+                        }
+                        if (tokens[state.pos] !== "+") {
+                            break;
+                        }
+                        state.pos += 1;
+                    }
+                    return {impl: true, bounds: bounds};
+                } else if (!/^[A-Za-z0-9_]/.test(token)) {
+                    state.pos -= 1;
+                    return null;
+                }
+                var segments = token.split("::");
+                var name = segments[segments.length - 1];
+                var generics = [];
+                if (tokens[state.pos] === "<") {
+                    state.pos += 1;
+                    generics = parseSigList(state, ">");
+                }
+                if (generics.length === 0 && /^[A-Z]$/.test(name)) {
+                    return {param: name};
+                }
+                return {name: name.toLowerCase(), generics: generics};
+            }
+
+            /**
+             * Parses a comma-separated list of types, up to the `close` token (or the end of
+             * the query if there is none).
+             */
+            function parseSigList(state, close) {
+                var tokens = state.tokens;
+                var types = [];
+                while (state.pos < tokens.length && tokens[state.pos] !== close) {
+                    // Only keep the type of associated type bindings like `Item = T`.
+                    if (tokens[state.pos + 1] === "=") {
+                        state.pos += 2;
+                    }
+                    var ty = parseSigType(state);
+                    if (ty !== null) {
+                        types.push(ty);
+                    }
+                    if (state.pos < tokens.length && tokens[state.pos] !== close) {
+                        // Skip the separating comma, or whatever we didn't understand.
+                        state.pos += 1;
+                    }
+                }
+                state.pos += 1;
+                return types;
+            }
+
+            function parseSigTypes(query) {
+                var tokens = query.match(/[A-Za-z0-9_]+(?:::[A-Za-z0-9_]+)*|[<>,&\[\]()=+;]/g);
+                return parseSigList({tokens: tokens || [], pos: 0}, null);
+            }
+
+            /**
+             * Checks if a type of a query matches a type of a signature from the search index,
+             * which is either the name of a type, an array `[name, generics]`, or the index of
+             * one of the function's generic parameters.
+             *
+             * Returns the bindings of the query's generic parameters if it does, `null` otherwise.
+             */
+            function unifySigType(queryType, indexType, sig, bindings) {
+                if (queryType.param !== undefined) {
+                    // A generic parameter of the query matches anything, but always the same
+                    // thing.
+                    var key = JSON.stringify(indexType);
+                    if (bindings.hasOwnProperty(queryType.param)) {
+                        return bindings[queryType.param] === key ? bindings : null;
+                    }
+                    var newBindings = {};
+                    for (var param in bindings) {
+                        if (bindings.hasOwnProperty(param)) {
+                            newBindings[param] = bindings[param];
+                        }
+                    }
+                    newBindings[queryType.param] = key;
+                    return newBindings;
+                }
+                if (typeof indexType === "number") {
+                    // A generic parameter of the function matches the traits it's bound by.
+                    var bounds = sig[2][indexType];
+                    if (queryType.impl === true) {
+                        return unifySigTypes(queryType.bounds, bounds, sig, bindings);
+                    }
+                    for (var i = 0; i < bounds.length; ++i) {
+                        var ret = unifySigType(queryType, bounds[i], sig, bindings);
+                        if (ret !== null) {
+                            return ret;
+                        }
+                    }
+                    return null;
+                }
+                if (queryType.impl === true) {
+                    // `impl Trait` also matches `dyn Trait`.
+                    if (queryType.bounds.length !== 1) {
+                        return null;
+                    }
+                    return unifySigType(queryType.bounds[0], indexType, sig, bindings);
+                }
+                var name = typeof indexType === "string" ? indexType : indexType[0];
+                var generics = typeof indexType === "string" ? [] : indexType[1];
+                if (name === queryType.name || (queryType.name === "slice" && name === "array")) {
+                    return unifySigTypes(queryType.generics, generics, sig, bindings);
+                }
+                if (SIG_WRAPPERS.indexOf(name) !== -1 && generics.length > 0) {
+                    return unifySigType(queryType, generics[0], sig, bindings);
+                }
+                return null;
+            }
+
+            /**
+             * Checks that each of `queryTypes` matches a different one of `indexTypes`, in any
+             * order. If `next` is given, it's called with the bindings of every possible match
+             * until it returns something else than `null`.
+             */
+            function unifySigTypes(queryTypes, indexTypes, sig, bindings, next) {
+                if (queryTypes.length === 0) {
+                    return next !== undefined ? next(bindings) : bindings;
+                }
+                var rest = queryTypes.slice(1);
+                for (var i = 0; i < indexTypes.length; ++i) {
+                    var newBindings = unifySigType(queryTypes[0], indexTypes[i], sig, bindings);
+                    if (newBindings === null) {
+                        continue;
+                    }
+                    var remaining = indexTypes.slice(0, i).concat(indexTypes.slice(i + 1));
+                    var ret = unifySigTypes(rest, remaining, sig, newBindings, next);
+                    if (ret !== null) {
+                        return ret;
+                    }
+                }
+                return null;
+            }
+
+            /**
+             * Checks if a function signature from the search index matches the given inputs and
+             * output, with consistent generic parameters (`Vec<T> -> T` doesn't match
+             * `fn(Vec<T>) -> usize`).
+             */
+            function checkSignature(sig, inputs, output) {
+                return unifySigTypes(inputs, sig[0], sig, {}, function(bindings) {
+                    if (output === null) {
+                        return bindings;
+                    } else if (sig[1] === null) {
+                        return null;
+                    }
+                    return unifySigType(output, sig[1], sig, bindings);
+                }) !== null;
+            }
+
             function checkPath(contains, lastElem, ty) {
                 if (contains.length === 0) {
                     return 0;
@@ -1014,6 +1216,11 @@ function getSearchElement() {
                     inputs[i] = extractGenerics(inputs[i]);
                 }
                 var output = extractGenerics(parts[1]);
+                // Generic parameters are told apart by their case, so we need the raw query.
+                var sigParts = query.query.split("->");
+                var sigInputs = parseSigTypes(sigParts[0]);
+                var sigOutput = parseSigTypes(sigParts[1]);
+                sigOutput = sigOutput.length > 0 ? sigOutput[0] : null;
 
                 for (i = 0; i < nSearchWords; ++i) {
                     if (filterCrates !== undefined && searchIndex[i].crate !== filterCrates) {
@@ -1021,6 +1228,23 @@ function getSearchElement() {
                     }
                     var type = searchIndex[i].type;
                     ty = searchIndex[i];
+                    // Prefer the full signature if the index has it.
+                    if (ty.sig) {
+                        if ((sigInputs.length > 0 || sigOutput !== null) &&
+                                checkSignature(ty.sig, sigInputs, sigOutput)) {
+                            fullId = generateId(ty);
+                            if (sigInputs.length > 0) {
+                                results_in_args[fullId] = {id: i, index: -1, dontValidate: true};
+                            }
+                            if (sigOutput !== null) {
+                                results_returned[fullId] = {id: i, index: -1, dontValidate: true};
+                            }
+                            if (input === "*") {
+                                results[fullId] = {id: i, index: -1, dontValidate: true};
+                            }
+                        }
+                        continue;
+                    }
                     if (!type) {
                         continue;
                     }
@@ -1308,7 +1532,7 @@ function getSearchElement() {
             search_input.onkeydown = function(e) {
                 // "actives" references the currently highlighted item in each search tab.
                 // Each array in "actives" represents a tab.
-                var actives = [[], [], []];
+                var actives = [[], [], [], []];
                 // "current" is used to know which tab we're looking into.
                 var current = 0;
                 onEachLazy(document.getElementById("results").childNodes, function(e) {
@@ -1345,10 +1569,11 @@ function getSearchElement() {
                             actives[currentTab][0].getElementsByTagName("a")[0].href;
                     }
                 } else if (e.which === 9) { // tab
+                    var nbTabs = document.getElementById("titles").childNodes.length;
                     if (e.shiftKey) {
-                        printTab(currentTab > 0 ? currentTab - 1 : 2);
+                        printTab(currentTab > 0 ? currentTab - 1 : nbTabs - 1);
                     } else {
-                        printTab(currentTab > 1 ? 0 : currentTab + 1);
+                        printTab(currentTab < nbTabs - 1 ? currentTab + 1 : 0);
                     }
                     e.preventDefault();
                 } else if (e.which === 16) { // shift
@@ -1506,6 +1731,10 @@ function getSearchElement() {
             var ret_others = addTab(results.others, query);
             var ret_in_args = addTab(results.in_args, query, false);
             var ret_returned = addTab(results.returned, query, false);
+            var ret_in_docs = hasFullText ? addTab(results.in_docs, query, false) : null;
+            if (ret_in_docs === null && currentTab === 3) {
+                currentTab = 0;
+            }
 
             var output = "<h1>Results for " + escape(query.query) +
                 (query.type ? " (type: " + escape(query.type) + ")" : "") + "</h1>" +
@@ -1513,8 +1742,11 @@ function getSearchElement() {
                 makeTabHeader(0, "In Names", ret_others[1]) +
                 makeTabHeader(1, "In Parameters", ret_in_args[1]) +
                 makeTabHeader(2, "In Return Types", ret_returned[1]) +
+                (ret_in_docs !== null ?
+                 makeTabHeader(3, "In Documentation", ret_in_docs[1]) : "") +
                 "</div><div id=\"results\">" +
-                ret_others[0] + ret_in_args[0] + ret_returned[0] + "</div>";
+                ret_others[0] + ret_in_args[0] + ret_returned[0] +
+                (ret_in_docs !== null ? ret_in_docs[0] : "") + "</div>";
 
             var search = getSearchElement();
             search.innerHTML = output;
@@ -1533,6 +1765,9 @@ function getSearchElement() {
             elems[0].onclick = function() { printTab(0); };
             elems[1].onclick = function() { printTab(1); };
             elems[2].onclick = function() { printTab(2); };
+            if (ret_in_docs !== null) {
+                elems[3].onclick = function() { printTab(3); };
+            }
             printTab(currentTab);
         }
 
@@ -1577,7 +1812,8 @@ function getSearchElement() {
                 return ret;
             }
 
-            var queries = query.raw.split(",");
+            // Commas separate the arguments of type-based queries.
+            var queries = query.raw.search("->") > -1 ? [query.raw] : query.raw.split(",");
             var results = {
                 "in_args": [],
                 "returned": [],
@@ -1609,6 +1845,121 @@ function getSearchElement() {
             }
         }
 
+        /**
+         * Splits a query into terms the same way rustdoc splits the documentation when
+         * building the full-text index.
+         */
+        function getFullTextTerms(query) {
+            return query.toLowerCase().split(/[\s!-\/:-@\[-`{-~]+/).filter(function(term) {
+                return term.length > 1 && FULLTEXT_STOP_WORDS.indexOf(term) === -1;
+            });
+        }
+
+        /**
+         * Looks up all the terms starting with `prefix` in a crate's full-text index.
+         * @param  {Object} fullTextIndex [The crate's full-text index]
+         * @param  {string} prefix        [The term to look up]
+         * @return {Object}               [Maps the positions of the matching items in the
+         *                                 crate to 1 for an exact match, 0 otherwise]
+         */
+        function fullTextLookup(fullTextIndex, prefix) {
+            var terms = fullTextIndex.t;
+            var low = 0;
+            var high = terms.length;
+            // The terms are sorted, so the matching ones are all next to each other.
+            while (low < high) {
+                var mid = (low + high) >> 1;
+                if (terms[mid] < prefix) {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            var found = {};
+            for (var i = low; i < terms.length && terms[i].startsWith(prefix); ++i) {
+                var exact = terms[i] === prefix ? 1 : 0;
+                var postings = fullTextIndex.p[i];
+                var pos = 0;
+                // Posting lists are delta-encoded.
+                for (var j = 0; j < postings.length; ++j) {
+                    pos += postings[j];
+                    found[pos] = Math.max(found[pos] || 0, exact);
+                }
+            }
+            return found;
+        }
+
+        function execFullTextSearch(query, filterCrates) {
+            var terms = getFullTextTerms(query.query);
+            var matches = [];
+            if (terms.length === 0) {
+                return [];
+            }
+            for (var crate in fullTextIndexes) {
+                if (!fullTextIndexes.hasOwnProperty(crate) ||
+                        (filterCrates !== undefined && crate !== filterCrates)) {
+                    continue;
+                }
+                // Every term has to be present; items get a better score for every term
+                // matching a word exactly rather than only a prefix of it.
+                var scores = fullTextLookup(fullTextIndexes[crate], terms[0]);
+                for (var i = 1; i < terms.length; ++i) {
+                    var found = fullTextLookup(fullTextIndexes[crate], terms[i]);
+                    for (var pos in scores) {
+                        if (!found.hasOwnProperty(pos)) {
+                            delete scores[pos];
+                        } else {
+                            scores[pos] += found[pos];
+                        }
+                    }
+                }
+                for (pos in scores) {
+                    if (scores.hasOwnProperty(pos)) {
+                        matches.push({
+                            id: crateOffsets[crate] + parseInt(pos, 10),
+                            score: scores[pos],
+                        });
+                    }
+                }
+            }
+            matches.sort(function(a, b) {
+                return b.score - a.score || a.id - b.id;
+            });
+            return matches.slice(0, MAX_RESULTS).map(function(match) {
+                var item = searchIndex[match.id];
+                var obj = {crate: item.crate, ty: item.ty, name: item.name, path: item.path,
+                           desc: item.desc, parent: item.parent, type: item.type};
+                var res = buildHrefAndPath(obj);
+                obj.displayPath = pathSplitter(res[0]);
+                obj.fullPath = obj.displayPath + obj.name;
+                obj.href = res[1];
+                return obj;
+            });
+        }
+
+        function loadFullTextIndexes() {
+            if (fullTextRequested === true) {
+                return;
+            }
+            fullTextRequested = true;
+            for (var crate in rawSearchIndex) {
+                if (rawSearchIndex.hasOwnProperty(crate) && rawSearchIndex[crate].f) {
+                    var script = document.createElement("script");
+                    script.src = rootPath + "search-fulltext/" + crate +
+                                 (window.resourceSuffix || "") + ".js";
+                    document.head.appendChild(script);
+                }
+            }
+        }
+
+        window.addFullTextIndex = function(crate, fullTextIndex) {
+            fullTextIndexes[crate] = fullTextIndex;
+            // Refresh the results which are currently displayed, if any.
+            if (search_input.value.length !== 0 && currentResults) {
+                search(undefined, true);
+            }
+        };
+
         function getFilterCrates() {
             var elem = document.getElementById("crate-search");
 
@@ -1650,7 +2001,12 @@ function getSearchElement() {
             }
 
             var filterCrates = getFilterCrates();
-            showResults(execSearch(query, index, filterCrates));
+            var results = execSearch(query, index, filterCrates);
+            if (hasFullText) {
+                loadFullTextIndexes();
+                results.in_docs = execFullTextSearch(query, filterCrates);
+            }
+            showResults(results);
         }
 
         function buildIndex(rawSearchIndex) {
@@ -1670,6 +2026,10 @@ function getSearchElement() {
                     desc: rawSearchIndex[crate].doc,
                     type: null,
                 });
+                crateOffsets[crate] = searchIndex.length;
+                if (rawSearchIndex[crate].f) {
+                    hasFullText = true;
+                }
 
                 // an array of [(Number) item type,
                 //              (String) name,
@@ -1701,7 +2061,7 @@ function getSearchElement() {
                     var rawRow = items[i];
                     var row = {crate: crate, ty: rawRow[0], name: rawRow[1],
                                path: rawRow[2] || lastPath, desc: rawRow[3],
-                               parent: paths[rawRow[4]], type: rawRow[5]};
+                               parent: paths[rawRow[4]], type: rawRow[5], sig: rawRow[6]};
                     searchIndex.push(row);
                     if (typeof row.name === "string") {
                         var word = row.name.toLowerCase();
@@ -2531,7 +2891,7 @@ function getSearchElement() {
 
     // In the search display, allows to switch between tabs.
     function printTab(nb) {
-        if (nb === 0 || nb === 1 || nb === 2) {
+        if (nb === 0 || nb === 1 || nb === 2 || nb === 3) {
             currentTab = nb;
         }
         var nb_copy = nb;
@@ -2697,6 +3057,9 @@ function getSearchElement() {
              and <code>const</code>.",
             "Search functions by type signature (e.g., <code>vec -&gt; usize</code> or \
              <code>* -&gt; vec</code>)",
+            "Single uppercase letters are generic parameters, and <code>impl Trait</code> \
+             matches anything implementing the trait (e.g., <code>Vec&lt;T&gt; -&gt; \
+             Option&lt;T&gt;</code> or <code>impl Read -&gt; String</code>)",
             "Search multiple things at once by splitting your query with comma (e.g., \
              <code>str,u8</code> or <code>String,struct:Vec,test</code>)",
             "You can look for items with an exact name by putting double quotes around \
@@ -388,6 +388,37 @@ fn opts() -> Vec<RustcOptGroup> {
                 "calculate percentage of public items with documentation",
             )
         }),
+        unstable("intra-doc-link-report", |o| {
+            o.optopt(
+                "",
+                "intra-doc-link-report",
+                "write a JSON report of broken and ambiguous intra-doc links to PATH",
+                "PATH",
+            )
+        }),
+        unstable("generate-fulltext-index", |o| {
+            o.optflag(
+                "",
+                "generate-fulltext-index",
+                "generate an index of the full documentation text for the search page",
+            )
+        }),
+        unstable("check-links", |o| {
+            o.optflag(
+                "",
+                "check-links",
+                "check that every link in the generated documentation works",
+            )
+        }),
+        unstable("cfg-set", |o| {
+            o.optmulti(
+                "",
+                "cfg-set",
+                "document the crate with this comma-separated set of cfgs, \
+                 and mark the items only available under some of the sets",
+                "SET",
+            )
+        }),
         unstable("enable-per-target-ignores", |o| {
             o.optflag(
                 "",
@@ -473,8 +504,14 @@ fn main_options(options: config::Options) -> i32 {
         let Output { krate, renderinfo, renderopts } = out;
         info!("going to format");
         let (error_format, edition, debugging_options) = diag_opts;
-        let diag = core::new_handler(error_format, None, &debugging_options);
+        // `--check-links` reports broken links with spans pointing into the doc comments.
+        let source_map = renderinfo.doc_link_lint.as_ref().map(|lint| lint.source_map.clone());
+        let diag = core::new_handler(error_format, source_map, &debugging_options);
         match html::render::run(krate, renderopts, renderinfo, &diag, edition) {
+            Ok(_) if diag.has_errors() => {
+                diag.print_error_count(&rustc_driver::diagnostics_registry());
+                rustc_driver::EXIT_FAILURE
+            }
             Ok(_) => rustc_driver::EXIT_SUCCESS,
             Err(e) => {
                 diag.struct_err(&format!("couldn't generate documentation: {}", e.error))
@@ -502,7 +539,11 @@ where
     let result = rustc_driver::catch_fatal_errors(move || {
         let crate_name = options.crate_name.clone();
         let crate_version = options.crate_version.clone();
-        let (mut krate, renderinfo, renderopts) = core::run_core(options);
+        let (mut krate, renderinfo, renderopts) = if options.cfg_sets.is_empty() {
+            core::run_core(options)
+        } else {
+            document_cfg_sets(options)
+        };
 
         info!("finished with rustc");
 
@@ -520,3 +561,21 @@ where
         Err(_) => panic::resume_unwind(Box::new(rustc_errors::FatalErrorMarker)),
     }
 }
+
+/// Documents the crate once for each `--cfg-set`, and merges the results into a single crate.
+fn document_cfg_sets(
+    options: config::Options,
+) -> (clean::Crate, html::render::RenderInfo, config::RenderOptions) {
+    let mut documented = vec![];
+    let mut last_renderopts = None;
+    for set in &options.cfg_sets {
+        let mut options = options.clone();
+        options.cfgs.extend(set.iter().cloned());
+        let (krate, renderinfo, renderopts) = core::run_core(options);
+        let cfgs = set.iter().map(|cfg| clean::merge::cfg_from_spec(cfg)).collect();
+        documented.push(clean::merge::CfgSetCrate { krate, renderinfo, cfgs });
+        last_renderopts = Some(renderopts);
+    }
+    let (krate, renderinfo) = clean::merge::merge(documented);
+    (krate, renderinfo, last_renderopts.expect("no cfg set to document"))
+}
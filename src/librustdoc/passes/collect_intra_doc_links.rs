@@ -1,5 +1,5 @@
 use rustc::lint;
-use rustc::ty;
+use rustc::ty::{self, DefIdTree};
 use rustc_ast::ast::{self, Ident};
 use rustc_errors::Applicability;
 use rustc_expand::base::SyntaxExtensionKind;
@@ -14,8 +14,11 @@ use rustc_hir::def_id::DefId;
 use rustc_resolve::ParentScope;
 use rustc_span::symbol::Symbol;
 use rustc_span::DUMMY_SP;
+use serde::Serialize;
 
+use std::fs;
 use std::ops::Range;
+use std::path::Path;
 
 use crate::clean::*;
 use crate::core::DocContext;
@@ -37,7 +40,13 @@ pub fn collect_intra_doc_links(krate: Crate, cx: &DocContext<'_>) -> Crate {
     } else {
         let mut coll = LinkCollector::new(cx);
 
-        coll.fold_crate(krate)
+        let krate = coll.fold_crate(krate);
+
+        if let Some(ref path) = cx.intra_doc_link_report {
+            coll.write_report(path);
+        }
+
+        krate
     }
 }
 
@@ -46,24 +55,55 @@ enum ErrorKind {
     AnchorFailure(&'static str),
 }
 
+/// What `Self` refers to in the documentation of an item.
+#[derive(Clone, Copy)]
+struct SelfContext {
+    /// The type or trait that `Self` resolves to.
+    res: Res,
+    /// The impl block the documented item belongs to, if any. Used to look up items of trait
+    /// impls, which can't be found through the inherent impls of the type.
+    impl_: Option<DefId>,
+}
+
+/// A link that could not be resolved, recorded for `--intra-doc-link-report`.
+#[derive(Serialize)]
+struct BrokenLink {
+    file: String,
+    line: usize,
+    column: usize,
+    item: String,
+    link: String,
+    kind: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    candidates: Vec<String>,
+}
+
 struct LinkCollector<'a, 'tcx> {
     cx: &'a DocContext<'tcx>,
     mod_ids: Vec<hir::HirId>,
+    broken_links: Vec<BrokenLink>,
 }
 
 impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
     fn new(cx: &'a DocContext<'tcx>) -> Self {
-        LinkCollector { cx, mod_ids: Vec::new() }
+        LinkCollector { cx, mod_ids: Vec::new(), broken_links: Vec::new() }
     }
 
     fn variant_field(
         &self,
         path_str: &str,
+        ns: Namespace,
         current_item: &Option<String>,
         module_id: rustc_ast::ast::NodeId,
     ) -> Result<(Res, Option<String>), ErrorKind> {
         let cx = self.cx;
 
+        // Fields only ever live in the value namespace.
+        if ns != ValueNS {
+            return Err(ErrorKind::ResolutionFailure);
+        }
+
         let mut split = path_str.rsplitn(3, "::");
         let variant_field_name =
             split.next().map(|f| Symbol::intern(f)).ok_or(ErrorKind::ResolutionFailure)?;
@@ -102,7 +142,12 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
                 }
                 match cx.tcx.type_of(did).kind {
                     ty::Adt(def, _) if def.is_enum() => {
-                        if def.all_fields().any(|item| item.ident.name == variant_field_name) {
+                        let variant = def
+                            .variants
+                            .iter()
+                            .find(|variant| variant.ident.name == variant_name)
+                            .ok_or(ErrorKind::ResolutionFailure)?;
+                        if variant.fields.iter().any(|item| item.ident.name == variant_field_name) {
                             Ok((
                                 ty_res,
                                 Some(format!(
@@ -121,6 +166,34 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
         }
     }
 
+    /// Resolves an associated item of a primitive type, looking through all the impls that
+    /// the standard library spreads the primitive's items over.
+    fn resolve_primitive_item(
+        &self,
+        prim: Res,
+        prim_name: &str,
+        item_name: Symbol,
+        ns: Namespace,
+        self_cx: Option<SelfContext>,
+        extra_fragment: &Option<String>,
+    ) -> Result<(Res, Option<String>), ErrorKind> {
+        let cx = self.cx;
+
+        let impls = self_cx.and_then(|self_cx| self_cx.impl_).into_iter();
+        let item = impls
+            .chain(primitive_impls(cx, prim_name))
+            .flat_map(|did| cx.tcx.associated_items(did).filter_by_name_unhygienic(item_name))
+            .find_map(|item| assoc_item_fragment(item, ns).map(|out| (item, out)));
+
+        match item {
+            Some((item, _)) if extra_fragment.is_some() => {
+                Err(ErrorKind::AnchorFailure(assoc_item_anchor_failure(item)))
+            }
+            Some((_, out)) => Ok((prim, Some(format!("{}#{}.{}", prim_name, out, item_name)))),
+            None => Err(ErrorKind::ResolutionFailure),
+        }
+    }
+
     /// Resolves a string as a path within a particular namespace. Also returns an optional
     /// URL fragment in the case of variants and methods.
     fn resolve(
@@ -128,11 +201,28 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
         path_str: &str,
         ns: Namespace,
         current_item: &Option<String>,
+        self_cx: Option<SelfContext>,
         parent_id: Option<hir::HirId>,
         extra_fragment: &Option<String>,
     ) -> Result<(Res, Option<String>), ErrorKind> {
         let cx = self.cx;
 
+        // The resolver doesn't know about `Self` outside of an impl or trait body, so handle
+        // a plain `[Self]` link ourselves.
+        if path_str == "Self" && ns == TypeNS {
+            if let Some(self_cx) = self_cx {
+                if let Some(prim_name) = primitive_name(self_cx.res) {
+                    if extra_fragment.is_some() {
+                        return Err(ErrorKind::AnchorFailure(
+                            "primitive types cannot be followed by anchors",
+                        ));
+                    }
+                    return Ok((self_cx.res, Some(prim_name.to_owned())));
+                }
+                return Ok((self_cx.res, extra_fragment.clone()));
+            }
+        }
+
         // In case we're in a module, try to resolve the relative path.
         if let Some(module_id) = parent_id.or(self.mod_ids.last().cloned()) {
             let module_id = cx.tcx.hir().hir_to_node_id(module_id);
@@ -176,55 +266,67 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
                     ));
                 }
                 return Ok((prim, Some(path_str.to_owned())));
-            } else {
-                // If resolution failed, it may still be a method
-                // because methods are not handled by the resolver
-                // If so, bail when we're not looking for a value.
-                if ns != ValueNS {
-                    return Err(ErrorKind::ResolutionFailure);
-                }
+            } else if ns != ValueNS && !path_str.contains("::") {
+                // If resolution failed, it may still be a method or an associated type
+                // because associated items are not handled by the resolver.
+                // If so, bail when we're not looking for one of those.
+                return Err(ErrorKind::ResolutionFailure);
             }
 
             // Try looking for methods and associated items.
             let mut split = path_str.rsplitn(2, "::");
             let item_name =
                 split.next().map(|f| Symbol::intern(f)).ok_or(ErrorKind::ResolutionFailure)?;
-            let path = split
-                .next()
-                .map(|f| {
-                    if f == "self" || f == "Self" {
-                        if let Some(name) = current_item.as_ref() {
-                            return name.clone();
-                        }
-                    }
-                    f.to_owned()
-                })
-                .ok_or(ErrorKind::ResolutionFailure)?;
+            let path = split.next().ok_or(ErrorKind::ResolutionFailure)?;
+
+            // `Self::item` is looked up on the enclosing type or trait directly, so that it
+            // works no matter which module the impl lives in.
+            let self_cx = if path == "Self" { self_cx } else { None };
+            let path = match self_cx.map(|self_cx| self_cx.res) {
+                Some(res) => match primitive_name(res) {
+                    Some(prim_name) => prim_name.to_owned(),
+                    None => path.to_owned(),
+                },
+                None if path == "Self" || path == "self" => {
+                    current_item.clone().unwrap_or_else(|| path.to_owned())
+                }
+                None => path.to_owned(),
+            };
 
             if let Some(prim) = is_primitive(&path, TypeNS) {
-                let did = primitive_impl(cx, &path).ok_or(ErrorKind::ResolutionFailure)?;
-                return cx
-                    .tcx
-                    .associated_items(did)
-                    .filter_by_name_unhygienic(item_name)
-                    .next()
-                    .and_then(|item| match item.kind {
-                        ty::AssocKind::Method => Some("method"),
-                        _ => None,
-                    })
-                    .map(|out| (prim, Some(format!("{}#{}.{}", path, out, item_name))))
-                    .ok_or(ErrorKind::ResolutionFailure);
+                return self.resolve_primitive_item(
+                    prim,
+                    &path,
+                    item_name,
+                    ns,
+                    self_cx,
+                    extra_fragment,
+                );
             }
 
-            let (_, ty_res) = cx
-                .enter_resolver(|resolver| {
-                    resolver.resolve_str_path_error(DUMMY_SP, &path, TypeNS, module_id)
-                })
-                .map_err(|_| ErrorKind::ResolutionFailure)?;
-            if let Res::Err = ty_res {
-                return self.variant_field(path_str, current_item, module_id);
-            }
-            let ty_res = ty_res.map_id(|_| panic!("unexpected node_id"));
+            let ty_res = match self_cx {
+                Some(self_cx) => self_cx.res,
+                None => {
+                    let (_, ty_res) = cx
+                        .enter_resolver(|resolver| {
+                            resolver.resolve_str_path_error(DUMMY_SP, &path, TypeNS, module_id)
+                        })
+                        .map_err(|_| ErrorKind::ResolutionFailure)?;
+                    if let Res::Err = ty_res {
+                        return self.variant_field(path_str, ns, current_item, module_id);
+                    }
+                    ty_res.map_id(|_| panic!("unexpected node_id"))
+                }
+            };
+            // Type aliases don't have any impls of their own, so look the item up on the
+            // aliased type instead and link there.
+            let ty_res = match ty_res {
+                Res::Def(DefKind::TyAlias, did) => match cx.tcx.type_of(did).kind {
+                    ty::Adt(def, _) => adt_res(def),
+                    _ => ty_res,
+                },
+                _ => ty_res,
+            };
             match ty_res {
                 Res::Def(DefKind::Struct, did)
                 | Res::Def(DefKind::Union, did)
@@ -234,23 +336,23 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
                         .tcx
                         .inherent_impls(did)
                         .iter()
+                        .chain(self_cx.and_then(|self_cx| self_cx.impl_).iter())
                         .flat_map(|imp| cx.tcx.associated_items(*imp).in_definition_order())
                         .find(|item| item.ident.name == item_name);
                     if let Some(item) = item {
-                        let out = match item.kind {
-                            ty::AssocKind::Method if ns == ValueNS => "method",
-                            ty::AssocKind::Const if ns == ValueNS => "associatedconstant",
-                            _ => return self.variant_field(path_str, current_item, module_id),
+                        let out = match assoc_item_fragment(item, ns) {
+                            Some(out) => out,
+                            None => {
+                                return self.variant_field(path_str, ns, current_item, module_id);
+                            }
                         };
                         if extra_fragment.is_some() {
-                            Err(ErrorKind::AnchorFailure(if item.kind == ty::AssocKind::Method {
-                                "methods cannot be followed by anchors"
-                            } else {
-                                "associated constants cannot be followed by anchors"
-                            }))
+                            Err(ErrorKind::AnchorFailure(assoc_item_anchor_failure(item)))
                         } else {
                             Ok((ty_res, Some(format!("{}.{}", out, item_name))))
                         }
+                    } else if ns != ValueNS {
+                        Err(ErrorKind::ResolutionFailure)
                     } else {
                         match cx.tcx.type_of(did).kind {
                             ty::Adt(def, _) => {
@@ -283,13 +385,30 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
                                         ))
                                     }
                                 } else {
-                                    self.variant_field(path_str, current_item, module_id)
+                                    self.variant_field(path_str, ns, current_item, module_id)
                                 }
                             }
-                            _ => self.variant_field(path_str, current_item, module_id),
+                            _ => self.variant_field(path_str, ns, current_item, module_id),
                         }
                     }
                 }
+                Res::Def(DefKind::Variant, did) if ns == ValueNS => {
+                    // A field of a variant reached through a `use Enum::*` or similar import.
+                    let enum_did = cx.tcx.parent(did).ok_or(ErrorKind::ResolutionFailure)?;
+                    let variant = cx.tcx.adt_def(enum_did).variant_with_id(did);
+                    if !variant.fields.iter().any(|field| field.ident.name == item_name) {
+                        Err(ErrorKind::ResolutionFailure)
+                    } else if extra_fragment.is_some() {
+                        Err(ErrorKind::AnchorFailure(
+                            "variant fields cannot be followed by anchors",
+                        ))
+                    } else {
+                        Ok((
+                            Res::Def(DefKind::Enum, enum_did),
+                            Some(format!("variant.{}.field.{}", variant.ident, item_name)),
+                        ))
+                    }
+                }
                 Res::Def(DefKind::Trait, did) => {
                     let item = cx
                         .tcx
@@ -298,37 +417,203 @@ impl<'a, 'tcx> LinkCollector<'a, 'tcx> {
                         .map(|item| cx.tcx.associated_item(*item))
                         .find(|item| item.ident.name == item_name);
                     if let Some(item) = item {
-                        let kind = match item.kind {
-                            ty::AssocKind::Const if ns == ValueNS => "associatedconstant",
-                            ty::AssocKind::Type if ns == TypeNS => "associatedtype",
-                            ty::AssocKind::Method if ns == ValueNS => {
-                                if item.defaultness.has_value() { "method" } else { "tymethod" }
+                        let kind = match assoc_item_fragment(&item, ns) {
+                            Some(kind) => kind,
+                            None => {
+                                return self.variant_field(path_str, ns, current_item, module_id);
                             }
-                            _ => return self.variant_field(path_str, current_item, module_id),
                         };
 
                         if extra_fragment.is_some() {
-                            Err(ErrorKind::AnchorFailure(if item.kind == ty::AssocKind::Const {
-                                "associated constants cannot be followed by anchors"
-                            } else if item.kind == ty::AssocKind::Type {
-                                "associated types cannot be followed by anchors"
-                            } else {
-                                "methods cannot be followed by anchors"
-                            }))
+                            Err(ErrorKind::AnchorFailure(assoc_item_anchor_failure(&item)))
                         } else {
                             Ok((ty_res, Some(format!("{}.{}", kind, item_name))))
                         }
                     } else {
-                        self.variant_field(path_str, current_item, module_id)
+                        self.variant_field(path_str, ns, current_item, module_id)
                     }
                 }
-                _ => self.variant_field(path_str, current_item, module_id),
+                _ => self.variant_field(path_str, ns, current_item, module_id),
             }
         } else {
             debug!("attempting to resolve item without parent module: {}", path_str);
             Err(ErrorKind::ResolutionFailure)
         }
     }
+
+    /// Records a link that failed to resolve for the `--intra-doc-link-report` output.
+    ///
+    /// Like the `intra_doc_link_resolution_failure` lint, this only covers local items.
+    fn record_broken_link(
+        &mut self,
+        item: &Item,
+        path_str: &str,
+        dox: &str,
+        link_range: &Option<Range<usize>>,
+        kind: &'static str,
+        message: &str,
+        candidates: Vec<String>,
+    ) {
+        let cx = self.cx;
+        if cx.intra_doc_link_report.is_none() || !item.def_id.is_local() {
+            return;
+        }
+
+        let sp = link_range
+            .as_ref()
+            .and_then(|range| super::source_span_for_markdown_range(cx, dox, range, &item.attrs))
+            .or_else(|| span_of_attrs(&item.attrs))
+            .unwrap_or(item.source.span());
+        let loc = cx.sess().source_map().lookup_char_pos(sp.lo());
+
+        self.broken_links.push(BrokenLink {
+            file: loc.file.name.to_string(),
+            line: loc.line,
+            column: loc.col.to_usize() + 1,
+            item: cx.tcx.def_path_str(item.def_id),
+            link: path_str.to_owned(),
+            kind,
+            message: message.to_owned(),
+            candidates,
+        });
+    }
+
+    fn record_unresolved(
+        &mut self,
+        item: &Item,
+        path_str: &str,
+        dox: &str,
+        link_range: &Option<Range<usize>>,
+    ) {
+        self.record_broken_link(
+            item,
+            path_str,
+            dox,
+            link_range,
+            "unresolved",
+            "cannot be resolved",
+            vec![],
+        );
+    }
+
+    fn record_anchor_failure(
+        &mut self,
+        item: &Item,
+        path_str: &str,
+        dox: &str,
+        link_range: &Option<Range<usize>>,
+        msg: &str,
+    ) {
+        self.record_broken_link(item, path_str, dox, link_range, "anchor", msg, vec![]);
+    }
+
+    /// Writes the links recorded by `record_broken_link` to the `--intra-doc-link-report` file.
+    fn write_report(&self, path: &Path) {
+        let report =
+            serde_json::to_string_pretty(&self.broken_links).expect("failed serde conversion");
+        if let Err(e) = fs::write(path, report) {
+            self.cx
+                .sess()
+                .struct_err(&format!(
+                    "failed to write intra-doc link report to {}: {}",
+                    path.display(),
+                    e
+                ))
+                .emit();
+        }
+    }
+}
+
+/// Computes what `Self` refers to in the documentation of `item`.
+fn self_context(cx: &DocContext<'_>, item: &Item) -> Option<SelfContext> {
+    let tcx = cx.tcx;
+    let did = item.def_id;
+    // Synthesized auto trait and blanket impls don't exist as far as the compiler knows.
+    if cx.all_fake_def_ids.borrow().contains(&did) {
+        return None;
+    }
+    let res = |kind| Some(SelfContext { res: Res::Def(kind, did), impl_: None });
+
+    match item.inner {
+        StructItem(..) => res(DefKind::Struct),
+        UnionItem(..) => res(DefKind::Union),
+        EnumItem(..) => res(DefKind::Enum),
+        TraitItem(..) => res(DefKind::Trait),
+        ForeignTypeItem => res(DefKind::ForeignTy),
+        ImplItem(..) => impl_self_res(cx, did).map(|res| SelfContext { res, impl_: Some(did) }),
+        VariantItem(..) => {
+            let parent = tcx.parent(did)?;
+            Some(SelfContext { res: Res::Def(DefKind::Enum, parent), impl_: None })
+        }
+        StructFieldItem(..) => {
+            let parent = tcx.parent(did)?;
+            match tcx.def_kind(parent)? {
+                DefKind::Variant => {
+                    let parent = tcx.parent(parent)?;
+                    Some(SelfContext { res: Res::Def(DefKind::Enum, parent), impl_: None })
+                }
+                kind => Some(SelfContext { res: Res::Def(kind, parent), impl_: None }),
+            }
+        }
+        MethodItem(..)
+        | TyMethodItem(..)
+        | AssocConstItem(..)
+        | AssocTypeItem(..)
+        | TypedefItem(_, true) => match tcx.opt_associated_item(did)?.container {
+            ty::ImplContainer(impl_did) => {
+                impl_self_res(cx, impl_did).map(|res| SelfContext { res, impl_: Some(impl_did) })
+            }
+            ty::TraitContainer(trait_did) => {
+                Some(SelfContext { res: Res::Def(DefKind::Trait, trait_did), impl_: None })
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Returns the `Res` of the self type of an impl, if it is something links can point to.
+fn impl_self_res(cx: &DocContext<'_>, impl_did: DefId) -> Option<Res> {
+    let self_ty = cx.tcx.type_of(impl_did);
+    match self_ty.kind {
+        ty::Adt(def, _) => Some(adt_res(def)),
+        ty::Foreign(did) => Some(Res::Def(DefKind::ForeignTy, did)),
+        ty::Bool | ty::Char | ty::Int(_) | ty::Uint(_) | ty::Float(_) | ty::Str => {
+            is_primitive(&self_ty.to_string(), TypeNS)
+        }
+        _ => None,
+    }
+}
+
+fn adt_res(def: &ty::AdtDef) -> Res {
+    let kind = match def.adt_kind() {
+        ty::AdtKind::Struct => DefKind::Struct,
+        ty::AdtKind::Union => DefKind::Union,
+        ty::AdtKind::Enum => DefKind::Enum,
+    };
+    Res::Def(kind, def.did)
+}
+
+/// Returns the kind of URL fragment used for an associated item, if it lives in `ns`.
+fn assoc_item_fragment(item: &ty::AssocItem, ns: Namespace) -> Option<&'static str> {
+    match item.kind {
+        ty::AssocKind::Const if ns == ValueNS => Some("associatedconstant"),
+        ty::AssocKind::Type if ns == TypeNS => Some("associatedtype"),
+        ty::AssocKind::Method if ns == ValueNS => match item.container {
+            ty::TraitContainer(_) if !item.defaultness.has_value() => Some("tymethod"),
+            _ => Some("method"),
+        },
+        _ => None,
+    }
+}
+
+fn assoc_item_anchor_failure(item: &ty::AssocItem) -> &'static str {
+    match item.kind {
+        ty::AssocKind::Const => "associated constants cannot be followed by anchors",
+        ty::AssocKind::Type | ty::AssocKind::OpaqueTy => {
+            "associated types cannot be followed by anchors"
+        }
+        ty::AssocKind::Method => "methods cannot be followed by anchors",
+    }
 }
 
 impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
@@ -391,6 +676,8 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
 
         look_for_tests(&cx, &dox, &item, true);
 
+        let self_cx = if dox.is_empty() { None } else { self_context(cx, &item) };
+
         for (ori_link, link_range) in markdown_links(&dox) {
             // Bail early for real links.
             if ori_link.contains('/') {
@@ -405,6 +692,15 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
             let link = ori_link.replace("`", "");
             let parts = link.split('#').collect::<Vec<_>>();
             let (link, extra_fragment) = if parts.len() > 2 {
+                self.record_broken_link(
+                    &item,
+                    &link,
+                    &dox,
+                    &link_range,
+                    "anchor",
+                    "only one `#` is allowed in a link",
+                    vec![],
+                );
                 build_diagnostic(
                     cx,
                     &item,
@@ -483,10 +779,17 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
 
                 match kind {
                     Some(ns @ ValueNS) => {
-                        match self.resolve(path_str, ns, &current_item, base_node, &extra_fragment)
-                        {
+                        match self.resolve(
+                            path_str,
+                            ns,
+                            &current_item,
+                            self_cx,
+                            base_node,
+                            &extra_fragment,
+                        ) {
                             Ok(res) => res,
                             Err(ErrorKind::ResolutionFailure) => {
+                                self.record_unresolved(&item, path_str, &dox, &link_range);
                                 resolution_failure(cx, &item, path_str, &dox, link_range);
                                 // This could just be a normal link or a broken link
                                 // we could potentially check if something is
@@ -494,21 +797,42 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                                 continue;
                             }
                             Err(ErrorKind::AnchorFailure(msg)) => {
+                                self.record_anchor_failure(
+                                    &item,
+                                    &ori_link,
+                                    &dox,
+                                    &link_range,
+                                    msg,
+                                );
                                 anchor_failure(cx, &item, &ori_link, &dox, link_range, msg);
                                 continue;
                             }
                         }
                     }
                     Some(ns @ TypeNS) => {
-                        match self.resolve(path_str, ns, &current_item, base_node, &extra_fragment)
-                        {
+                        match self.resolve(
+                            path_str,
+                            ns,
+                            &current_item,
+                            self_cx,
+                            base_node,
+                            &extra_fragment,
+                        ) {
                             Ok(res) => res,
                             Err(ErrorKind::ResolutionFailure) => {
+                                self.record_unresolved(&item, path_str, &dox, &link_range);
                                 resolution_failure(cx, &item, path_str, &dox, link_range);
                                 // This could just be a normal link.
                                 continue;
                             }
                             Err(ErrorKind::AnchorFailure(msg)) => {
+                                self.record_anchor_failure(
+                                    &item,
+                                    &ori_link,
+                                    &dox,
+                                    &link_range,
+                                    msg,
+                                );
                                 anchor_failure(cx, &item, &ori_link, &dox, link_range, msg);
                                 continue;
                             }
@@ -523,10 +847,18 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                                 path_str,
                                 TypeNS,
                                 &current_item,
+                                self_cx,
                                 base_node,
                                 &extra_fragment,
                             ) {
                                 Err(ErrorKind::AnchorFailure(msg)) => {
+                                    self.record_anchor_failure(
+                                        &item,
+                                        &ori_link,
+                                        &dox,
+                                        &link_range,
+                                        msg,
+                                    );
                                     anchor_failure(cx, &item, &ori_link, &dox, link_range, msg);
                                     continue;
                                 }
@@ -536,10 +868,18 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                                 path_str,
                                 ValueNS,
                                 &current_item,
+                                self_cx,
                                 base_node,
                                 &extra_fragment,
                             ) {
                                 Err(ErrorKind::AnchorFailure(msg)) => {
+                                    self.record_anchor_failure(
+                                        &item,
+                                        &ori_link,
+                                        &dox,
+                                        &link_range,
+                                        msg,
+                                    );
                                     anchor_failure(cx, &item, &ori_link, &dox, link_range, msg);
                                     continue;
                                 }
@@ -563,6 +903,7 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                         };
 
                         if candidates.is_empty() {
+                            self.record_unresolved(&item, path_str, &dox, &link_range);
                             resolution_failure(cx, &item, path_str, &dox, link_range);
                             // this could just be a normal link
                             continue;
@@ -572,6 +913,19 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                         if is_unambiguous {
                             candidates.present_items().next().unwrap()
                         } else {
+                            self.record_broken_link(
+                                &item,
+                                path_str,
+                                &dox,
+                                &link_range,
+                                "ambiguous",
+                                "ambiguous link",
+                                candidates
+                                    .clone()
+                                    .present_items()
+                                    .map(|(res, _)| res.descr().to_owned())
+                                    .collect(),
+                            );
                             ambiguity_error(
                                 cx,
                                 &item,
@@ -587,6 +941,7 @@ impl<'a, 'tcx> DocFolder for LinkCollector<'a, 'tcx> {
                         if let Some(res) = macro_resolve(cx, path_str) {
                             (res, extra_fragment)
                         } else {
+                            self.record_unresolved(&item, path_str, &dox, &link_range);
                             resolution_failure(cx, &item, path_str, &dox, link_range);
                             continue;
                         }
@@ -880,8 +1235,6 @@ fn handle_variant(
     res: Res,
     extra_fragment: &Option<String>,
 ) -> Result<(Res, Option<String>), ErrorKind> {
-    use rustc::ty::DefIdTree;
-
     if extra_fragment.is_some() {
         return Err(ErrorKind::AnchorFailure("variants cannot be followed by anchors"));
     }
@@ -919,6 +1272,24 @@ fn is_primitive(path_str: &str, ns: Namespace) -> Option<Res> {
     if ns == TypeNS { PRIMITIVES.iter().find(|x| x.0 == path_str).map(|x| x.1) } else { None }
 }
 
+fn primitive_name(res: Res) -> Option<&'static str> {
+    PRIMITIVES.iter().find(|x| x.1 == res).map(|x| x.0)
+}
+
+/// Returns all the impls that define associated items on a primitive type.
+///
+/// Besides the impl in `core`, some primitives get more methods from `alloc` and `std`.
+fn primitive_impls(cx: &DocContext<'_>, path_str: &str) -> Vec<DefId> {
+    let tcx = cx.tcx;
+    let extra = match path_str {
+        "str" => tcx.lang_items().str_alloc_impl(),
+        "f32" => tcx.lang_items().f32_runtime_impl(),
+        "f64" => tcx.lang_items().f64_runtime_impl(),
+        _ => None,
+    };
+    primitive_impl(cx, path_str).into_iter().chain(extra).collect()
+}
+
 fn primitive_impl(cx: &DocContext<'_>, path_str: &str) -> Option<DefId> {
     let tcx = cx.tcx;
     match path_str {
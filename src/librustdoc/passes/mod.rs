@@ -4,6 +4,7 @@
 use rustc::lint;
 use rustc::middle::privacy::AccessLevels;
 use rustc_hir::def_id::{DefId, DefIdSet};
+use rustc_span::source_map::SourceMap;
 use rustc_span::{InnerSpan, Span, DUMMY_SP};
 use std::mem;
 use std::ops::Range;
@@ -381,6 +382,16 @@ crate fn source_span_for_markdown_range(
     markdown: &str,
     md_range: &Range<usize>,
     attrs: &clean::Attributes,
+) -> Option<Span> {
+    source_map_span_for_markdown_range(cx.sess().source_map(), markdown, md_range, attrs)
+}
+
+/// Like `source_span_for_markdown_range`, for when the `DocContext` is no longer around.
+crate fn source_map_span_for_markdown_range(
+    source_map: &SourceMap,
+    markdown: &str,
+    md_range: &Range<usize>,
+    attrs: &clean::Attributes,
 ) -> Option<Span> {
     let is_all_sugared_doc = attrs.doc_strings.iter().all(|frag| match frag {
         clean::DocFragment::SugaredDoc(..) => true,
@@ -391,7 +402,7 @@ crate fn source_span_for_markdown_range(
         return None;
     }
 
-    let snippet = cx.sess().source_map().span_to_snippet(span_of_attrs(attrs)?).ok()?;
+    let snippet = source_map.span_to_snippet(span_of_attrs(attrs)?).ok()?;
 
     let starting_line = markdown[..md_range.start].matches('\n').count();
     let ending_line = starting_line + markdown[md_range.start..md_range.end].matches('\n').count();
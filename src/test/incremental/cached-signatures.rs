@@ -0,0 +1,63 @@
+// Test that the signatures, predicates and borrowck results cached on disk are loaded back
+// unchanged when a query reading them is re-executed: `-Z incremental-verify-ich` checks the hash
+// of every green result and `-Z incremental-verify-cache` recomputes the loaded ones.
+
+// revisions: rpass1 rpass2
+// compile-flags: -Z query-dep-graph -Z incremental-verify-ich -Z incremental-verify-cache=100
+
+#![feature(rustc_attrs)]
+
+pub trait Base {
+    fn base(&self) -> u32;
+}
+
+#[rustc_clean(label = "super_predicates_of", cfg = "rpass2")]
+pub trait Doubled: Base {
+    fn doubled(&self) -> u32 {
+        self.base() * 2
+    }
+}
+
+#[rustc_clean(label = "inferred_outlives_of", cfg = "rpass2")]
+pub struct Wrapper<'a, T> {
+    value: &'a T,
+}
+
+impl Base for u32 {
+    fn base(&self) -> u32 {
+        *self
+    }
+}
+
+#[rustc_clean(label = "impl_trait_ref", cfg = "rpass2")]
+impl Doubled for u32 {}
+
+#[rustc_clean(label = "fn_sig", cfg = "rpass2")]
+#[rustc_clean(label = "explicit_predicates_of", cfg = "rpass2")]
+#[rustc_clean(label = "predicates_defined_on", cfg = "rpass2")]
+#[rustc_clean(label = "mir_borrowck", cfg = "rpass2")]
+pub fn callee<'a, T>(wrapper: Wrapper<'a, T>) -> u32
+where
+    T: Doubled + Clone,
+{
+    wrapper.value.clone().doubled()
+}
+
+#[rustc_dirty(label = "typeck_tables_of", cfg = "rpass2")]
+pub fn caller() -> u32 {
+    #[cfg(rpass1)]
+    let value = 1;
+
+    #[cfg(rpass2)]
+    let value = 2;
+
+    callee(Wrapper { value: &value })
+}
+
+fn main() {
+    #[cfg(rpass1)]
+    assert_eq!(caller(), 2);
+
+    #[cfg(rpass2)]
+    assert_eq!(caller(), 4);
+}
@@ -0,0 +1,26 @@
+// Tests that the MIR inliner doesn't cause query cycles between mutually
+// recursive local functions in incremental mode, where it can't rely on the
+// order of their definitions.
+
+// revisions:rpass1 rpass2
+// compile-flags: -Zmir-opt-level=2
+
+#[inline]
+fn even(n: u32) -> bool {
+    if n == 0 { true } else { odd(n - 1) }
+}
+
+#[inline]
+fn odd(n: u32) -> bool {
+    if n == 0 { false } else { even(n - 1) }
+}
+
+#[inline]
+fn leaf(n: u32) -> u32 {
+    n + 1
+}
+
+fn main() {
+    assert!(even(leaf(3)));
+    assert!(odd(leaf(4)));
+}
@@ -0,0 +1,38 @@
+// Test that the query results loaded from the incremental compilation cache
+// are the same as freshly computed ones, when `-Z incremental-verify-cache`
+// recomputes all of them.
+
+// revisions: rpass1 rpass2
+// compile-flags: -Z incremental-verify-cache=100
+
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+pub trait Norm {
+    fn norm(&self) -> f32;
+}
+
+impl Norm for Point {
+    fn norm(&self) -> f32 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+}
+
+fn scale<T: Norm>(value: &T, factor: f32) -> f32 {
+    value.norm() * factor
+}
+
+fn main() {
+    let point = Point { x: 3.0, y: 4.0 };
+
+    #[cfg(rpass1)]
+    let factor = 1.0;
+
+    #[cfg(rpass2)]
+    let factor = 2.0;
+
+    let closure = |p: &Point| scale(p, factor);
+    assert_eq!(closure(&point), 5.0 * factor);
+}
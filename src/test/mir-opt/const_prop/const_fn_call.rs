@@ -0,0 +1,62 @@
+// compile-flags: -O
+
+// Tests that the results of calls to `const fn`s with constant arguments, and the known fields
+// of aggregates whose other fields are not known, are propagated.
+
+#[inline(never)]
+const fn double(x: u32) -> u32 {
+    x * 2
+}
+
+fn partial(a: u32) -> u32 {
+    (a, 3u32).1 + 1
+}
+
+fn main() {
+    let x = double(4) + 1;
+    partial(x);
+}
+
+// END RUST SOURCE
+// START rustc.main.ConstProp.before.mir
+//  bb0: {
+//      ...
+//      _2 = const double(const 4u32) -> bb1;
+//  }
+//  bb1: {
+//      _1 = Add(move _2, const 1u32);
+//      ...
+//  }
+// END rustc.main.ConstProp.before.mir
+// START rustc.main.ConstProp.after.mir
+//  bb0: {
+//      ...
+//      _2 = const double(const 4u32) -> bb1;
+//  }
+//  bb1: {
+//      _1 = const 9u32;
+//      ...
+//  }
+// END rustc.main.ConstProp.after.mir
+// START rustc.partial.ConstProp.before.mir
+//  bb0: {
+//      ...
+//      _3 = (move _4, const 3u32);
+//      ...
+//      _2 = (_3.1: u32);
+//      ...
+//      _0 = Add(move _2, const 1u32);
+//      ...
+//  }
+// END rustc.partial.ConstProp.before.mir
+// START rustc.partial.ConstProp.after.mir
+//  bb0: {
+//      ...
+//      _3 = (move _4, const 3u32);
+//      ...
+//      _2 = const 3u32;
+//      ...
+//      _0 = const 4u32;
+//      ...
+//  }
+// END rustc.partial.ConstProp.after.mir
@@ -1,4 +1,5 @@
 // compile-flags: -C overflow-checks=on
+// compile-flags: -Zmir-enable-passes=-ScalarReplacementOfAggregates,-DeadStoreElimination
 
 struct Point {
     x: u32,
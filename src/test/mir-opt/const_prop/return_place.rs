@@ -1,4 +1,5 @@
 // compile-flags: -C overflow-checks=on
+// compile-flags: -Zmir-enable-passes=-ScalarReplacementOfAggregates,-DeadStoreElimination
 
 fn add() -> u32 {
     2 + 2
@@ -0,0 +1,88 @@
+// Test that stores to locals which are never read afterwards are removed, including stores
+// which are overwritten in another block, and writes to a part of a dead local. Setting the
+// discriminant of an enum doesn't make the payload of its variant dead.
+
+// compile-flags: -O
+// compile-flags: -Zmir-enable-passes=-ScalarReplacementOfAggregates,-DestinationPropagation
+
+fn overwritten(c: bool) -> i32 {
+    let mut x = 1;
+    if c {
+        x = 2;
+    } else {
+        x = 3;
+    }
+    x
+}
+
+fn partial(a: u32) -> u32 {
+    let mut p = (a, a);
+    let r = p.0;
+    p.1 = 5;
+    r
+}
+
+fn variant_through_return(x: u32) -> Option<u32> {
+    Some(x + 1)
+}
+
+fn variant_through_local(x: u32) -> Option<u32> {
+    let o = Some(x + 1);
+    o
+}
+
+fn main() {
+    overwritten(true);
+    partial(1);
+    variant_through_return(1);
+    variant_through_local(1);
+}
+
+// END RUST SOURCE
+// START rustc.overwritten.DeadStoreElimination.before.mir
+//  bb0: {
+//      StorageLive(_2);
+//      _2 = const 1i32;
+//      ...
+//  }
+// END rustc.overwritten.DeadStoreElimination.before.mir
+// START rustc.overwritten.DeadStoreElimination.after.mir
+//  bb0: {
+//      StorageLive(_2);
+//      nop;
+//      ...
+//  }
+// END rustc.overwritten.DeadStoreElimination.after.mir
+// START rustc.partial.DeadStoreElimination.before.mir
+//  bb0: {
+//      ...
+//      (_2.1: u32) = const 5u32;
+//      ...
+//  }
+// END rustc.partial.DeadStoreElimination.before.mir
+// START rustc.partial.DeadStoreElimination.after.mir
+//  bb0: {
+//      ...
+//      _5 = (_2.0: u32);
+//      nop;
+//      ...
+//  }
+// END rustc.partial.DeadStoreElimination.after.mir
+// START rustc.variant_through_return.DeadStoreElimination.after.mir
+//  bb0: {
+//      ...
+//      ((_0 as Some).0: u32) = move _2;
+//      discriminant(_0) = 1;
+//      ...
+//  }
+// END rustc.variant_through_return.DeadStoreElimination.after.mir
+// START rustc.variant_through_local.DeadStoreElimination.after.mir
+//  bb0: {
+//      ...
+//      ((_2 as Some).0: u32) = move _3;
+//      discriminant(_2) = 1;
+//      ...
+//      _0 = move _2;
+//      ...
+//  }
+// END rustc.variant_through_local.DeadStoreElimination.after.mir
@@ -0,0 +1,163 @@
+// Test that moving a value through several locals before returning it doesn't copy it, and that
+// locals are not merged when both are borrowed, when one of them is changed while the other is
+// still used afterwards, or when one is assigned the other's value from the previous iteration.
+
+// compile-flags: -O
+
+struct Big([u64; 8]);
+
+struct Tagged {
+    tag: u64,
+    data: [u64; 8],
+}
+
+#[derive(Clone, Copy)]
+struct Pair {
+    x: u64,
+    y: u64,
+}
+
+#[inline(never)]
+fn make() -> Big {
+    Big([0; 8])
+}
+
+#[inline(never)]
+fn make_tagged(tag: u64) -> Tagged {
+    Tagged { tag, data: [0; 8] }
+}
+
+#[inline(never)]
+fn make_pair() -> Pair {
+    Pair { x: 1, y: 2 }
+}
+
+#[inline(never)]
+fn observe(_: &Big) {}
+
+#[inline(never)]
+fn consume(_: u64) {}
+
+fn test() -> Big {
+    let a = make();
+    let b = a;
+    b
+}
+
+fn borrowed() -> Big {
+    let a = make();
+    observe(&a);
+    let b = a;
+    observe(&b);
+    b
+}
+
+fn read_after_copy() -> Pair {
+    let a = make_pair();
+    let mut b = a;
+    b.x = 3;
+    consume(a.x);
+    b
+}
+
+fn loop_carried(n: usize) -> Tagged {
+    let mut prev = make_tagged(0);
+    let mut cur = make_tagged(1);
+    let mut next;
+    let mut i = 0;
+    while i < n {
+        next = make_tagged(prev.tag + cur.tag);
+        prev = cur;
+        cur = next;
+        i += 1;
+    }
+    prev
+}
+
+fn main() {
+    test();
+    borrowed();
+    read_after_copy();
+    loop_carried(3);
+}
+
+// END RUST SOURCE
+// START rustc.test.DestinationPropagation.before.mir
+//  bb0: {
+//      ...
+//      _1 = const make() -> bb1;
+//  }
+//  bb1: {
+//      ...
+//      _2 = move _1;
+//      _0 = move _2;
+//      ...
+//      return;
+//  }
+// END rustc.test.DestinationPropagation.before.mir
+// START rustc.test.DestinationPropagation.after.mir
+//  bb0: {
+//      nop;
+//      _0 = const make() -> bb1;
+//  }
+//  bb1: {
+//      nop;
+//      nop;
+//      nop;
+//      nop;
+//      nop;
+//      return;
+//  }
+// END rustc.test.DestinationPropagation.after.mir
+// START rustc.borrowed.DestinationPropagation.after.mir
+//  bb1: {
+//      ...
+//      _3 = &_1;
+//      _2 = const observe(move _3) -> bb2;
+//  }
+//  bb2: {
+//      ...
+//      _4 = move _1;
+//      ...
+//      _6 = &_4;
+//      _5 = const observe(move _6) -> bb3;
+//  }
+//  bb3: {
+//      ...
+//      _0 = move _4;
+//      ...
+//      return;
+//  }
+// END rustc.borrowed.DestinationPropagation.after.mir
+// START rustc.read_after_copy.DestinationPropagation.before.mir
+//  bb1: {
+//      ...
+//      _2 = _1;
+//      (_2.0: u64) = const 3u64;
+//      ...
+//      _4 = (_1.0: u64);
+//      _3 = const consume(move _4) -> bb2;
+//  }
+// END rustc.read_after_copy.DestinationPropagation.before.mir
+// START rustc.read_after_copy.DestinationPropagation.after.mir
+//  bb1: {
+//      ...
+//      _0 = _1;
+//      (_0.0: u64) = const 3u64;
+//      ...
+//      _4 = (_1.0: u64);
+//      _3 = const consume(move _4) -> bb2;
+//  }
+// END rustc.read_after_copy.DestinationPropagation.after.mir
+// START rustc.loop_carried.DestinationPropagation.before.mir
+//  ...
+//  _2 = move _3;
+//  _3 = move _4;
+//  ...
+// END rustc.loop_carried.DestinationPropagation.before.mir
+// START rustc.loop_carried.DestinationPropagation.after.mir
+//  ...
+//  _0 = move _3;
+//  _3 = move _4;
+//  ...
+// END rustc.loop_carried.DestinationPropagation.after.mir
@@ -0,0 +1,88 @@
+// Test that computations which are repeated on the same values are only done once, and that the
+// length of a slice is only reused when it is read through a shared reference which can't change.
+
+// compile-flags: -O
+
+fn test(x: u32, y: u32) -> u32 {
+    let a = x * y;
+    let b = x * y;
+    a + b
+}
+
+fn len_through_ref(s: &[u32]) -> u32 {
+    s[0] + s[1]
+}
+
+fn len_through_raw(p: *mut &[u32], other: &'static [u32]) -> u32 {
+    unsafe {
+        let a = (*p)[0];
+        *p = other;
+        let b = (*p)[0];
+        a + b
+    }
+}
+
+fn main() {
+    test(2, 3);
+    len_through_ref(&[1, 2]);
+    let mut s: &[u32] = &[1, 2];
+    len_through_raw(&mut s, &[3]);
+}
+
+// END RUST SOURCE
+// START rustc.test.GlobalValueNumbering.before.mir
+//  bb0: {
+//      ...
+//      _3 = Mul(move _4, move _5);
+//      ...
+//      _6 = Mul(move _7, move _8);
+//      ...
+//  }
+// END rustc.test.GlobalValueNumbering.before.mir
+// START rustc.test.GlobalValueNumbering.after.mir
+//  bb0: {
+//      ...
+//      _3 = Mul(move _4, move _5);
+//      ...
+//      _6 = _3;
+//      ...
+//  }
+// END rustc.test.GlobalValueNumbering.after.mir
+// START rustc.len_through_ref.GlobalValueNumbering.before.mir
+//  bb0: {
+//      ...
+//      _4 = Len((*_1));
+//      ...
+//  }
+//  bb1: {
+//      ...
+//      _8 = Len((*_1));
+//      ...
+//  }
+// END rustc.len_through_ref.GlobalValueNumbering.before.mir
+// START rustc.len_through_ref.GlobalValueNumbering.after.mir
+//  bb0: {
+//      ...
+//      _4 = Len((*_1));
+//      ...
+//  }
+//  bb1: {
+//      ...
+//      _8 = _4;
+//      ...
+//  }
+// END rustc.len_through_ref.GlobalValueNumbering.after.mir
+// START rustc.len_through_raw.GlobalValueNumbering.after.mir
+//  bb0: {
+//      ...
+//      _5 = Len((*(*_1)));
+//      ...
+//  }
+//  bb1: {
+//      ...
+//      (*_1) = move _7;
+//      ...
+//      _10 = Len((*(*_1)));
+//      ...
+//  }
+// END rustc.len_through_raw.GlobalValueNumbering.after.mir
@@ -33,11 +33,11 @@ fn foo<T: Copy>(_t: T, q: &i32) -> i32 {
 //         scope 2 {
 //             debug r => _8;
 //             debug _s => _9;
+//             scope 3 {
+//                 debug variable => _8;
+//             }
 //         }
 //     }
-//     scope 3 {
-//         debug variable => _8;
-//     }
 //     bb0: {
 //         ...
 //         _3 = [closure@foo::<T>::{{closure}}#0];
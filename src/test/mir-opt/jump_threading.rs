@@ -0,0 +1,91 @@
+// Test that a `match` on an enum which was just built jumps directly to the right arm.
+
+// compile-flags: -O
+
+fn option_chain(x: Option<u32>) -> Option<u32> {
+    let y = match x {
+        Some(v) => Some(v + 1),
+        None => None,
+    };
+    match y {
+        Some(v) => Some(v * 2),
+        None => None,
+    }
+}
+
+fn result_chain(x: u32) -> Result<u32, ()> {
+    let r = if x > 10 { Ok(x) } else { Err(()) };
+    let v = r?;
+    Ok(v + 1)
+}
+
+fn main() {
+    option_chain(Some(1));
+    result_chain(1);
+}
+
+// END RUST SOURCE
+// START rustc.option_chain.JumpThreading.before.mir
+//  bb2: {
+//      discriminant(_2) = 0;
+//      goto -> bb4;
+//  }
+//  bb3: {
+//      ...
+//      discriminant(_2) = 1;
+//      ...
+//      goto -> bb4;
+//  }
+//  bb4: {
+//      _5 = discriminant(_2);
+//      switchInt(move _5) -> [0isize: bb6, 1isize: bb7, otherwise: bb5];
+//  }
+// END rustc.option_chain.JumpThreading.before.mir
+// START rustc.option_chain.JumpThreading.after.mir
+//  bb2: {
+//      discriminant(_2) = 0;
+//      goto -> bb8;
+//  }
+//  bb3: {
+//      ...
+//      discriminant(_2) = 1;
+//      ...
+//      goto -> bb9;
+//  }
+//  ...
+//  bb8: {
+//      _5 = discriminant(_2);
+//      goto -> bb6;
+//  }
+//  bb9: {
+//      _5 = discriminant(_2);
+//      goto -> bb7;
+//  }
+// END rustc.option_chain.JumpThreading.after.mir
+// START rustc.result_chain.JumpThreading.before.mir
+//  bb2: {
+//      ...
+//      discriminant(_2) = 0;
+//      goto -> bb3;
+//  }
+//  bb3: {
+//      ...
+//      _5 = move _2;
+//      _6 = discriminant(_5);
+//      switchInt(move _6) -> [0isize: bb5, 1isize: bb6, otherwise: bb4];
+//  }
+// END rustc.result_chain.JumpThreading.before.mir
+// START rustc.result_chain.JumpThreading.after.mir
+//  bb2: {
+//      ...
+//      discriminant(_2) = 0;
+//      goto -> bb8;
+//  }
+//  ...
+//  bb8: {
+//      ...
+//      _5 = move _2;
+//      _6 = discriminant(_5);
+//      goto -> bb5;
+//  }
+// END rustc.result_chain.JumpThreading.after.mir
@@ -0,0 +1,64 @@
+// Test that bounds and overflow checks which can't fail are removed: the second check of the
+// same index into a slice, and the checks of an index which is compared with the length of an
+// array in the loop condition.
+
+// compile-flags: -O -C overflow-checks=on -Zmir-enable-passes=-GlobalValueNumbering
+
+fn get_twice(s: &[u32], i: usize) -> u32 {
+    s[i] ^ s[i]
+}
+
+fn xor_all(a: [u32; 4]) -> u32 {
+    let mut x = 0;
+    let mut i = 0;
+    while i < 4 {
+        x ^= a[i];
+        i += 1;
+    }
+    x
+}
+
+fn main() {
+    get_twice(&[1, 2], 1);
+    xor_all([1, 2, 3, 4]);
+}
+
+// END RUST SOURCE
+// START rustc.get_twice.RemoveRedundantChecks.before.mir
+//  bb0: {
+//      ...
+//      assert(move _6, "index out of bounds: the len is move _5 but the index is _4") -> bb1;
+//  }
+//  bb1: {
+//      ...
+//      assert(move _10, "index out of bounds: the len is move _9 but the index is _8") -> bb2;
+//  }
+// END rustc.get_twice.RemoveRedundantChecks.before.mir
+// START rustc.get_twice.RemoveRedundantChecks.after.mir
+//  bb0: {
+//      ...
+//      assert(move _6, "index out of bounds: the len is move _5 but the index is _4") -> bb1;
+//  }
+//  bb1: {
+//      ...
+//      goto -> bb2;
+//  }
+// END rustc.get_twice.RemoveRedundantChecks.after.mir
+// START rustc.xor_all.RemoveRedundantChecks.before.mir
+//  ...
+//  _9 = Lt(_7, _8);
+//  assert(move _9, "index out of bounds: the len is move _8 but the index is _7") -> bb4;
+//  ...
+//  _10 = CheckedAdd(_3, const 1usize);
+//  assert(!move (_10.1: bool), "attempt to add with overflow") -> bb5;
+//  ...
+// END rustc.xor_all.RemoveRedundantChecks.before.mir
+// START rustc.xor_all.RemoveRedundantChecks.after.mir
+//  ...
+//  _9 = Lt(_7, _8);
+//  goto -> bb4;
+//  ...
+//  _10 = CheckedAdd(_3, const 1usize);
+//  goto -> bb5;
+//  ...
+// END rustc.xor_all.RemoveRedundantChecks.after.mir
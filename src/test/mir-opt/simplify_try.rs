@@ -1,3 +1,5 @@
+// compile-flags: -Zmir-enable-passes=-DeadStoreElimination
+
 fn try_identity(x: Result<u32, i32>) -> Result<u32, i32> {
     let y = x?;
     Ok(y)
@@ -0,0 +1,126 @@
+// Test that tuples and structs which are only accessed field by field are split into one local
+// per field, that a struct moved into such a tuple is kept whole, and so are borrowed ones, called
+// closures and structs with a destructor.
+
+// compile-flags: -O
+
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+struct Guard {
+    x: u32,
+    y: u32,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {}
+}
+
+fn pair(a: u32, b: u32) -> u32 {
+    let t = (a, b);
+    t.0 ^ t.1
+}
+
+fn nested(a: u32, b: u32) -> u32 {
+    let p = (Point { x: a, y: b }, a);
+    p.0.x ^ p.1
+}
+
+fn borrowed(a: u32, b: u32) -> u32 {
+    let p = Point { x: a, y: b };
+    let r = &p;
+    r.x
+}
+
+fn called_closure(a: u32, b: u32) -> u32 {
+    let f = move |x: u32| x ^ a;
+    f(b)
+}
+
+fn dropped(a: u32, b: u32) -> u32 {
+    let g = Guard { x: a, y: b };
+    g.x ^ g.y
+}
+
+fn main() {
+    pair(1, 2);
+    nested(1, 2);
+    borrowed(1, 2);
+    called_closure(1, 2);
+    dropped(1, 2);
+}
+
+// END RUST SOURCE
+// START rustc.pair.ScalarReplacementOfAggregates.before.mir
+//  bb0: {
+//      StorageLive(_3);
+//      ...
+//      (_3.0: u32) = move _4;
+//      (_3.1: u32) = move _5;
+//      ...
+//      _6 = (_3.0: u32);
+//      ...
+//      _7 = (_3.1: u32);
+//      ...
+//  }
+// END rustc.pair.ScalarReplacementOfAggregates.before.mir
+// START rustc.pair.ScalarReplacementOfAggregates.after.mir
+//  bb0: {
+//      StorageLive(_8);
+//      StorageLive(_9);
+//      ...
+//      _8 = move _4;
+//      _9 = move _5;
+//      ...
+//      _6 = _8;
+//      ...
+//      _7 = _9;
+//      ...
+//  }
+// END rustc.pair.ScalarReplacementOfAggregates.after.mir
+// START rustc.nested.ScalarReplacementOfAggregates.after.mir
+//  bb0: {
+//      ...
+//      _10 = move _4;
+//      ...
+//      _11 = move _7;
+//      ...
+//      _8 = (_10.0: u32);
+//      ...
+//      _9 = _11;
+//      ...
+//  }
+// END rustc.nested.ScalarReplacementOfAggregates.after.mir
+// START rustc.borrowed.ScalarReplacementOfAggregates.after.mir
+//  bb0: {
+//      ...
+//      (_3.0: u32) = move _4;
+//      (_3.1: u32) = move _5;
+//      ...
+//      _6 = &_3;
+//      ...
+//  }
+// END rustc.borrowed.ScalarReplacementOfAggregates.after.mir
+// START rustc.called_closure.ScalarReplacementOfAggregates.after.mir
+//  bb0: {
+//      ...
+//      (_3.0: u32) = move _4;
+//      ...
+//      _5 = &_3;
+//      ...
+//  }
+// END rustc.called_closure.ScalarReplacementOfAggregates.after.mir
+// START rustc.dropped.ScalarReplacementOfAggregates.after.mir
+//  bb0: {
+//      ...
+//      (_3.0: u32) = move _4;
+//      (_3.1: u32) = move _5;
+//      ...
+//      _6 = (_3.0: u32);
+//      ...
+//      _7 = (_3.1: u32);
+//      ...
+//  }
+// END rustc.dropped.ScalarReplacementOfAggregates.after.mir
@@ -1,3 +1,5 @@
+// compile-flags: -Zmir-enable-passes=-DeadStoreElimination
+
 #![feature(never_type)]
 
 pub enum Void {}
@@ -0,0 +1,9 @@
+#![crate_type = "rlib"]
+
+pub fn bar() -> u32 {
+    1
+}
+
+pub fn baz() -> u32 {
+    2
+}
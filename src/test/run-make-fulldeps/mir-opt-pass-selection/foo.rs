@@ -0,0 +1,8 @@
+fn add(x: u32) -> u32 {
+    let y = 2 + 3;
+    x + y
+}
+
+fn main() {
+    assert_eq!(add(1), 6);
+}
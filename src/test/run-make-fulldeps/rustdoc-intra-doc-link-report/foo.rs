@@ -0,0 +1,16 @@
+#![crate_name = "foo"]
+
+/// Links to [Missing], [Bar::f#anchor] and [Bar].
+pub fn f() {}
+
+pub struct Bar {
+    pub f: u8,
+}
+
+/// Is it a struct or a function? [ambiguous]
+pub fn g() {}
+
+#[allow(non_camel_case_types)]
+pub struct ambiguous {}
+
+pub fn ambiguous() {}
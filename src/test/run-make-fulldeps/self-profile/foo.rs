@@ -0,0 +1,11 @@
+#![crate_type = "rlib"]
+
+pub fn foo(x: u32) -> u32 {
+    x + 1
+}
+
+pub mod bar {
+    pub fn bar(x: &[u32]) -> u32 {
+        x.iter().sum()
+    }
+}
@@ -0,0 +1,27 @@
+#!/usr/bin/env python
+
+# Checks that the file passed as the first argument is a trace event file with a `typeck_tables_of`
+# query event, and whose events were recorded on more than one thread.
+
+import sys
+import json
+
+with open(sys.argv[1]) as f:
+    trace = json.load(f)
+
+events = trace["traceEvents"]
+threads = set()
+names = set()
+for event in events:
+    assert event["ph"] in ("X", "M"), event
+    assert isinstance(event["pid"], int), event
+    assert isinstance(event["tid"], int), event
+    if event["ph"] == "X":
+        assert isinstance(event["ts"], (int, float)), event
+        assert isinstance(event["dur"], (int, float)), event
+        threads.add(event["tid"])
+        names.add(event["name"])
+
+assert "typeck_tables_of" in names, names
+# The LLVM work items run on the codegen threads, not on the main thread.
+assert len(threads) > 1, threads
@@ -0,0 +1,12 @@
+const QUERY = 'Vec<T> -> Option<T>';
+
+const EXPECTED = {
+    'in_args': [
+        { 'path': 'type_signature', 'name': 'pop_last' },
+        { 'path': 'type_signature', 'name': 'shared_first' },
+    ],
+    'returned': [
+        { 'path': 'type_signature', 'name': 'pop_last' },
+        { 'path': 'type_signature', 'name': 'shared_first' },
+    ],
+};
@@ -0,0 +1,23 @@
+use std::rc::Rc;
+
+pub struct Stack<T> {
+    items: Vec<T>,
+}
+
+impl<T> Stack<T> {
+    pub fn from_vec(items: Vec<T>) -> Option<Self> {
+        if items.is_empty() { None } else { Some(Stack { items }) }
+    }
+}
+
+pub fn pop_last<T>(v: &mut Vec<T>) -> Option<T> {
+    v.pop()
+}
+
+pub fn shared_first<T: Clone>(v: Rc<Vec<T>>) -> Option<T> {
+    v.first().cloned()
+}
+
+pub fn first_len<T>(v: &Vec<Vec<T>>) -> Option<usize> {
+    v.first().map(|v| v.len())
+}
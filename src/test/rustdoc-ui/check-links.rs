@@ -0,0 +1,19 @@
+// compile-flags: -Z unstable-options --check-links
+
+#![deny(broken_doc_links)]
+
+/// Points to [a missing page](struct.Missing.html).
+//~^ ERROR broken link
+pub struct Foo {
+    /// A field.
+    pub bar: u32,
+}
+
+/// See [the field](struct.Foo.html#structfield.baz)
+/// and [the method](struct.Foo.html#method.new).
+pub fn foo() {}
+//~^^^ ERROR broken link
+//~^^^ ERROR broken link
+
+/// Those are fine: [`Foo::bar`](struct.Foo.html#structfield.bar) and [`foo`](fn.foo.html).
+pub fn ok() {}
@@ -0,0 +1,10 @@
+#![deny(intra_doc_link_resolution_failure)]
+
+pub enum Foo {
+    X { y: u8 },
+    Z { w: u8 },
+}
+
+/// [Foo::X::w]
+//~^ ERROR `[Foo::X::w]` cannot be resolved
+pub fn foo() {}
@@ -0,0 +1,50 @@
+// compile-flags: -Z unstable-options --cfg-set foo --cfg-set bar --cfg-set bar,baz
+
+#![crate_name = "foo"]
+
+// @has foo/struct.Everywhere.html
+// @!has - '//*[@class="stab portability"]' ''
+pub struct Everywhere;
+
+// @has foo/fn.foo_only.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on foo only.'
+#[cfg(foo)]
+pub fn foo_only() {}
+
+// @has foo/fn.bar_only.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on bar only.'
+#[cfg(bar)]
+pub fn bar_only() {}
+
+// @has foo/fn.baz_only.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on baz only.'
+#[cfg(baz)]
+pub fn baz_only() {}
+
+// @has foo/fn.not_baz.html \
+//  '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' \
+//  'This is supported on non-baz only.'
+#[cfg(not(baz))]
+pub fn not_baz() {}
+
+// @has foo/struct.Split.html
+// @!has - '//*[@id="main"]/*[@class="stability"]/*[@class="stab portability"]' ''
+// @has - '//*[@class="stab portability"]' 'This is supported on foo only.'
+// @has - '//*[@class="stab portability"]' 'This is supported on bar only.'
+pub struct Split;
+
+impl Split {
+    #[cfg(foo)]
+    pub fn on_foo(&self) {}
+
+    #[cfg(bar)]
+    pub fn on_bar(&self) {}
+}
+
+// @has foo/index.html
+// @matches - '//*[@class="module-item"]//*[@class="stab portability"]' '\Afoo\Z'
+// @matches - '//*[@class="module-item"]//*[@class="stab portability"]' '\Abaz\Z'
+// @count - '//*[@class="module-item"]//*[@class="stab portability"]' 4
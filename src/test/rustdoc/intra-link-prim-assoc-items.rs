@@ -0,0 +1,12 @@
+#![deny(intra_doc_link_resolution_failure)]
+#![crate_name = "foo"]
+
+// Associated items of primitives are spread over impls in `core`, `alloc` and `std`.
+
+// @has foo/fn.f.html '//a/@href' 'https://doc.rust-lang.org/nightly/std/primitive.str.html#method.len'
+// @has foo/fn.f.html '//a/@href' 'https://doc.rust-lang.org/nightly/std/primitive.str.html#method.to_uppercase'
+// @has foo/fn.f.html '//a/@href' 'https://doc.rust-lang.org/nightly/std/primitive.f64.html#method.sqrt'
+// @has foo/fn.f.html '//a/@href' 'https://doc.rust-lang.org/nightly/std/primitive.u8.html#associatedconstant.MAX'
+
+/// See [`str::len`], [`str::to_uppercase`], [`f64::sqrt`] and [`u8::MAX`].
+pub fn f() {}
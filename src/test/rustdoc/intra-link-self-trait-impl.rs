@@ -0,0 +1,35 @@
+#![deny(intra_doc_link_resolution_failure)]
+#![crate_name = "foo"]
+
+pub mod inner {
+    pub struct Foo;
+
+    impl Foo {
+        pub fn new() -> Self {
+            Foo
+        }
+    }
+}
+
+pub trait Tr {
+    type Item;
+
+    /// Returns the next [`Self::Item`].
+    fn next(&mut self) -> Option<Self::Item>;
+}
+
+// The impl lives in a different module than `Foo`, so `Self` can't be found by name.
+
+// @has foo/inner/struct.Foo.html '//a/@href' '../../foo/inner/struct.Foo.html#associatedtype.Item'
+// @has foo/inner/struct.Foo.html '//a/@href' '../../foo/inner/struct.Foo.html#method.new'
+// @has foo/inner/struct.Foo.html '//a/@href' '../../foo/inner/struct.Foo.html'
+impl Tr for inner::Foo {
+    type Item = u8;
+
+    /// Yields a [`Self::Item`], see [`Self::new`] to create [`Self`].
+    fn next(&mut self) -> Option<Self::Item> {
+        None
+    }
+}
+
+// @has foo/trait.Tr.html '//a/@href' '../foo/trait.Tr.html#associatedtype.Item'
@@ -0,0 +1,28 @@
+#![deny(intra_doc_link_resolution_failure)]
+#![crate_name = "foo"]
+
+pub enum Foo {
+    X { y: u8 },
+    Z { w: u8 },
+}
+
+pub use self::Foo::*;
+
+// @has foo/fn.foo.html '//a/@href' '../foo/enum.Foo.html#variant.X.field.y'
+// @has foo/fn.foo.html '//a/@href' '../foo/enum.Foo.html#variant.Z.field.w'
+
+/// Links to [X::y] through the re-exported variant, and to [Foo::Z::w].
+pub fn foo() {}
+
+pub type Alias = Bar;
+
+pub struct Bar;
+
+impl Bar {
+    pub fn baz() {}
+}
+
+// @has foo/fn.bar.html '//a/@href' '../foo/struct.Bar.html#method.baz'
+
+/// Methods are found through type aliases: [Alias::baz].
+pub fn bar() {}
@@ -0,0 +1,19 @@
+// compile-flags: -Z unstable-options --generate-fulltext-index
+
+#![crate_name = "foo"]
+
+// @has 'search-index.js' '"f":1'
+// @has 'search-fulltext/foo.js' 'addFullTextIndex("foo"'
+// @has - '"frobnicates"'
+// @has - '"widgets"'
+// @!has - '"the"'
+// @!has - 'unrelated_code'
+
+/// Foo short summary.
+///
+/// The rest of these docs frobnicates the widgets.
+///
+/// ```
+/// let unrelated_code = 1;
+/// ```
+pub struct Foo;
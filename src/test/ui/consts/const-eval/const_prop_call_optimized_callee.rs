@@ -0,0 +1,22 @@
+// run-pass
+// revisions: default noinline
+//[noinline]compile-flags: -Zinline-mir=no
+
+// Tests that calls to a `const fn` can be const propagated after its MIR was optimized, here
+// because it was evaluated for a `const` item first.
+
+const fn double(x: u32) -> u32 {
+    x * 2
+}
+
+const fn square(x: u32) -> u32 {
+    double(x) * x / 2
+}
+
+const SQUARE: u32 = square(3);
+
+fn main() {
+    assert_eq!(SQUARE, 9);
+    assert_eq!(square(4), 16);
+    assert_eq!(double(square(2)), 8);
+}
@@ -0,0 +1,15 @@
+// build-fail
+
+// Tests that const propagation sees through calls to `const fn`s with constant arguments, the
+// known fields of aggregates and shared borrows, and reports the panics and overflows it finds.
+// Calls to local `const fn`s are only propagated when the MIR inliner is enabled, so this calls
+// the ones of `core`.
+
+fn main() {
+    let array = [0u32; 3];
+    array[2u8.wrapping_add(1) as usize]; //~ ERROR this operation will panic at runtime
+
+    let _ = (std::env::args().len(), 255u8).1 + 1; //~ ERROR this arithmetic operation will overflow
+
+    let _ = *&254u8.wrapping_add(1) + 1; //~ ERROR this arithmetic operation will overflow
+}
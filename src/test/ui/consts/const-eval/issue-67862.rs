@@ -0,0 +1,20 @@
+// run-pass
+// revisions: opt mir_opt_level_2
+//[opt]compile-flags: -O
+//[mir_opt_level_2]compile-flags: -O -Zmir-opt-level=2
+
+// Tests that const propagation through shared borrows doesn't assume the value of the elements of
+// an array returned by a call.
+
+#[inline(never)]
+fn make() -> [u32; 2] {
+    [1, 2]
+}
+
+fn main() {
+    let res = make();
+    match (&res[0], &res[1]) {
+        (&1, &2) => {}
+        _ => panic!("wrong elements"),
+    }
+}
@@ -0,0 +1,21 @@
+// run-fail
+// compile-flags: -O -Zmir-opt-level=2
+// error-pattern:index out of bounds
+
+// Tests that a bounds check is kept when the index is a copy of a checked local, which was
+// modified after the check.
+
+#[inline(never)]
+fn get(s: &[u32], i: usize) -> u32 {
+    let mut j = i;
+    if i < s.len() {
+        j += 1;
+        s[j]
+    } else {
+        0
+    }
+}
+
+fn main() {
+    get(&[1, 2], 1);
+}
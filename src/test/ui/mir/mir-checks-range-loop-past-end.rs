@@ -0,0 +1,19 @@
+// run-fail
+// compile-flags: -O -Zmir-opt-level=2
+// error-pattern:index out of bounds
+
+// Tests that the bounds check is kept in a `for` loop over a range which goes past the end of the
+// slice.
+
+#[inline(never)]
+fn sum(v: &[u32]) -> u32 {
+    let mut s = 0;
+    for i in 0..v.len() + 1 {
+        s += v[i];
+    }
+    s
+}
+
+fn main() {
+    sum(&[1, 2, 3]);
+}
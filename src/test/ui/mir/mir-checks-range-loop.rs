@@ -0,0 +1,19 @@
+// run-pass
+// compile-flags: -O -Zmir-opt-level=2
+
+// Tests indexing a slice in a `for` loop over the range of its indices, whose bounds checks are
+// kept by `RemoveRedundantChecks`.
+
+#[inline(never)]
+fn sum(v: &[u32]) -> u32 {
+    let mut s = 0;
+    for i in 0..v.len() {
+        s += v[i];
+    }
+    s
+}
+
+fn main() {
+    assert_eq!(sum(&[]), 0);
+    assert_eq!(sum(&[1, 2, 3]), 6);
+}
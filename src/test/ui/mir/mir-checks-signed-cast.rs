@@ -0,0 +1,19 @@
+// run-fail
+// compile-flags: -O -Zmir-opt-level=2
+// error-pattern:index out of bounds
+
+// Tests that a bounds check is kept when a signed index, which was only checked against the
+// length from above, is cast to `usize`.
+
+#[inline(never)]
+fn get(a: &[u32; 4], i: i64) -> u32 {
+    if i < 4 {
+        a[i as usize]
+    } else {
+        0
+    }
+}
+
+fn main() {
+    get(&[1, 2, 3, 4], -1);
+}
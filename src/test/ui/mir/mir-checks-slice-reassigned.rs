@@ -0,0 +1,23 @@
+// run-fail
+// compile-flags: -O -Zmir-opt-level=2
+// error-pattern:index out of bounds
+
+// Tests that a bounds check is kept when the slice it guards was reassigned in a loop, after the
+// index was compared with the length of the previous slice.
+
+#[inline(never)]
+fn sum(long: &[u32], short: &[u32]) -> u32 {
+    let mut s = long;
+    let mut total = 0;
+    let mut i = 0;
+    while i < long.len() {
+        total += s[i];
+        s = short;
+        i += 1;
+    }
+    total
+}
+
+fn main() {
+    sum(&[1, 2, 3], &[4]);
+}
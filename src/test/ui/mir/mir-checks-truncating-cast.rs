@@ -0,0 +1,19 @@
+// run-fail
+// compile-flags: -O -Zmir-opt-level=2
+// error-pattern:index out of bounds
+
+// Tests that a bounds check is kept when only a truncated copy of the index was compared with
+// the length.
+
+#[inline(never)]
+fn get(s: &[u32], i: usize) -> u32 {
+    if (i as u8 as usize) < s.len() {
+        s[i]
+    } else {
+        0
+    }
+}
+
+fn main() {
+    get(&[1, 2, 3], 256);
+}
@@ -0,0 +1,50 @@
+// run-pass
+// compile-flags: -O -Zmir-opt-level=2
+
+// Tests that dead store elimination keeps the payload of an enum variant, which is written before
+// its discriminant is set, whether the enum is built in a local or in the return place.
+
+enum E {
+    A(u32, u64),
+    B(u8),
+    C,
+}
+
+#[inline(never)]
+fn through_return(x: u32) -> Option<u32> {
+    Some(x + 1)
+}
+
+#[inline(never)]
+fn through_local(x: u32) -> Option<u32> {
+    let o = Some(x + 1);
+    o
+}
+
+#[inline(never)]
+fn through_locals(c: u8, x: u32) -> E {
+    let e = match c {
+        0 => E::A(x, x as u64 * 2),
+        1 => E::B(x as u8),
+        _ => E::C,
+    };
+    let f = e;
+    f
+}
+
+fn main() {
+    assert_eq!(through_return(1), Some(2));
+    assert_eq!(through_local(2), Some(3));
+    match through_locals(0, 3) {
+        E::A(a, b) => assert_eq!((a, b), (3, 6)),
+        _ => panic!(),
+    }
+    match through_locals(1, 4) {
+        E::B(b) => assert_eq!(b, 4),
+        _ => panic!(),
+    }
+    match through_locals(2, 5) {
+        E::C => {}
+        _ => panic!(),
+    }
+}
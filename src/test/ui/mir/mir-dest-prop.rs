@@ -0,0 +1,69 @@
+// run-pass
+// compile-flags: -O
+
+// Tests that destination propagation keeps apart the locals which can't share their storage, and
+// that the values moved through the locals it merges are unchanged.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Pair {
+    x: u64,
+    y: u64,
+}
+
+struct Tagged {
+    tag: u64,
+    data: [u64; 8],
+}
+
+#[inline(never)]
+fn make_tagged(tag: u64) -> Tagged {
+    Tagged { tag, data: [tag; 8] }
+}
+
+#[inline(never)]
+fn sum(tagged: &Tagged) -> u64 {
+    tagged.data.iter().sum()
+}
+
+fn moved_through(tag: u64) -> Tagged {
+    let a = make_tagged(tag);
+    let b = a;
+    let c = b;
+    c
+}
+
+fn borrowed(tag: u64) -> (u64, u64) {
+    let mut a = make_tagged(tag);
+    let first = sum(&a);
+    let b = a;
+    a = make_tagged(tag + 1);
+    (first + sum(&a), sum(&b))
+}
+
+fn read_after_copy(pair: Pair) -> (Pair, u64) {
+    let a = pair;
+    let mut b = a;
+    b.x = 3;
+    (b, a.x)
+}
+
+fn loop_carried(n: usize) -> (u64, u64) {
+    let mut prev = make_tagged(0);
+    let mut cur = make_tagged(1);
+    let mut next;
+    let mut i = 0;
+    while i < n {
+        next = make_tagged(prev.tag + cur.tag);
+        prev = cur;
+        cur = next;
+        i += 1;
+    }
+    (prev.tag, cur.tag)
+}
+
+fn main() {
+    assert_eq!(sum(&moved_through(2)), 16);
+    assert_eq!(borrowed(1), (24, 8));
+    assert_eq!(read_after_copy(Pair { x: 1, y: 2 }), (Pair { x: 3, y: 2 }, 1));
+    assert_eq!(loop_carried(10), (55, 89));
+}
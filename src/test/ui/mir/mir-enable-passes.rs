@@ -0,0 +1,17 @@
+// run-pass
+// compile-flags:-Zmir-enable-passes=+Inline,+CopyPropagation,-ConstProp,-SimplifyCfg-final
+
+// Tests that MIR optimizations can be turned on and off individually, whatever the MIR
+// optimization level, and that the result still runs correctly.
+
+#[inline]
+fn add(x: u32, y: u32) -> u32 {
+    let z = x + y;
+    z
+}
+
+fn main() {
+    let a = add(1, 2);
+    let b = if a > 2 { add(a, 3) } else { 0 };
+    assert_eq!(b, 6);
+}
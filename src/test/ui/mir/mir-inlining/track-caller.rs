@@ -0,0 +1,41 @@
+// run-pass
+// compile-flags:-Zmir-opt-level=2
+
+// Tests that `#[track_caller]` functions can be inlined in MIR, and still
+// report the location of their (possibly inlined) caller.
+
+#![feature(track_caller)]
+
+use std::panic::Location;
+
+#[inline(always)]
+#[track_caller]
+fn tracked() -> &'static Location<'static> {
+    Location::caller()
+}
+
+#[inline(always)]
+#[track_caller]
+fn nested_tracked() -> &'static Location<'static> {
+    tracked()
+}
+
+#[inline(always)]
+fn untracked() -> &'static Location<'static> {
+    tracked()
+}
+
+fn main() {
+    let location = tracked();
+    assert_eq!(location.file(), file!());
+    assert_eq!(location.line(), 29);
+    assert_eq!(location.column(), 20);
+
+    let location = nested_tracked();
+    assert_eq!(location.line(), 34);
+    assert_eq!(location.column(), 20);
+
+    let location = untracked();
+    assert_eq!(location.line(), 25);
+    assert_eq!(location.column(), 5);
+}
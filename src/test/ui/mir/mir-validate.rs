@@ -0,0 +1,48 @@
+// run-pass
+// compile-flags:-Zvalidate-mir -Zmir-opt-level=3
+
+// Tests that the MIR of functions with drops, moves, closures and generators stays valid
+// through all the MIR optimizations.
+
+#![feature(generators, generator_trait)]
+
+use std::ops::{Generator, GeneratorState};
+use std::pin::Pin;
+
+struct Noisy(Vec<u32>);
+
+impl Drop for Noisy {
+    fn drop(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[inline]
+fn take(n: Noisy) -> usize {
+    n.0.len()
+}
+
+fn moves(c: bool) -> usize {
+    let a = Noisy(vec![1, 2, 3]);
+    let b = Noisy(vec![4]);
+    let r = if c { take(a) } else { take(b) };
+    let (x, _y) = (Noisy(vec![5, 6]), Noisy(vec![]));
+    r + take(x)
+}
+
+fn main() {
+    assert_eq!(moves(true), 5);
+    assert_eq!(moves(false), 3);
+
+    let v = vec![1u32, 2, 3];
+    let sum = move |k: u32| v.iter().map(|x| x * k).sum::<u32>();
+    assert_eq!(sum(2), 12);
+
+    let mut gen = |x: u32| {
+        let n = Noisy(vec![x]);
+        let y = yield n.0[0];
+        y + take(n) as u32
+    };
+    assert_eq!(Pin::new(&mut gen).resume(7), GeneratorState::Yielded(7));
+    assert_eq!(Pin::new(&mut gen).resume(1), GeneratorState::Complete(2));
+}
@@ -0,0 +1,36 @@
+// Test that the borrow checking errors of many item bodies are reported in the order of their
+// spans, whichever thread checked each body.
+
+// compile-flags: -Z threads=4
+
+fn f0() {
+    let x: u32;
+    let _y = x; //~ ERROR use of possibly-uninitialized variable: `x`
+}
+
+fn f1() {
+    let x: u32;
+    let _y = x; //~ ERROR use of possibly-uninitialized variable: `x`
+}
+
+fn f2() {
+    let x: u32;
+    let _y = x; //~ ERROR use of possibly-uninitialized variable: `x`
+}
+
+fn f3() {
+    let x: u32;
+    let _y = x; //~ ERROR use of possibly-uninitialized variable: `x`
+}
+
+fn f4() {
+    let x: u32;
+    let _y = x; //~ ERROR use of possibly-uninitialized variable: `x`
+}
+
+fn f5() {
+    let x: u32;
+    let _y = x; //~ ERROR use of possibly-uninitialized variable: `x`
+}
+
+fn main() {}
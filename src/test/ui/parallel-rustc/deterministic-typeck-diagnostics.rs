@@ -0,0 +1,30 @@
+// Test that the type errors of many item bodies are reported in the order of their spans,
+// whichever thread checked each body.
+
+// compile-flags: -Z threads=4
+
+fn f0() {
+    let _: u32 = "0"; //~ ERROR mismatched types
+}
+
+fn f1() {
+    let _: u32 = "1"; //~ ERROR mismatched types
+}
+
+fn f2() {
+    let _: u32 = "2"; //~ ERROR mismatched types
+}
+
+fn f3() {
+    let _: u32 = "3"; //~ ERROR mismatched types
+}
+
+fn f4() {
+    let _: u32 = "4"; //~ ERROR mismatched types
+}
+
+fn f5() {
+    let _: u32 = "5"; //~ ERROR mismatched types
+}
+
+fn main() {}